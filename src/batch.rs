@@ -0,0 +1,160 @@
+use std::io::{Error, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use crate::Parser;
+
+/// Packets queued for a single stream, written together with one `write_all` on [`flush`](Batch::flush).
+///
+/// Writing a burst at once saves syscalls and keeps it contiguous on the wire. Anything still queued when the
+/// batch is dropped is flushed then; since `Drop` cannot return an error, call `flush` to see whether it worked.
+///
+/// ```no_run
+/// use lurk_protocol::{Batch, PktMessage};
+/// use std::net::TcpStream;
+/// use std::sync::Arc;
+///
+/// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+/// let mut batch = Batch::new(&stream);
+///
+/// batch.push(&PktMessage::narrator("Player", "The door creaks open.")).unwrap();
+/// batch.push(&PktMessage::narrator("Player", "A draft chills the room.")).unwrap();
+///
+/// batch.flush().unwrap();
+/// ```
+pub struct Batch {
+    stream: Arc<TcpStream>,
+    buffer: Vec<u8>,
+    count: usize,
+}
+
+impl Batch {
+    /// Create an empty batch for the given stream.
+    pub fn new(stream: &Arc<TcpStream>) -> Self {
+        Self {
+            stream: stream.clone(),
+            buffer: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Serialize a packet onto the end of the batch.
+    ///
+    /// Nothing is queued if serialization fails.
+    pub fn push<'a>(&mut self, packet: &impl Parser<'a>) -> Result<(), Error> {
+        let len = self.buffer.len();
+
+        if let Err(e) = packet.write_to(&mut self.buffer) {
+            self.buffer.truncate(len);
+            return Err(e);
+        }
+
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// The number of queued packets.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether no packets are queued.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Discard every queued packet without sending it.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.count = 0;
+    }
+
+    /// Write every queued packet to the stream and empty the batch.
+    ///
+    /// The batch is emptied even if the write fails, as it is unknown how much reached the peer.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::info!("Sending batch of {} packets", self.count);
+
+        let result = self
+            .stream
+            .as_ref()
+            .write_all(&self.buffer)
+            .map_err(crate::disconnected);
+        self.clear();
+
+        result
+    }
+}
+
+impl Drop for Batch {
+    fn drop(&mut self) {
+        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+        if let Err(e) = self.flush() {
+            #[cfg(feature = "tracing")]
+            tracing::error!("Failed to flush batch on drop: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_common::socket_pair;
+    use crate::{PktChangeRoom, Protocol};
+
+    /// Queued packets are written in order on flush.
+    #[test]
+    fn batch_flush() {
+        let (client, server) = socket_pair();
+        let mut batch = Batch::new(&client);
+
+        batch.push(&PktChangeRoom::from(1u16)).expect("Push failed");
+        batch.push(&PktChangeRoom::from(2u16)).expect("Push failed");
+        assert_eq!(batch.len(), 2);
+
+        batch.flush().expect("Flush failed");
+        assert!(batch.is_empty());
+
+        for expected in [1, 2] {
+            let room = Protocol::recv(&server).expect("Recv failed");
+            assert_eq!(room.as_change_room().map(|c| c.room_number), Some(expected));
+        }
+    }
+
+    /// Dropping a batch sends whatever was still queued.
+    #[test]
+    fn batch_flush_on_drop() {
+        let (client, server) = socket_pair();
+
+        {
+            let mut batch = Batch::new(&client);
+            batch.push(&PktChangeRoom::from(9u16)).expect("Push failed");
+        }
+
+        let room = Protocol::recv(&server).expect("Recv failed");
+        assert_eq!(room.as_change_room().map(|c| c.room_number), Some(9));
+    }
+
+    /// Cleared packets are never sent.
+    #[test]
+    fn batch_clear() {
+        let (client, server) = socket_pair();
+        let mut batch = Batch::new(&client);
+
+        batch.push(&PktChangeRoom::from(1u16)).expect("Push failed");
+        batch.clear();
+        assert!(batch.is_empty());
+
+        batch.push(&PktChangeRoom::from(2u16)).expect("Push failed");
+        drop(batch);
+
+        let room = Protocol::recv(&server).expect("Recv failed");
+        assert_eq!(room.as_change_room().map(|c| c.room_number), Some(2));
+    }
+}
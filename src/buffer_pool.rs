@@ -0,0 +1,87 @@
+use std::sync::Mutex;
+
+/// A shared pool of receive buffers for [`Protocol::recv_pooled`](crate::Protocol::recv_pooled).
+///
+/// Buffers are handed out for the duration of one receive and returned afterwards, so their allocations are
+/// reused across every connection sharing the pool. At most `capacity` idle buffers are kept; extras are freed.
+///
+/// ```
+/// use lurk_protocol::BufferPool;
+///
+/// let pool = BufferPool::new(16);
+/// assert_eq!(pool.idle(), 0);
+/// ```
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    capacity: usize,
+}
+
+impl BufferPool {
+    /// Create an empty pool that keeps up to `capacity` idle buffers.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// The number of idle buffers currently held.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Borrow a buffer, allocating a new one if none are idle.
+    pub(crate) fn take(&self) -> Vec<u8> {
+        self.lock().pop().unwrap_or_default()
+    }
+
+    /// Return a buffer to the pool, or free it if the pool is full.
+    pub(crate) fn give(&self, buffer: Vec<u8>) {
+        let mut buffers = self.lock();
+
+        if buffers.len() < self.capacity {
+            buffers.push(buffer);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        // A poisoned lock only means another thread panicked; the buffers are still usable
+        self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for BufferPool {
+    /// A pool keeping up to 32 idle buffers.
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A returned buffer is handed out again, allocation included.
+    #[test]
+    fn buffer_pool_reuses_buffers() {
+        let pool = BufferPool::new(1);
+        let mut buffer = pool.take();
+        buffer.reserve(1024);
+        let capacity = buffer.capacity();
+
+        pool.give(buffer);
+        assert_eq!(pool.idle(), 1);
+        assert_eq!(pool.take().capacity(), capacity);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    /// Buffers beyond the capacity are dropped rather than kept.
+    #[test]
+    fn buffer_pool_capacity() {
+        let pool = BufferPool::new(1);
+        pool.give(Vec::new());
+        pool.give(Vec::new());
+
+        assert_eq!(pool.idle(), 1);
+    }
+}
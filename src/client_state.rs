@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+
+use crate::PktType;
+
+/// The phases a Lurk client moves through over the life of a connection.
+///
+/// ```text
+/// Connected --recv GAME--> Handshaken --send CHARACTER--> CharacterSent --send START--> InGame
+/// ```
+///
+/// Sending `LEAVE` from any phase moves to `Left`, after which nothing more may be sent.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientState {
+    #[default]
+    /// Connected, waiting for the server's `VERSION` and `GAME`.
+    Connected,
+    /// The server's `GAME` has arrived; the client may describe its character.
+    Handshaken,
+    /// A `CHARACTER` has been sent. It may be re-sent if the server rejects it, or the client may `START`.
+    CharacterSent,
+    /// `START` has been sent and the player is in the game world.
+    InGame,
+    /// `LEAVE` has been sent.
+    Left,
+}
+
+impl ClientState {
+    /// Whether a client in this state may send a packet of the given type.
+    pub(crate) fn allows(&self, pkt_type: PktType) -> bool {
+        match (self, pkt_type) {
+            (ClientState::Left, _) => false,
+            (_, PktType::LEAVE) => true,
+            (ClientState::Handshaken | ClientState::CharacterSent, PktType::CHARACTER) => true,
+            (ClientState::CharacterSent, PktType::START) => true,
+            (
+                ClientState::InGame,
+                PktType::MESSAGE
+                | PktType::CHANGEROOM
+                | PktType::FIGHT
+                | PktType::PVPFIGHT
+                | PktType::LOOT,
+            ) => true,
+            _ => false,
+        }
+    }
+
+    /// Check that a client in this state may send a packet of the given type.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] naming the packet and the state, e.g. a `FIGHT` before `START`. A
+    /// [`Connection`](crate::Connection) tracking its state runs this check on every send.
+    ///
+    /// ```
+    /// use lurk_protocol::{ClientState, PktType};
+    ///
+    /// assert!(ClientState::InGame.can_send(PktType::FIGHT).is_ok());
+    ///
+    /// let err = ClientState::Handshaken.can_send(PktType::FIGHT).unwrap_err();
+    /// assert_eq!(err.to_string(), "Cannot send a Fight packet while Handshaken");
+    /// ```
+    pub fn can_send(&self, pkt_type: PktType) -> Result<(), Error> {
+        if !self.allows(pkt_type) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Cannot send a {} packet while {}", pkt_type, self),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The state after successfully sending a packet of the given type.
+    pub(crate) fn after_send(self, pkt_type: PktType) -> Self {
+        match pkt_type {
+            PktType::LEAVE => ClientState::Left,
+            PktType::CHARACTER => ClientState::CharacterSent,
+            PktType::START => ClientState::InGame,
+            _ => self,
+        }
+    }
+
+    /// The state after receiving a packet of the given type.
+    pub(crate) fn after_recv(self, pkt_type: PktType) -> Self {
+        match (self, pkt_type) {
+            (ClientState::Connected, PktType::GAME) => ClientState::Handshaken,
+            _ => self,
+        }
+    }
+}
+
+impl std::fmt::Display for ClientState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientState::Connected => write!(f, "Connected"),
+            ClientState::Handshaken => write!(f, "Handshaken"),
+            ClientState::CharacterSent => write!(f, "CharacterSent"),
+            ClientState::InGame => write!(f, "InGame"),
+            ClientState::Left => write!(f, "Left"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_state_happy_path() {
+        let state = ClientState::default()
+            .after_recv(PktType::VERSION)
+            .after_recv(PktType::GAME);
+        assert_eq!(state, ClientState::Handshaken);
+        assert!(state.allows(PktType::CHARACTER));
+
+        let state = state.after_send(PktType::CHARACTER);
+        assert_eq!(state, ClientState::CharacterSent);
+        assert!(state.allows(PktType::START));
+
+        let state = state.after_send(PktType::START);
+        assert_eq!(state, ClientState::InGame);
+        assert!(state.allows(PktType::FIGHT));
+        assert!(state.allows(PktType::MESSAGE));
+    }
+
+    #[test]
+    fn client_state_fight_before_start() {
+        assert!(!ClientState::Connected.allows(PktType::FIGHT));
+        assert!(!ClientState::Handshaken.allows(PktType::FIGHT));
+        assert!(!ClientState::CharacterSent.allows(PktType::FIGHT));
+    }
+
+    #[test]
+    fn client_state_start_without_character() {
+        assert!(!ClientState::Connected.allows(PktType::START));
+        assert!(!ClientState::Handshaken.allows(PktType::START));
+    }
+
+    #[test]
+    fn client_state_character_after_start() {
+        assert!(!ClientState::InGame.allows(PktType::CHARACTER));
+    }
+
+    #[test]
+    fn client_state_leave_always_allowed_until_left() {
+        for state in [
+            ClientState::Connected,
+            ClientState::Handshaken,
+            ClientState::CharacterSent,
+            ClientState::InGame,
+        ] {
+            assert!(state.allows(PktType::LEAVE), "LEAVE rejected in {}", state);
+            assert_eq!(state.after_send(PktType::LEAVE), ClientState::Left);
+        }
+
+        assert!(!ClientState::Left.allows(PktType::LEAVE));
+    }
+
+    #[test]
+    fn client_state_server_packets_never_allowed() {
+        for pkt_type in [
+            PktType::ERROR,
+            PktType::ACCEPT,
+            PktType::ROOM,
+            PktType::GAME,
+            PktType::CONNECTION,
+            PktType::VERSION,
+        ] {
+            assert!(
+                !ClientState::InGame.allows(pkt_type),
+                "{} allowed",
+                pkt_type
+            );
+        }
+    }
+
+    #[test]
+    fn client_state_can_send_reports_transition() {
+        assert!(ClientState::CharacterSent.can_send(PktType::START).is_ok());
+
+        let err = ClientState::InGame
+            .can_send(PktType::CHARACTER)
+            .expect_err("CHARACTER after START must be rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(
+            err.to_string(),
+            "Cannot send a Character packet while InGame"
+        );
+    }
+}
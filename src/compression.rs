@@ -0,0 +1,133 @@
+use std::io::{Error, ErrorKind, Read, Write};
+
+use flate2::Compression;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+use crate::{PktType, Protocol};
+
+/// The extension advertised in `VERSION` by peers that accept zlib compressed descriptions.
+///
+/// Once both peers advertise it, the description of every `CHARACTER`, `ROOM`, `GAME`, and `CONNECTION` packet
+/// is sent compressed, with its length field holding the compressed length. A [`Connection`](crate::Connection)
+/// does this transparently; see [`Connection::negotiated_extensions`](crate::Connection::negotiated_extensions).
+pub const EXTENSION: &[u8] = b"compress";
+
+/// Whether packets of this type carry a compressible description.
+fn has_description(packet_type: PktType) -> bool {
+    matches!(
+        packet_type,
+        PktType::CHARACTER | PktType::ROOM | PktType::GAME | PktType::CONNECTION
+    )
+}
+
+/// Replace the description of an encoded packet (type byte included) using `transform`, updating its length field.
+fn rewrite_description(
+    bytes: &mut Vec<u8>,
+    transform: impl FnOnce(&[u8]) -> Result<Vec<u8>, Error>,
+) -> Result<(), Error> {
+    let packet_type = PktType::from(bytes[0]);
+
+    if !has_description(packet_type) {
+        return Ok(());
+    }
+
+    let Some((fixed_len, Some((lo, hi)))) = Protocol::body_layout(packet_type) else {
+        return Ok(());
+    };
+
+    let start = 1 + fixed_len;
+    let description = transform(&bytes[start..])?;
+    let length = u16::try_from(description.len())
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "Description is too long"))?
+        .to_le_bytes();
+
+    bytes[1 + lo] = length[0];
+    bytes[1 + hi] = length[1];
+    bytes.truncate(start);
+    bytes.extend(description);
+
+    Ok(())
+}
+
+/// Compress the description of an encoded packet in place. Packets without a description are left untouched.
+pub(crate) fn deflate(bytes: &mut Vec<u8>) -> Result<(), Error> {
+    rewrite_description(bytes, |description| {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(description)?;
+        encoder.finish()
+    })
+}
+
+/// Decompress the description of a received packet in place. Packets without a description are left untouched.
+pub(crate) fn inflate(bytes: &mut Vec<u8>) -> Result<(), Error> {
+    rewrite_description(bytes, |description| {
+        let mut inflated = Vec::new();
+
+        // Read one byte past the limit so an oversized description is reported rather than truncated
+        ZlibDecoder::new(description)
+            .take(u16::MAX as u64 + 1)
+            .read_to_end(&mut inflated)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        Ok(inflated)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parser, PktRoom};
+
+    fn room(description: &str) -> PktRoom {
+        PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: 1,
+            room_name: "Library".into(),
+            description_len: description.len() as u16,
+            description: description.into(),
+        }
+    }
+
+    /// A compressed description shrinks on the wire and inflates back to the original packet.
+    #[test]
+    fn compression_roundtrip() {
+        let original = room(&"Dusty shelves line the walls. ".repeat(40))
+            .encode_to_vec()
+            .expect("Encoding failed");
+
+        let mut bytes = original.clone();
+        deflate(&mut bytes).expect("Deflate failed");
+        assert!(bytes.len() < original.len());
+        assert_eq!(
+            u16::from_le_bytes([bytes[35], bytes[36]]) as usize,
+            bytes.len() - 37
+        );
+
+        inflate(&mut bytes).expect("Inflate failed");
+        assert_eq!(bytes, original);
+    }
+
+    /// Packets without a description pass through unchanged.
+    #[test]
+    fn compression_skips_other_packets() {
+        let original = crate::PktMessage::server("Player", "Hello")
+            .encode_to_vec()
+            .expect("Encoding failed");
+
+        let mut bytes = original.clone();
+        deflate(&mut bytes).expect("Deflate failed");
+        assert_eq!(bytes, original);
+    }
+
+    /// A description that is not valid zlib data is rejected.
+    #[test]
+    fn compression_invalid_data() {
+        let mut bytes = room("not compressed")
+            .encode_to_vec()
+            .expect("Encoding failed");
+
+        let err = inflate(&mut bytes).expect_err("Inflate must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,1207 @@
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind, Read};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{ClientState, ExtensionSet, Parser, PktType, Protocol, SendConfig};
+
+/// Which way a packet passed to a [`Connection::set_tap`] callback was travelling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Written to the peer.
+    Sent,
+    /// Read from the peer.
+    Received,
+}
+
+/// A callback observing the raw bytes of every packet on a connection.
+type Tap = Box<dyn FnMut(Direction, &[u8]) + Send>;
+
+/// A connected peer, wrapping the shared [`TcpStream`] used to send and receive packets.
+///
+/// ```no_run
+/// use lurk_protocol::{Connection, PktMessage, Protocol};
+/// use std::net::TcpStream;
+///
+/// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+///
+/// conn.send(&PktMessage::server("Player", "Welcome!")).unwrap();
+///
+/// if let Protocol::Start(_) = conn.recv().unwrap() {
+///     // Handle start packet
+/// }
+/// ```
+///
+/// A server can attach its own per-connection state as the context `C`, see [`Connection::with_context`].
+pub struct Connection<C = ()> {
+    stream: Arc<TcpStream>,
+    peer: Option<SocketAddr>,
+    tap: Option<Arc<Mutex<Tap>>>,
+    last_sent: Option<Protocol>,
+    state: Option<ClientState>,
+    extensions: ExtensionSet,
+    negotiated: ExtensionSet,
+    send_config: SendConfig,
+    pending: Vec<u8>,
+    deferred: Option<Error>,
+    context: C,
+}
+
+impl Connection {
+    /// Create a new `Connection` that takes ownership of the stream.
+    pub fn new(stream: TcpStream) -> Self {
+        Self::from(Arc::new(stream))
+    }
+}
+
+impl<C> Connection<C> {
+    /// Attach per-connection state, such as the player's id or inventory, replacing any previous context.
+    ///
+    /// The state then travels with the connection handle instead of living in a side table keyed by address.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Connection;
+    /// use std::net::TcpStream;
+    ///
+    /// struct Player {
+    ///     room: u16,
+    /// }
+    ///
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap())
+    ///     .with_context(Player { room: 1 });
+    ///
+    /// conn.context_mut().room = 2;
+    /// assert_eq!(conn.context().room, 2);
+    /// ```
+    pub fn with_context<D>(self, context: D) -> Connection<D> {
+        Connection {
+            stream: self.stream,
+            peer: self.peer,
+            tap: self.tap,
+            last_sent: self.last_sent,
+            state: self.state,
+            extensions: self.extensions,
+            negotiated: self.negotiated,
+            send_config: self.send_config,
+            pending: self.pending,
+            deferred: self.deferred,
+            context,
+        }
+    }
+
+    /// The state attached with [`with_context`](Connection::with_context).
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Mutable access to the state attached with [`with_context`](Connection::with_context).
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// The underlying stream, for use with [`Protocol::recv`] and the `send_*!` macros.
+    pub fn stream(&self) -> &Arc<TcpStream> {
+        &self.stream
+    }
+
+    /// Time out every subsequent [`recv`](Connection::recv) after waiting `timeout` for data.
+    ///
+    /// A timed out receive fails with [`ErrorKind::TimedOut`] on every platform. If the timeout hits partway
+    /// through a packet the rest of it is still in flight, so the connection should be closed.
+    ///
+    /// Fails if `timeout` is zero.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Connection;
+    /// use std::net::TcpStream;
+    /// use std::time::Duration;
+    ///
+    /// let conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap())
+    ///     .with_read_timeout(Duration::from_secs(30))?
+    ///     .with_write_timeout(Duration::from_secs(5))?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn with_read_timeout(self, timeout: Duration) -> Result<Self, Error> {
+        self.stream.set_read_timeout(Some(timeout))?;
+        Ok(self)
+    }
+
+    /// Time out every subsequent [`send`](Connection::send) that cannot finish writing within `timeout`.
+    ///
+    /// A timed out send fails with [`ErrorKind::TimedOut`]. Fails if `timeout` is zero.
+    pub fn with_write_timeout(self, timeout: Duration) -> Result<Self, Error> {
+        self.stream.set_write_timeout(Some(timeout))?;
+        Ok(self)
+    }
+
+    /// Serialize every packet sent through this connection, or its [`WriteHalf`], with the given options.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Connection, NamePolicy, SendConfig};
+    /// use std::net::TcpStream;
+    ///
+    /// // Refuse to send names that would be cut short
+    /// let conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap()).with_send_config(SendConfig {
+    ///     name_policy: NamePolicy::Error,
+    /// });
+    /// ```
+    pub fn with_send_config(mut self, config: SendConfig) -> Self {
+        self.send_config = config;
+        self
+    }
+
+    /// Call `tap` with the raw bytes of every packet sent or received, type byte included.
+    ///
+    /// The bytes are exactly those on the wire, so negotiated transforms such as compression have been applied.
+    /// Sent packets are reported once fully written, received ones before they are decoded. The tap is shared by
+    /// both halves after a [`split`](Connection::split). Replaces any previous tap.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Connection, Direction};
+    /// use std::net::TcpStream;
+    ///
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// conn.set_tap(Box::new(|direction, bytes| {
+    ///     let arrow = if direction == Direction::Sent { "->" } else { "<-" };
+    ///     println!("{} {:02X?}", arrow, bytes);
+    /// }));
+    /// ```
+    pub fn set_tap(&mut self, tap: Box<dyn FnMut(Direction, &[u8]) + Send>) {
+        self.tap = Some(Arc::new(Mutex::new(tap)));
+    }
+
+    /// Enforce the client side of the protocol on this connection.
+    ///
+    /// Every send is checked against the current [`ClientState`], and packets that the client may not send yet
+    /// (e.g. `FIGHT` before `START`) are rejected with [`ErrorKind::InvalidInput`] instead of being written.
+    /// Servers should leave this off.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{ClientState, Connection, PktFight};
+    /// use std::net::TcpStream;
+    ///
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap()).track_client_state();
+    ///
+    /// assert_eq!(conn.state(), Some(ClientState::Connected));
+    /// assert!(conn.send(&PktFight::default()).is_err());
+    /// ```
+    pub fn track_client_state(mut self) -> Self {
+        self.state = Some(ClientState::default());
+        self
+    }
+
+    /// The current client state, or `None` unless [`track_client_state`](Connection::track_client_state) was used.
+    pub fn state(&self) -> Option<ClientState> {
+        self.state
+    }
+
+    /// Advertise support for the given extensions.
+    ///
+    /// When the peer's `VERSION` arrives, the extensions both sides support become the
+    /// [`negotiated_extensions`](Connection::negotiated_extensions). Send our own list with
+    /// [`ExtensionSet::to_version`].
+    pub fn with_extensions(mut self, extensions: ExtensionSet) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// The extensions supported by both peers, empty until the peer's `VERSION` has been received.
+    ///
+    /// Optional features should only be turned on when they appear here.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Connection, ExtensionSet};
+    /// use std::net::TcpStream;
+    ///
+    /// let supported: ExtensionSet = ["SEQ"].into_iter().collect();
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap())
+    ///     .with_extensions(supported.clone());
+    ///
+    /// let _version = conn.recv().unwrap();
+    /// conn.send(&supported.to_version(2, 3).unwrap()).unwrap();
+    ///
+    /// if conn.negotiated_extensions().contains(b"SEQ") {
+    ///     // Both sides agreed to use sequence numbers
+    /// }
+    /// ```
+    pub fn negotiated_extensions(&self) -> &ExtensionSet {
+        &self.negotiated
+    }
+
+    /// Receive one packet from the peer.
+    pub fn recv(&mut self) -> Result<Protocol, Error> {
+        if let Some(error) = self.deferred.take() {
+            return Err(error);
+        }
+
+        if !self.pending.is_empty() {
+            // Finish the packet left partway through by drain_available before reading any further
+            let frame = loop {
+                if let Some(frame) = self.take_frame()? {
+                    break frame;
+                }
+
+                let mut chunk = [0; 4096];
+                match (&*self.stream).read(&mut chunk).map_err(timed_out)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "Peer closed the connection",
+                        ));
+                    }
+                    n => self.pending.extend_from_slice(&chunk[..n]),
+                }
+            };
+
+            let packet = decode_frame(frame, &self.negotiated, self.tap.as_deref())?;
+            return Ok(self.received(packet));
+        }
+
+        let packet =
+            recv_with(&self.stream, &self.negotiated, self.tap.as_deref()).map_err(timed_out)?;
+
+        Ok(self.received(packet))
+    }
+
+    /// Receive every packet that has already arrived, without waiting for more.
+    ///
+    /// Reads whatever the peer has sent so far and returns the complete packets in order, possibly none. A packet
+    /// that has only partly arrived is kept and completed by the next call, or by [`recv`](Connection::recv).
+    /// Intended for clients that poll once per frame. Bytes still buffered are discarded by
+    /// [`split`](Connection::split) and [`reconnect`](Connection::reconnect).
+    ///
+    /// Fails with [`ErrorKind::UnexpectedEof`] once the peer has closed the connection and every complete packet
+    /// has been returned. If a packet cannot be decoded, the packets before it are still returned and the error
+    /// is reported by the next call to this or [`recv`](Connection::recv).
+    ///
+    /// The stream is switched to non-blocking mode while it is read. That mode belongs to the socket, not to this
+    /// handle, so any other holder of the shared stream, such as a `send_*!` macro on another thread, may see
+    /// [`ErrorKind::WouldBlock`] in the meantime. Do not use the stream elsewhere while draining.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Connection;
+    /// use std::net::TcpStream;
+    ///
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// loop {
+    ///     for packet in conn.drain_available().unwrap() {
+    ///         println!("{}", packet);
+    ///     }
+    ///
+    ///     // Draw the frame
+    /// }
+    /// ```
+    pub fn drain_available(&mut self) -> Result<Vec<Protocol>, Error> {
+        if let Some(error) = self.deferred.take() {
+            return Err(error);
+        }
+
+        self.stream.set_nonblocking(true)?;
+        let closed = self.fill_pending();
+        self.stream.set_nonblocking(false)?;
+        let closed = closed?;
+
+        let mut packets = Vec::new();
+
+        loop {
+            let decoded = self.take_frame().and_then(|frame| {
+                frame
+                    .map(|frame| decode_frame(frame, &self.negotiated, self.tap.as_deref()))
+                    .transpose()
+            });
+
+            match decoded {
+                Ok(Some(packet)) => packets.push(self.received(packet)),
+                Ok(None) => break,
+                Err(error) if packets.is_empty() => return Err(error),
+                Err(error) => {
+                    // The packets before the bad one have already updated the state, so hand them over
+                    self.deferred = Some(error);
+                    return Ok(packets);
+                }
+            }
+        }
+
+        if closed && packets.is_empty() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Peer closed the connection",
+            ));
+        }
+
+        Ok(packets)
+    }
+
+    /// Read everything available on the non-blocking stream into the pending buffer. Returns whether the peer
+    /// has closed the connection.
+    fn fill_pending(&mut self) -> Result<bool, Error> {
+        let mut chunk = [0; 4096];
+
+        loop {
+            match (&*self.stream).read(&mut chunk) {
+                Ok(0) => return Ok(true),
+                Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(false),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Remove the first complete packet from the pending buffer, if there is one.
+    fn take_frame(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        match Protocol::frame_len(&self.pending) {
+            Ok(len) => Ok(Some(self.pending.drain(..len).collect())),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Update the client state and negotiated extensions for a received packet.
+    fn received(&mut self, packet: Protocol) -> Protocol {
+        if let Some(state) = self.state {
+            self.state = Some(state.after_recv(packet.packet_type()));
+        }
+
+        if let Protocol::Version(version) = &packet {
+            // Peers are free to ignore a malformed list, which leaves nothing negotiated
+            self.negotiated = ExtensionSet::try_from(version)
+                .map(|theirs| self.extensions.intersection(&theirs))
+                .unwrap_or_default();
+        }
+
+        packet
+    }
+
+    /// Serialize a packet and send it to the peer.
+    ///
+    /// The packet is remembered as the [`last_action`](Connection::last_action).
+    pub fn send<'a>(
+        &mut self,
+        packet: &(impl Parser<'a> + std::fmt::Display),
+    ) -> Result<(), Error> {
+        let bytes = encode(packet, &self.send_config)?;
+        let packet_type = PktType::from(bytes[0]);
+
+        if let Some(state) = self.state {
+            state.can_send(packet_type)?;
+        }
+
+        let wire = to_wire(&self.negotiated, &bytes)?;
+        crate::write_encoded(&self.stream, packet, &wire).map_err(timed_out)?;
+        observe(self.tap.as_deref(), Direction::Sent, &wire);
+
+        self.state = self.state.map(|state| state.after_send(packet_type));
+        // Parsed with the checked decoder, so a packet that does not read back is forgotten rather than panicking
+        self.last_sent = Protocol::from_bytes(&bytes).ok().map(|(packet, _)| packet);
+
+        Ok(())
+    }
+
+    /// Check, on a best-effort basis, that the peer is still there, e.g. from a thread reaping idle connections.
+    ///
+    /// Lurk has no ping, so this writes an `ACCEPT` for packet type 0, which no action uses and peers should
+    /// ignore, and reports [`ErrorKind::NotConnected`] if the write shows the peer has gone. This is a TCP
+    /// heuristic, not a protocol feature: the first write to a vanished peer usually succeeds and only draws the
+    /// reset, so a dead connection is typically noticed by the second probe, and a peer that silently dropped off
+    /// the network is only noticed once TCP gives up retransmitting.
+    ///
+    /// The probe bypasses client state tracking and is not remembered as the [`last_action`](Connection::last_action).
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Connection;
+    /// use std::net::TcpStream;
+    ///
+    /// let conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// if conn.probe().is_err() {
+    ///     // Drop the player
+    /// }
+    /// ```
+    pub fn probe(&self) -> Result<(), Error> {
+        if let Some(e) = self.stream.take_error()? {
+            return Err(crate::disconnected(e));
+        }
+
+        let ping = crate::PktAccept::new(PktType::DEFAULT);
+        let bytes = ping.encode_to_vec()?;
+
+        crate::write_encoded(&self.stream, &ping, &bytes).map_err(timed_out)?;
+        observe(self.tap.as_deref(), Direction::Sent, &bytes);
+
+        Ok(())
+    }
+
+    /// The most recent packet sent through this connection, if any.
+    ///
+    /// Lurk errors carry no reference to the action that caused them, so a client can pair a received
+    /// [`PktError`](crate::PktError) with this to explain what failed. The pairing is best-effort:
+    /// the server may send an error at any time, and other packets may have been sent in between.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Connection, PktChangeRoom, Protocol};
+    /// use std::net::TcpStream;
+    ///
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    /// conn.send(&PktChangeRoom::from(5)).unwrap();
+    ///
+    /// if let Protocol::Error(error) = conn.recv().unwrap() {
+    ///     if let Some(Protocol::ChangeRoom(change)) = conn.last_action() {
+    ///         println!("Your change room to room {} failed: {}", change.room_number, error.error);
+    ///     }
+    /// }
+    /// ```
+    pub fn last_action(&self) -> Option<&Protocol> {
+        self.last_sent.as_ref()
+    }
+
+    /// Replace a dropped stream with a fresh connection to the same peer, retrying with exponential backoff.
+    ///
+    /// Up to `retries` further attempts are made after the first, waiting `backoff`, then twice that, and so on in
+    /// between. The last connection error is returned if every attempt fails, and [`ErrorKind::NotConnected`] if
+    /// the peer address was never known. Read and write timeouts are carried over to the new stream.
+    ///
+    /// A new stream is a new Lurk session: the server sends `VERSION` and `GAME` again and the client must
+    /// re-send `CHARACTER` and `START`. Negotiated extensions and the [`last_action`](Connection::last_action) are
+    /// cleared and a tracked [`state`](Connection::state) restarts at [`ClientState::Connected`]. The state before
+    /// the reconnect is returned so the caller knows how far to replay, e.g. [`ClientState::InGame`] means both
+    /// `CHARACTER` and `START` were sent.
+    ///
+    /// The stream lives in a new [`Arc`]; clones of the old [`stream`](Connection::stream) still refer to the old socket.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Connection;
+    /// use std::net::TcpStream;
+    /// use std::time::Duration;
+    ///
+    /// let mut conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap()).track_client_state();
+    ///
+    /// if conn.recv().is_err() {
+    ///     let previous = conn.reconnect(5, Duration::from_millis(250)).unwrap();
+    ///     println!("Reconnected; was {:?} before the drop", previous);
+    /// }
+    /// ```
+    pub fn reconnect(
+        &mut self,
+        retries: usize,
+        backoff: Duration,
+    ) -> Result<Option<ClientState>, Error> {
+        let peer = self
+            .peer
+            .ok_or_else(|| Error::new(ErrorKind::NotConnected, "Peer address is unknown"))?;
+
+        let mut delay = backoff;
+        let mut attempt = 0;
+
+        let stream = loop {
+            match TcpStream::connect(peer) {
+                Ok(stream) => break stream,
+                Err(e) if attempt >= retries => return Err(e),
+                Err(_) => {
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+            }
+        };
+
+        stream.set_read_timeout(self.stream.read_timeout().ok().flatten())?;
+        stream.set_write_timeout(self.stream.write_timeout().ok().flatten())?;
+
+        let previous = self.state;
+
+        self.stream = Arc::new(stream);
+        self.last_sent = None;
+        self.pending.clear();
+        self.deferred = None;
+        self.negotiated = ExtensionSet::new();
+        self.state = self.state.map(|_| ClientState::default());
+
+        Ok(previous)
+    }
+
+    /// Split the connection into independent read and write halves.
+    ///
+    /// The halves carry no context; take it out first with [`context_mut`](Connection::context_mut) and
+    /// [`std::mem::take`] if it is still needed.
+    ///
+    /// Since [`TcpStream`] is full-duplex, one thread can block on [`ReadHalf::recv`] while others send through [`WriteHalf`].
+    /// The write half can be cloned; clones share a lock so packets from different threads are never interleaved on the wire.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Connection, PktMessage};
+    /// use std::net::TcpStream;
+    /// use std::thread;
+    ///
+    /// let conn = Connection::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    /// let (mut reader, writer) = conn.split();
+    ///
+    /// thread::spawn(move || {
+    ///     while let Ok(packet) = reader.recv() {
+    ///         println!("{}", packet);
+    ///     }
+    /// });
+    ///
+    /// writer.send(&PktMessage::server("Player", "Hello!")).unwrap();
+    /// ```
+    pub fn split(self) -> (ReadHalf, WriteHalf) {
+        let read = ReadHalf {
+            stream: self.stream.clone(),
+            negotiated: self.negotiated.clone(),
+            tap: self.tap.clone(),
+        };
+        let write = WriteHalf {
+            stream: self.stream,
+            tap: self.tap,
+            negotiated: Arc::new(self.negotiated),
+            send_config: self.send_config,
+            lock: Arc::new(Mutex::new(())),
+        };
+
+        (read, write)
+    }
+}
+
+impl From<Arc<TcpStream>> for Connection {
+    /// Wrap an already shared stream, e.g. one that is also used with the `send_*!` macros.
+    fn from(stream: Arc<TcpStream>) -> Self {
+        Self {
+            peer: stream.peer_addr().ok(),
+            stream,
+            tap: None,
+            last_sent: None,
+            state: None,
+            extensions: ExtensionSet::new(),
+            negotiated: ExtensionSet::new(),
+            send_config: SendConfig::default(),
+            pending: Vec::new(),
+            deferred: None,
+            context: (),
+        }
+    }
+}
+
+/// The receiving half of a [`Connection`], created by [`Connection::split`].
+pub struct ReadHalf {
+    stream: Arc<TcpStream>,
+    negotiated: ExtensionSet,
+    tap: Option<Arc<Mutex<Tap>>>,
+}
+
+impl ReadHalf {
+    /// Receive one packet from the peer.
+    pub fn recv(&mut self) -> Result<Protocol, Error> {
+        recv_with(&self.stream, &self.negotiated, self.tap.as_deref()).map_err(timed_out)
+    }
+}
+
+/// The sending half of a [`Connection`], created by [`Connection::split`].
+#[derive(Clone)]
+pub struct WriteHalf {
+    stream: Arc<TcpStream>,
+    tap: Option<Arc<Mutex<Tap>>>,
+    negotiated: Arc<ExtensionSet>,
+    send_config: SendConfig,
+    lock: Arc<Mutex<()>>,
+}
+
+impl WriteHalf {
+    /// Serialize a packet and send it to the peer.
+    ///
+    /// Holds the lock shared by every clone of this half for the duration of the write.
+    pub fn send<'a>(&self, packet: &(impl Parser<'a> + std::fmt::Display)) -> Result<(), Error> {
+        // A poisoned lock only means another writer panicked; the stream itself is still usable
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let bytes = encode(packet, &self.send_config)?;
+        let wire = to_wire(&self.negotiated, &bytes)?;
+
+        crate::write_encoded(&self.stream, packet, &wire).map_err(timed_out)?;
+        observe(self.tap.as_deref(), Direction::Sent, &wire);
+
+        Ok(())
+    }
+}
+
+/// Serialize a packet with the configured options.
+fn encode<'a>(packet: &impl Parser<'a>, config: &SendConfig) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    packet.write_with_policy(&mut bytes, config.name_policy)?;
+
+    Ok(bytes)
+}
+
+/// Receive one packet, passing its raw bytes to the tap and undoing any negotiated transforms before it is decoded.
+#[cfg_attr(not(feature = "compression"), allow(unused_variables, unused_mut))]
+fn recv_with(
+    stream: &Arc<TcpStream>,
+    negotiated: &ExtensionSet,
+    tap: Option<&Mutex<Tap>>,
+) -> Result<Protocol, Error> {
+    #[cfg(feature = "compression")]
+    let compressed = negotiated.contains(crate::compression::EXTENSION);
+    #[cfg(not(feature = "compression"))]
+    let compressed = false;
+
+    if tap.is_none() && !compressed {
+        return Protocol::recv(stream);
+    }
+
+    decode_frame(Protocol::read_raw(stream)?, negotiated, tap)
+}
+
+/// Decode one complete packet as read off the wire, reporting it to the tap and undoing any negotiated transforms.
+#[cfg_attr(not(feature = "compression"), allow(unused_mut))]
+fn decode_frame(
+    mut bytes: Vec<u8>,
+    negotiated: &ExtensionSet,
+    tap: Option<&Mutex<Tap>>,
+) -> Result<Protocol, Error> {
+    observe(tap, Direction::Received, &bytes);
+
+    #[cfg(feature = "compression")]
+    if negotiated.contains(crate::compression::EXTENSION) {
+        crate::compression::inflate(&mut bytes)?;
+    }
+    #[cfg(not(feature = "compression"))]
+    let _ = negotiated;
+
+    Protocol::decode_bytes(&bytes)
+}
+
+/// Pass the raw bytes of a packet to the tap, if one is set.
+fn observe(tap: Option<&Mutex<Tap>>, direction: Direction, bytes: &[u8]) {
+    if let Some(tap) = tap {
+        // A poisoned lock only means an earlier call panicked; the callback itself may still be usable
+        let mut tap = tap.lock().unwrap_or_else(|e| e.into_inner());
+        tap(direction, bytes);
+    }
+}
+
+/// Apply any negotiated transforms to an encoded packet before it is written.
+#[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+fn to_wire<'b>(negotiated: &ExtensionSet, bytes: &'b [u8]) -> Result<Cow<'b, [u8]>, Error> {
+    #[cfg(feature = "compression")]
+    if negotiated.contains(crate::compression::EXTENSION) {
+        let mut compressed = bytes.to_vec();
+        crate::compression::deflate(&mut compressed)?;
+
+        return Ok(Cow::Owned(compressed));
+    }
+
+    Ok(Cow::Borrowed(bytes))
+}
+
+/// Socket timeouts surface as `WouldBlock` on Unix and `TimedOut` on Windows; report both as `TimedOut`.
+///
+/// A timeout partway through a packet already says so in its message, which is kept.
+fn timed_out(error: Error) -> Error {
+    match error.kind() {
+        ErrorKind::TimedOut if error.get_ref().is_some() => error,
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+            Error::new(ErrorKind::TimedOut, "Timed out waiting for the peer")
+        }
+        _ => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_common::socket_pair;
+    use crate::{PktChangeRoom, PktFight, PktLeave, PktLoot, PktMessage};
+    use std::io::Write as _;
+    use std::thread;
+
+    #[test]
+    fn connection_send_recv() {
+        let (client, server) = socket_pair();
+        let mut client = Connection::from(client);
+        let mut server = Connection::from(server);
+
+        client
+            .send(&PktChangeRoom::from(3u16))
+            .expect("Send failed");
+
+        let room = server.recv().expect("Recv failed").into_change_room().ok();
+        assert_eq!(room.map(|r| r.room_number), Some(3));
+    }
+
+    /// The halves of a split connection work from separate threads.
+    #[test]
+    fn connection_split_halves() {
+        let (client, server) = socket_pair();
+        let (mut reader, writer) = Connection::from(server).split();
+        let mut client = Connection::from(client);
+
+        let handle = thread::spawn(move || reader.recv().expect("Recv failed"));
+
+        client
+            .send(&PktMessage::server("Server", "ping"))
+            .expect("Send failed");
+        let received = handle.join().expect("Reader thread panicked");
+        assert_eq!(
+            received.as_message().map(|m| m.message.as_ref()),
+            Some("ping")
+        );
+
+        writer
+            .send(&PktMessage::server("Client", "pong"))
+            .expect("Send failed");
+        let reply = client.recv().expect("Recv failed");
+        assert_eq!(reply.as_message().map(|m| m.message.as_ref()), Some("pong"));
+    }
+
+    /// Cloned write halves never interleave their packets.
+    #[test]
+    fn connection_split_cloned_writers() {
+        let (client, server) = socket_pair();
+        let (_reader, writer) = Connection::from(server).split();
+        let mut client = Connection::from(client);
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let writer = writer.clone();
+                thread::spawn(move || {
+                    let text = "x".repeat(4096);
+                    for _ in 0..8 {
+                        writer
+                            .send(&PktMessage::server("Client", &text))
+                            .expect("Send failed");
+                    }
+                })
+            })
+            .collect();
+
+        for _ in 0..32 {
+            let packet = client.recv().expect("Recv failed");
+            let message = packet.into_message().ok().expect("Expected Message");
+            assert_eq!(message.message.len(), 4096);
+        }
+
+        handles
+            .into_iter()
+            .for_each(|h| h.join().expect("Writer thread panicked"));
+    }
+
+    /// The last sent packet is available to pair with an incoming error.
+    #[test]
+    fn connection_last_action() {
+        let (client, server) = socket_pair();
+        let mut client = Connection::from(client);
+        let mut server = Connection::from(server);
+
+        assert!(client.last_action().is_none());
+
+        client
+            .send(&PktChangeRoom::from(5u16))
+            .expect("Send failed");
+        let _ = server.recv().expect("Recv failed");
+        server
+            .send(&crate::PktError::new(
+                crate::LurkError::BADROOM,
+                "No such room",
+            ))
+            .expect("Send failed");
+
+        let error = client.recv().expect("Recv failed").into_error().ok();
+        let action = client.last_action().and_then(Protocol::as_change_room);
+
+        assert_eq!(error.map(|e| e.error), Some(crate::LurkError::BADROOM));
+        assert_eq!(action.map(|a| a.room_number), Some(5));
+    }
+
+    /// A tracked client follows the handshake and rejects out-of-order sends.
+    #[test]
+    fn connection_client_state() {
+        let (client, server) = socket_pair();
+        let mut client = Connection::from(client).track_client_state();
+        let mut server = Connection::from(server);
+
+        assert_eq!(client.state(), Some(ClientState::Connected));
+
+        let err = client
+            .send(&crate::PktFight::default())
+            .expect_err("FIGHT before START must be rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        server
+            .send(&crate::PktGame {
+                packet_type: PktType::GAME,
+                initial_points: 100,
+                stat_limit: 65535,
+                description_len: 0,
+                description: "".into(),
+            })
+            .expect("Send failed");
+        let _ = client.recv().expect("Recv failed");
+        assert_eq!(client.state(), Some(ClientState::Handshaken));
+
+        let err = client
+            .send(&crate::PktStart::default())
+            .expect_err("START before CHARACTER must be rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// Receiving the peer's `VERSION` negotiates the extensions both sides advertise.
+    #[test]
+    fn connection_negotiates_extensions() {
+        let (client, server) = socket_pair();
+        let mut client =
+            Connection::from(client).with_extensions(["SEQ", "ZLIB"].into_iter().collect());
+        let theirs: ExtensionSet = ["SEQ", "MAP"].into_iter().collect();
+
+        crate::send_to(&server, &theirs.to_version(2, 3).unwrap()).expect("Send failed");
+        client.recv().expect("Recv failed");
+
+        let negotiated = client.negotiated_extensions();
+        assert!(negotiated.contains(b"SEQ"));
+        assert!(!negotiated.contains(b"ZLIB") && !negotiated.contains(b"MAP"));
+    }
+
+    /// A receive with nothing to read fails with `TimedOut` once the read timeout elapses.
+    #[test]
+    fn connection_read_timeout() {
+        let (client, _server) = socket_pair();
+        let mut client = Connection::from(client)
+            .with_read_timeout(Duration::from_millis(50))
+            .expect("Failed to set timeout");
+
+        let err = client.recv().err().expect("Recv must time out");
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+    }
+
+    /// A timeout partway through a packet is `TimedOut` and says the rest is in flight, not `UnexpectedEof`.
+    #[test]
+    fn connection_read_timeout_mid_body() {
+        let (client, server) = socket_pair();
+        let mut client = Connection::from(client)
+            .with_read_timeout(Duration::from_millis(50))
+            .expect("Failed to set timeout");
+
+        let bytes = PktLoot::new("Goblin").encode_to_vec().unwrap();
+        (&*server).write_all(&bytes[..10]).unwrap();
+
+        let err = client.recv().err().expect("Recv must time out");
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        assert!(err.to_string().contains("partway"), "{}", err);
+    }
+
+    /// A zero timeout is rejected rather than silently disabling the timeout.
+    #[test]
+    fn connection_zero_timeout() {
+        let (client, _server) = socket_pair();
+
+        assert!(
+            Connection::from(client)
+                .with_write_timeout(Duration::ZERO)
+                .is_err()
+        );
+    }
+
+    /// Descriptions are only compressed once both peers have agreed to it, and arrive intact either way.
+    #[cfg(feature = "compression")]
+    #[test]
+    fn connection_compressed_descriptions() {
+        let description = "Scrolls and ledgers. ".repeat(30);
+        let room = crate::PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: 9,
+            room_name: "Archive".into(),
+            description_len: description.len() as u16,
+            description: description.into(),
+        };
+
+        for negotiated in [false, true] {
+            let (client, server) = socket_pair();
+            let mut client = Connection::from(client);
+            let mut server = Connection::from(server);
+
+            if negotiated {
+                let agreed: ExtensionSet = [crate::compression::EXTENSION].into_iter().collect();
+                client.negotiated = agreed.clone();
+                server.negotiated = agreed;
+            }
+
+            server.send(&room).expect("Send failed");
+            let received = client.recv().expect("Recv failed").into_room().ok();
+
+            assert_eq!(
+                received.map(|r| r.description),
+                Some(room.description.clone())
+            );
+        }
+    }
+
+    /// Sending to a peer that has hung up reports `NotConnected`.
+    #[test]
+    fn connection_send_to_closed_peer() {
+        let (client, server) = socket_pair();
+        let mut client = Connection::from(client);
+        drop(server);
+
+        // The first writes may be buffered before the reset arrives
+        let err = (0..100)
+            .find_map(|_| client.send(&PktMessage::server("Gone", "Hello?")).err())
+            .expect("Send to a closed peer must fail");
+        assert_eq!(err.kind(), ErrorKind::NotConnected);
+    }
+
+    /// A strict name policy rejects the packet before anything is written.
+    #[test]
+    fn connection_strict_name_policy() {
+        let (client, _server) = socket_pair();
+        let mut client = Connection::from(client).with_send_config(SendConfig {
+            name_policy: crate::NamePolicy::Error,
+        });
+
+        let err = client
+            .send(&crate::PktLoot::new(&"x".repeat(33)))
+            .expect_err("Overlong name must be rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(client.last_action().is_none());
+    }
+
+    /// Untracked connections, as used by servers, may send anything.
+    #[test]
+    fn connection_untracked_state() {
+        let (client, _server) = socket_pair();
+        let mut client = Connection::from(client);
+
+        assert_eq!(client.state(), None);
+        client
+            .send(&crate::PktFight::default())
+            .expect("Send failed");
+    }
+
+    /// The tap sees the exact bytes of every packet sent and received, including through split halves.
+    #[test]
+    fn connection_tap() {
+        let (client, server) = socket_pair();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut client = Connection::from(client);
+        let mut server = Connection::from(server);
+
+        let log = seen.clone();
+        client.set_tap(Box::new(move |direction, bytes| {
+            log.lock().unwrap().push((direction, bytes.to_vec()));
+        }));
+
+        let change = PktChangeRoom::new(3);
+        client.send(&change).expect("Send failed");
+        server.recv().expect("Recv failed");
+
+        let reply = PktMessage::server("Player", "Moved.");
+        server.send(&reply).expect("Send failed");
+
+        let (mut reader, writer) = client.split();
+        reader.recv().expect("Recv failed");
+        writer.send(&change).expect("Send failed");
+
+        let expected = vec![
+            (Direction::Sent, change.encode_to_vec().unwrap()),
+            (Direction::Received, reply.encode_to_vec().unwrap()),
+            (Direction::Sent, change.encode_to_vec().unwrap()),
+        ];
+        assert_eq!(*seen.lock().unwrap(), expected);
+    }
+
+    /// A reconnect opens a new session with the same peer and resets the per-session state.
+    #[test]
+    fn connection_reconnect() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let client = TcpStream::connect(listener.local_addr().unwrap()).expect("Failed to connect");
+        let (first, _) = listener.accept().expect("Failed to accept");
+
+        let mut client = Connection::new(client).track_client_state();
+        client.state = Some(ClientState::InGame);
+        client.send(&PktChangeRoom::new(2)).expect("Send failed");
+        drop(first);
+
+        let previous = client
+            .reconnect(0, Duration::from_millis(1))
+            .expect("Reconnect failed");
+        assert_eq!(previous, Some(ClientState::InGame));
+        assert_eq!(client.state(), Some(ClientState::Connected));
+        assert!(client.last_action().is_none());
+
+        let (second, _) = listener.accept().expect("Failed to accept");
+        let mut server = Connection::new(second);
+        server
+            .send(&PktMessage::server("Player", "Welcome back"))
+            .expect("Send failed");
+        assert!(client.recv().expect("Recv failed").as_message().is_some());
+    }
+
+    /// When the peer is gone for good, the last connection error is returned after the retries.
+    #[test]
+    fn connection_reconnect_gives_up() {
+        // `socket_pair` drops its listener, so nothing accepts on the peer address any more
+        let (client, _server) = socket_pair();
+        let mut client = Connection::from(client);
+
+        let err = client
+            .reconnect(2, Duration::from_millis(1))
+            .expect_err("Reconnect must fail");
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    }
+
+    /// A probe reaches a live peer as a harmless ACCEPT and reports a peer that has hung up.
+    #[test]
+    fn connection_probe() {
+        let (client, server) = socket_pair();
+        let client = Connection::from(client);
+        let mut server = Connection::from(server);
+
+        client.probe().expect("Probe of a live peer failed");
+        let ping = server.recv().expect("Recv failed");
+        assert_eq!(ping.as_accept().map(|a| a.accept_type), Some(0));
+        assert!(client.last_action().is_none());
+
+        drop(server);
+
+        let err = (0..20)
+            .find_map(|_| {
+                thread::sleep(Duration::from_millis(10));
+                client.probe().err()
+            })
+            .expect("Probe never noticed the closed peer");
+        assert_eq!(err.kind(), ErrorKind::NotConnected);
+    }
+
+    /// A context attached to a connection keeps its settings and can be updated in place.
+    #[test]
+    fn connection_context() {
+        let (client, server) = socket_pair();
+        let mut server = Connection::from(server)
+            .track_client_state()
+            .with_context(vec![1u16]);
+        let mut client = Connection::from(client);
+
+        server.context_mut().push(2);
+        assert_eq!(server.context(), &[1, 2]);
+        assert_eq!(server.state(), Some(ClientState::Connected));
+
+        client.send(&PktChangeRoom::new(2)).expect("Send failed");
+        assert!(
+            server
+                .recv()
+                .expect("Recv failed")
+                .as_change_room()
+                .is_some()
+        );
+    }
+
+    /// Drain until at least one packet has arrived, giving the loopback a moment to deliver.
+    fn drain_some(conn: &mut Connection) -> Vec<Protocol> {
+        for _ in 0..200 {
+            let packets = conn.drain_available().expect("Drain failed");
+            if !packets.is_empty() {
+                return packets;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        panic!("Nothing arrived");
+    }
+
+    /// Draining returns the complete packets and keeps a partial one for the next call.
+    #[test]
+    fn connection_drain_available() {
+        use std::io::Write;
+
+        let (client, server) = socket_pair();
+        let mut server = Connection::from(server);
+
+        assert!(server.drain_available().expect("Drain failed").is_empty());
+
+        let mut bytes = PktFight::new().encode_to_vec().unwrap();
+        PktChangeRoom::new(4).write_to(&mut bytes).unwrap();
+        let message = PktMessage::server("Hero", "Hello").encode_to_vec().unwrap();
+        bytes.extend_from_slice(&message[..40]);
+        client.as_ref().write_all(&bytes).unwrap();
+
+        let mut packets = drain_some(&mut server);
+        while packets.len() < 2 {
+            packets.extend(drain_some(&mut server));
+        }
+        let types: Vec<PktType> = packets.iter().map(Protocol::packet_type).collect();
+        assert_eq!(types, [PktType::FIGHT, PktType::CHANGEROOM]);
+
+        client.as_ref().write_all(&message[40..]).unwrap();
+
+        let packets = drain_some(&mut server);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(
+            packets[0].as_message().map(|m| m.message.as_ref()),
+            Some("Hello")
+        );
+    }
+
+    /// `recv` finishes a packet that `drain_available` left partway through.
+    #[test]
+    fn connection_recv_after_partial_drain() {
+        use std::io::Write;
+
+        let (client, server) = socket_pair();
+        let mut server = Connection::from(server);
+        let message = PktMessage::server("Hero", "Hello").encode_to_vec().unwrap();
+
+        client.as_ref().write_all(&message[..10]).unwrap();
+        while server.pending.len() < 10 {
+            assert!(server.drain_available().expect("Drain failed").is_empty());
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        client.as_ref().write_all(&message[10..]).unwrap();
+        let packet = server.recv().expect("Recv failed");
+        assert_eq!(
+            packet.as_message().map(|m| m.message.as_ref()),
+            Some("Hello")
+        );
+        assert!(server.pending.is_empty());
+    }
+
+    /// Once the peer has gone and everything is drained, draining reports end of stream.
+    #[test]
+    fn connection_drain_after_close() {
+        let (client, server) = socket_pair();
+        let mut server = Connection::from(server);
+        crate::send_to(&client, &PktLeave::new()).expect("Send failed");
+        drop(client);
+
+        let packets = drain_some(&mut server);
+        assert_eq!(packets[0].packet_type(), PktType::LEAVE);
+
+        let err = server
+            .drain_available()
+            .err()
+            .expect("Drain after close must fail");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    /// Packets decoded before a bad one are returned, and the error is reported by the next call.
+    #[test]
+    fn connection_drain_defers_error() {
+        let (client, server) = socket_pair();
+        let mut server = Connection::from(server);
+
+        let mut bytes = PktFight::new().encode_to_vec().unwrap();
+        bytes.push(200);
+        client.as_ref().write_all(&bytes).unwrap();
+
+        let packets = drain_some(&mut server);
+        assert_eq!(packets[0].packet_type(), PktType::FIGHT);
+
+        let err = loop {
+            match server.drain_available() {
+                Ok(packets) => assert!(packets.is_empty()),
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
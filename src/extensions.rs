@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::io::{Error, ErrorKind};
+
+use crate::PktVersion;
+
+/// A set of protocol extensions, as advertised in the extension list of a [`PktVersion`].
+///
+/// Each side of a connection advertises the extensions it supports; an optional feature should only be
+/// turned on when it is in the intersection of both lists (see [`Connection::negotiated_extensions`](crate::Connection::negotiated_extensions)).
+///
+/// ```
+/// use lurk_protocol::ExtensionSet;
+///
+/// let ours: ExtensionSet = [b"SEQ".as_slice(), b"ZLIB".as_slice()].into_iter().collect();
+/// let theirs: ExtensionSet = [b"SEQ".as_slice()].into_iter().collect();
+///
+/// let agreed = ours.intersection(&theirs);
+/// assert!(agreed.contains(b"SEQ"));
+/// assert!(!agreed.contains(b"ZLIB"));
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionSet {
+    extensions: BTreeSet<Vec<u8>>,
+}
+
+impl ExtensionSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an extension, returning `false` if it was already present.
+    pub fn insert(&mut self, extension: impl Into<Vec<u8>>) -> bool {
+        self.extensions.insert(extension.into())
+    }
+
+    /// Whether the set contains the given extension.
+    pub fn contains(&self, extension: &[u8]) -> bool {
+        self.extensions.contains(extension)
+    }
+
+    /// The extensions present in both sets.
+    pub fn intersection(&self, other: &ExtensionSet) -> ExtensionSet {
+        Self {
+            extensions: self
+                .extensions
+                .intersection(&other.extensions)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Iterate over the extensions in the set.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.extensions.iter().map(Vec::as_slice)
+    }
+
+    /// The number of extensions in the set.
+    pub fn len(&self) -> usize {
+        self.extensions.len()
+    }
+
+    /// Whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    /// Build a `VERSION` packet advertising these extensions.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if an extension or the encoded list is longer than 65535 bytes.
+    pub fn to_version(&self, major_rev: u8, minor_rev: u8) -> Result<PktVersion, Error> {
+        PktVersion::with_extensions(major_rev, minor_rev, &self.extensions)
+    }
+}
+
+impl<T: Into<Vec<u8>>> FromIterator<T> for ExtensionSet {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            extensions: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<&PktVersion> for ExtensionSet {
+    type Error = Error;
+
+    /// Read the length-prefixed extension list of a `VERSION` packet.
+    ///
+    /// Fails with [`ErrorKind::InvalidData`] if an extension's length runs past the end of the list.
+    fn try_from(version: &PktVersion) -> Result<Self, Self::Error> {
+        let mut rest = version.extensions.as_deref().unwrap_or_default();
+        let mut set = ExtensionSet::new();
+
+        while !rest.is_empty() {
+            let (len, tail) = rest
+                .split_first_chunk::<2>()
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Truncated extension length"))?;
+            let len = u16::from_le_bytes(*len) as usize;
+
+            if tail.len() < len {
+                return Err(Error::new(ErrorKind::InvalidData, "Truncated extension"));
+            }
+
+            let (extension, tail) = tail.split_at(len);
+            set.insert(extension);
+            rest = tail;
+        }
+
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PktType;
+
+    /// Extensions survive being advertised in a VERSION packet and read back.
+    #[test]
+    fn extensions_version_roundtrip() {
+        let set: ExtensionSet = [b"SEQ".as_slice(), b"ZLIB".as_slice()]
+            .into_iter()
+            .collect();
+        let version = set.to_version(2, 3).expect("Encoding failed");
+
+        assert_eq!(version.extensions_len, 11);
+        assert_eq!(ExtensionSet::try_from(&version).ok(), Some(set));
+    }
+
+    /// An empty set advertises no extension list at all.
+    #[test]
+    fn extensions_empty_version() {
+        let version = ExtensionSet::new()
+            .to_version(2, 3)
+            .expect("Encoding failed");
+
+        assert_eq!(version.extensions_len, 0);
+        assert!(version.extensions.is_none());
+        assert_eq!(
+            ExtensionSet::try_from(&version).ok(),
+            Some(ExtensionSet::new())
+        );
+    }
+
+    /// Only extensions advertised by both sides are negotiated.
+    #[test]
+    fn extensions_intersection() {
+        let ours: ExtensionSet = ["SEQ", "ZLIB", "MAP"].into_iter().collect();
+        let theirs: ExtensionSet = ["MAP", "SEQ", "TLS"].into_iter().collect();
+
+        let agreed = ours.intersection(&theirs);
+        assert_eq!(agreed.len(), 2);
+        assert_eq!(agreed.iter().collect::<Vec<_>>(), [b"MAP", b"SEQ"]);
+    }
+
+    /// A length prefix running past the end of the list is rejected.
+    #[test]
+    fn extensions_truncated_list() {
+        let version = PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: 2,
+            minor_rev: 3,
+            extensions_len: 4,
+            extensions: Some(vec![0x05, 0x00, 0x41, 0x42]),
+        };
+
+        let err = ExtensionSet::try_from(&version).expect_err("Truncated list must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -8,6 +8,9 @@ bitflags! {
     /// When a client uses [`PktType::CHARACTER`] to describe a new player, the server may (should) ignore the client's initial specification for flags, health, gold, etc.
     /// using [`CharacterFlags::reset()`].
     /// > Since the character packet is shared between players and monsters, the server is responsible for setting these values correctly.
+    ///
+    /// With serde the flags are written as their names joined by `|`, e.g. `"ALIVE | BATTLE"`, with any reserved
+    /// bits appended in hex (`"ALIVE | 0x5"`). Every value, reserved bits included, reads back unchanged.
     pub struct CharacterFlags: u8 {
         /// The character is alive.
         const ALIVE = 0b1000_0000;
@@ -28,12 +31,17 @@ impl CharacterFlags {
         self.contains(CharacterFlags::ALIVE)
     }
 
-    /// Check if the character is a monster.
+    /// Check if the character will join battles in its room.
     pub fn is_battle(&self) -> bool {
         self.contains(CharacterFlags::BATTLE)
     }
 
     /// Check if the character is a monster.
+    pub fn is_monster(&self) -> bool {
+        self.contains(CharacterFlags::MONSTER)
+    }
+
+    /// Check if the character has started.
     pub fn is_started(&self) -> bool {
         self.contains(CharacterFlags::STARTED)
     }
@@ -55,10 +63,61 @@ impl CharacterFlags {
             .union(CharacterFlags::READY)
     }
 
+    /// Flags for a newly spawned, living monster.
+    pub fn monster() -> Self {
+        CharacterFlags::ALIVE.union(CharacterFlags::MONSTER)
+    }
+
     /// Reset a character when first starting or respawning.
     pub fn reset() -> Self {
         CharacterFlags::ALIVE.union(CharacterFlags::BATTLE)
     }
+
+    /// The flags that differ between `previous` and these flags, in either direction.
+    pub fn changed_from(&self, previous: CharacterFlags) -> CharacterFlags {
+        self.symmetric_difference(previous)
+    }
+
+    /// The flags set now that were not set in `previous`.
+    pub fn gained(&self, previous: CharacterFlags) -> CharacterFlags {
+        self.difference(previous)
+    }
+
+    /// The flags set in `previous` that are no longer set.
+    ///
+    /// ```
+    /// use lurk_protocol::CharacterFlags;
+    ///
+    /// let now = CharacterFlags::dead();
+    ///
+    /// if now.lost(CharacterFlags::alive()).is_alive() {
+    ///     println!("Hero has fallen!");
+    /// }
+    /// ```
+    pub fn lost(&self, previous: CharacterFlags) -> CharacterFlags {
+        previous.difference(*self)
+    }
+
+    /// Build flags from a raw byte, keeping the three reserved low bits as well as the known flags.
+    ///
+    /// Unlike [`from_bits_truncate`](CharacterFlags::from_bits_truncate), nothing is dropped, so a server that
+    /// stores its own state in the reserved bits gets it back from [`to_raw`](CharacterFlags::to_raw).
+    ///
+    /// ```
+    /// use lurk_protocol::CharacterFlags;
+    ///
+    /// let flags = CharacterFlags::from_raw(0b1000_0101);
+    /// assert!(flags.is_alive());
+    /// assert_eq!(flags.to_raw(), 0b1000_0101);
+    /// ```
+    pub fn from_raw(raw: u8) -> Self {
+        CharacterFlags::from_bits_retain(raw)
+    }
+
+    /// All eight bits of the flags, reserved bits included.
+    pub fn to_raw(&self) -> u8 {
+        self.bits()
+    }
 }
 
 #[cfg(test)]
@@ -182,4 +241,128 @@ mod tests {
         // ALIVE (0x80) | BATTLE (0x40) = 0xC0
         assert_eq!(CharacterFlags::reset().bits(), 0b1100_0000);
     }
+
+    // ── serde ─────────────────────────────────────────────────────────
+    #[test]
+    fn serde_uses_flag_names() {
+        let json = serde_json::to_string(&CharacterFlags::reset()).unwrap();
+        assert_eq!(json, "\"ALIVE | BATTLE\"");
+        assert_eq!(
+            serde_json::from_str::<CharacterFlags>(&json).unwrap(),
+            CharacterFlags::reset()
+        );
+    }
+
+    #[test]
+    fn serde_empty_flags() {
+        assert_eq!(
+            serde_json::to_string(&CharacterFlags::empty()).unwrap(),
+            "\"\""
+        );
+    }
+
+    #[test]
+    fn serde_roundtrip_representative_flags() {
+        let cases = [
+            CharacterFlags::empty(),
+            CharacterFlags::alive(),
+            CharacterFlags::dead(),
+            CharacterFlags::reset(),
+            CharacterFlags::MONSTER | CharacterFlags::ALIVE,
+            CharacterFlags::all(),
+        ];
+
+        for flags in cases {
+            let json = serde_json::to_string(&flags).unwrap();
+            assert_eq!(
+                serde_json::from_str::<CharacterFlags>(&json).unwrap(),
+                flags,
+                "{} did not round-trip",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn serde_roundtrip_reserved_bits() {
+        for raw in 0..=u8::MAX {
+            let flags = CharacterFlags::from_raw(raw);
+            let json = serde_json::to_string(&flags).unwrap();
+            let back = serde_json::from_str::<CharacterFlags>(&json).unwrap();
+            assert_eq!(back.to_raw(), raw, "{} did not round-trip", json);
+        }
+    }
+
+    // ── is_monster ────────────────────────────────────────────────────
+    #[test]
+    fn is_monster_when_set() {
+        assert!(CharacterFlags::MONSTER.is_monster());
+    }
+
+    #[test]
+    fn is_monster_when_not_set() {
+        assert!(!CharacterFlags::alive().is_monster());
+    }
+
+    // ── monster() ─────────────────────────────────────────────────────
+    #[test]
+    fn monster_is_alive_monster() {
+        let flags = CharacterFlags::monster();
+        assert!(flags.is_alive() && flags.is_monster());
+        assert!(!flags.is_battle() && !flags.is_ready() && !flags.is_started());
+    }
+
+    #[test]
+    fn monster_exact_bits() {
+        // ALIVE (0x80) | MONSTER (0x20) = 0xA0
+        assert_eq!(CharacterFlags::MONSTER.bits(), 0b0010_0000);
+        assert_eq!(CharacterFlags::monster().bits(), 0b1010_0000);
+    }
+
+    // ── changed_from / gained / lost ──────────────────────────────────
+    #[test]
+    fn alive_to_dead_loses_only_alive() {
+        let (before, after) = (CharacterFlags::alive(), CharacterFlags::dead());
+
+        assert_eq!(after.changed_from(before), CharacterFlags::ALIVE);
+        assert_eq!(after.lost(before), CharacterFlags::ALIVE);
+        assert!(after.gained(before).is_empty());
+    }
+
+    #[test]
+    fn dead_to_alive_gains_only_alive() {
+        let (before, after) = (CharacterFlags::dead(), CharacterFlags::alive());
+
+        assert_eq!(after.gained(before), CharacterFlags::ALIVE);
+        assert!(after.lost(before).is_empty());
+    }
+
+    #[test]
+    fn changed_from_is_gained_and_lost() {
+        let before = CharacterFlags::reset();
+        let after = CharacterFlags::STARTED | CharacterFlags::ALIVE;
+
+        assert_eq!(after.gained(before), CharacterFlags::STARTED);
+        assert_eq!(after.lost(before), CharacterFlags::BATTLE);
+        assert_eq!(
+            after.changed_from(before),
+            after.gained(before) | after.lost(before)
+        );
+        assert!(after.changed_from(after).is_empty());
+    }
+
+    // ── from_raw / to_raw ─────────────────────────────────────────────
+    #[test]
+    fn raw_roundtrip_all_bytes() {
+        for raw in 0..=u8::MAX {
+            assert_eq!(CharacterFlags::from_raw(raw).to_raw(), raw);
+        }
+    }
+
+    #[test]
+    fn raw_keeps_known_flags() {
+        let flags = CharacterFlags::from_raw(0b1100_0111);
+        assert!(flags.is_alive() && flags.is_battle());
+        assert!(!flags.is_ready());
+    }
 }
@@ -0,0 +1,200 @@
+use std::io::{Error, ErrorKind};
+use std::net::TcpStream;
+
+use crate::{ExtensionSet, Parser, PktGame, PktVersion};
+
+/// The `VERSION` and `GAME` pair a server sends, in that order, to every new connection.
+///
+/// Build one with [`Handshake::builder`] so the extension list, revisions, and game limits are consistent, then
+/// send it to each client with [`Handshake::send`].
+///
+/// ```no_run
+/// use lurk_protocol::Handshake;
+/// use std::net::TcpListener;
+///
+/// let handshake = Handshake::builder()
+///     .version(2, 3)
+///     .extension("SEQ")
+///     .initial_points(100)
+///     .description("Deep Caves.")
+///     .build()
+///     .unwrap();
+///
+/// for stream in TcpListener::bind("127.0.0.1:8080").unwrap().incoming() {
+///     handshake.send(&stream.unwrap()).unwrap();
+/// }
+/// ```
+pub struct Handshake {
+    /// The `VERSION` packet, sent first.
+    pub version: PktVersion,
+    /// The `GAME` packet, sent second.
+    pub game: PktGame,
+}
+
+impl Handshake {
+    /// Start building a handshake; see [`HandshakeBuilder`] for the defaults.
+    pub fn builder() -> HandshakeBuilder {
+        HandshakeBuilder::default()
+    }
+
+    /// Write `VERSION` then `GAME` to the stream in a single write, so nothing can be sent in between.
+    ///
+    /// A peer that has hung up is reported as [`ErrorKind::NotConnected`].
+    pub fn send(&self, stream: &TcpStream) -> Result<(), Error> {
+        let mut bytes = self.version.encode_to_vec()?;
+        self.game.write_to(&mut bytes)?;
+
+        crate::write_encoded(stream, &self.version, &bytes)
+    }
+}
+
+/// Builder for a [`Handshake`].
+///
+/// Unless set, the handshake advertises [`PROTOCOL_VERSION`](crate::PROTOCOL_VERSION) with no extensions, 100 initial points, no stat
+/// limit, and an empty description.
+pub struct HandshakeBuilder {
+    major_rev: u8,
+    minor_rev: u8,
+    extensions: ExtensionSet,
+    initial_points: u16,
+    stat_limit: u16,
+    description: String,
+}
+
+impl Default for HandshakeBuilder {
+    fn default() -> Self {
+        let (major_rev, minor_rev) = crate::PROTOCOL_VERSION;
+
+        Self {
+            major_rev,
+            minor_rev,
+            extensions: ExtensionSet::new(),
+            initial_points: 100,
+            stat_limit: PktGame::UNUSED_STAT_LIMIT,
+            description: String::new(),
+        }
+    }
+}
+
+impl HandshakeBuilder {
+    /// Set the protocol revision advertised in `VERSION`.
+    pub fn version(mut self, major_rev: u8, minor_rev: u8) -> Self {
+        self.major_rev = major_rev;
+        self.minor_rev = minor_rev;
+        self
+    }
+
+    /// Advertise an extension in `VERSION`. May be called repeatedly; duplicates are advertised once.
+    pub fn extension(mut self, extension: impl Into<Vec<u8>>) -> Self {
+        self.extensions.insert(extension);
+        self
+    }
+
+    /// Set the points a new character may spend across attack, defense, and regen.
+    pub fn initial_points(mut self, initial_points: u16) -> Self {
+        self.initial_points = initial_points;
+        self
+    }
+
+    /// Set the server-wide stat limit. Defaults to [`PktGame::UNUSED_STAT_LIMIT`].
+    pub fn stat_limit(mut self, stat_limit: u16) -> Self {
+        self.stat_limit = stat_limit;
+        self
+    }
+
+    /// Set the description of the game.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Build the packets, computing every length field.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if the extension list or description does not fit its `u16`
+    /// length, or if `initial_points` exceeds a stat limit in use, since no new character could then spend them.
+    pub fn build(self) -> Result<Handshake, Error> {
+        if self.stat_limit != PktGame::UNUSED_STAT_LIMIT && self.initial_points > self.stat_limit {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Initial points {} exceed the stat limit {}",
+                    self.initial_points, self.stat_limit
+                ),
+            ));
+        }
+
+        if self.description.len() > u16::MAX as usize {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Game description is too long",
+            ));
+        }
+
+        Ok(Handshake {
+            version: self.extensions.to_version(self.major_rev, self.minor_rev)?,
+            game: PktGame::new(self.initial_points, self.stat_limit, &self.description),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_common::socket_pair;
+    use crate::{PktType, Protocol};
+
+    /// The built packets carry consistent lengths for the extensions and description.
+    #[test]
+    fn handshake_build() {
+        let handshake = Handshake::builder()
+            .version(2, 4)
+            .extension("SEQ")
+            .extension("SEQ")
+            .extension("ZLIB")
+            .initial_points(120)
+            .stat_limit(200)
+            .description("Deep Caves.")
+            .build()
+            .expect("Build failed");
+
+        assert_eq!(
+            (handshake.version.major_rev, handshake.version.minor_rev),
+            (2, 4)
+        );
+        assert_eq!(handshake.version.extensions_len, 11);
+        assert_eq!(handshake.game.packet_type, PktType::GAME);
+        assert_eq!(
+            (handshake.game.initial_points, handshake.game.stat_limit),
+            (120, 200)
+        );
+        assert_eq!(handshake.game.description_len, 11);
+    }
+
+    /// Initial points above a stat limit in use are rejected.
+    #[test]
+    fn handshake_inconsistent_limits() {
+        let err = Handshake::builder()
+            .initial_points(300)
+            .stat_limit(200)
+            .build()
+            .err()
+            .expect("Build must fail");
+
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// `send` delivers VERSION followed by GAME.
+    #[test]
+    fn handshake_send_order() {
+        let (client, server) = socket_pair();
+        let handshake = Handshake::builder().build().expect("Build failed");
+
+        handshake.send(&server).expect("Send failed");
+
+        let first = Protocol::recv(&client).expect("Recv failed");
+        let second = Protocol::recv(&client).expect("Recv failed");
+        assert_eq!(first.packet_type(), PktType::VERSION);
+        assert_eq!(second.packet_type(), PktType::GAME);
+        assert_eq!(second.as_game().map(|g| g.initial_points), Some(100));
+    }
+}
@@ -7,7 +7,10 @@
 //! including parsing, error handling, and protocol definitions.
 //!
 //! ## Features
-//! - Optional `tracing` support for structured logging and diagnostics. Also adds the [`PCap`] type for capturing and debugging packet data.
+//! - Optional `tracing` support for structured logging and diagnostics, with packet data dumped through [`PCap`].
+//! - Optional `pcap-table` support for laying out [`PCap`] dumps with `tabled`. Without it they use plain padded columns.
+//! - Optional `compression` support for zlib compressed descriptions, used when both peers advertise the `compress` extension.
+//! - Optional `testing` support for generating random, valid packets from a seed with [`testing::random_packet`].
 //!
 //! For more details about the protocol itself, see the [Lurk Protocol Wiki](https://github.com/The24Kings/LurkProtocol/wiki).
 //!
@@ -108,34 +111,66 @@
 use std::io::Write;
 use std::net::TcpStream;
 
+pub use batch::Batch;
+pub use buffer_pool::BufferPool;
+pub use client_state::ClientState;
+pub use connection::{Connection, Direction, ReadHalf, WriteHalf};
+pub use extensions::ExtensionSet;
 pub use flags::CharacterFlags;
+pub use handshake::{Handshake, HandshakeBuilder};
 pub use lurk_error::LurkError;
+pub use map::Map;
+pub use names::NameRegistry;
 #[doc(hidden)]
 pub use packet::Packet;
-pub use packet::Parser;
+pub use packet::{NamePolicy, Parser};
 pub use packet::{
-    accept::PktAccept, change_room::PktChangeRoom, character::PktCharacter,
-    connection::PktConnection, error::PktError, fight::PktFight, game::PktGame, leave::PktLeave,
-    loot::PktLoot, message::PktMessage, pvp_fight::PktPVPFight, room::PktRoom, start::PktStart,
-    version::PktVersion,
+    accept::PktAccept, change_room::PktChangeRoom, character::CharacterKind, character::Monster,
+    character::PktCharacter, character::PktCharacterBuilder, character::Player,
+    connection::PktConnection, error::PktError, fight::PktFight, game::PktGame,
+    game::StatLimitKind, game::StatViolation, leave::PktLeave, loot::PktLoot,
+    message::MessageSource, message::PktMessage, pvp_fight::PktPVPFight, room::PktRoom,
+    start::PktStart, version::PktVersion,
 };
+pub use pending::PendingCharacter;
 pub use pkt_type::PktType;
-pub use protocol::Protocol;
+pub use protocol::{Incomplete, Protocol, RecvConfig, SendConfig, UnknownTypePolicy};
+pub use protocol_ref::ProtocolRef;
+pub use registry::PktTypeRegistry;
+pub use roster::Roster;
 
+/// Queue several packets and write them to a stream at once.
+pub mod batch;
+/// A shared pool of receive buffers.
+pub mod buffer_pool;
+/// The phases of a client connection and the packets each allows.
+pub mod client_state;
+#[cfg(feature = "compression")]
+/// Compression of descriptions, enabled by the `compression` feature.
+pub mod compression;
+/// A connected peer, and its independent read and write halves.
+pub mod connection;
+/// Protocol extensions advertised in `VERSION` and negotiated between peers.
+pub mod extensions;
 /// Flags representing the state of a character in the game.
 ///
 /// When a client uses [`PktType::CHARACTER`] to describe a new player, the server may (should) ignore the client's initial specification for flags, health, gold, etc.
 /// using [`CharacterFlags::reset()`].
 /// > Since the character packet is shared between players and monsters, the server is responsible for setting these values correctly.
 pub mod flags;
+/// The `VERSION` and `GAME` pair a server sends to new connections.
+pub mod handshake;
 /// Error types for the Lurk protocol.
 pub mod lurk_error;
+/// Client-side tracking of the rooms seen and the exits between them.
+pub mod map;
+/// Server-side tracking of the player names in use.
+pub mod names;
 /// Module for handling various packet types in the Lurk protocol.
 ///
 /// This module defines the [`Parser`] trait for serializing and deserializing packets,
 /// as well as the various packet structures used in the protocol.
 pub mod packet;
-#[cfg(feature = "tracing")]
 /// Packet capture and tracing utilities.
 ///
 /// ```no_run
@@ -148,32 +183,114 @@ pub mod packet;
 /// let mut buffer: Vec<u8> = Vec::new();
 /// packet.write_to(&mut buffer).unwrap();
 ///
-/// println!("{}", PCap::build(buffer));
+/// println!("{}", PCap::build(&buffer));
 /// ```
 pub mod pcap;
+/// Server-side tracking of a character between its acceptance and `START`.
+pub mod pending;
 /// Packet type definitions.
 pub mod pkt_type;
 /// The Protocol.
 pub mod protocol;
+/// Zero-copy decoding into views that borrow from the input buffer.
+pub mod protocol_ref;
+/// Custom packet types for experimental extensions.
+pub mod registry;
+/// Client-side tracking of the characters in the player's room.
+pub mod roster;
+/// Helpers composing the packet sequences a server sends for common game events, and the rules behind them.
+pub mod server_events;
+#[cfg(test)]
+mod test_common;
+#[cfg(feature = "testing")]
+/// Deterministic packet generation for property tests and fuzz corpora, enabled by the `testing` feature.
+pub mod testing;
 
-#[cfg(feature = "tracing")]
 pub use pcap::PCap;
 
+/// The Lurk protocol revision this crate implements, as `(major, minor)`.
+///
+/// The crate's own major and minor version follow the protocol revision, so `lurk_protocol` 2.3.x speaks
+/// Lurk 2.3; the patch version is the crate's own. See [`PktVersion::current`] for the matching `VERSION` packet.
+pub const PROTOCOL_VERSION: (u8, u8) = (2, 3);
+
 /// Serialize a packet and write it directly to a [`TcpStream`].
+///
+/// If the peer has hung up (the write fails with `BrokenPipe`, `ConnectionReset`, or `ConnectionAborted`),
+/// the error is reported as [`ErrorKind::NotConnected`](std::io::ErrorKind::NotConnected) so it can be told
+/// apart from transient failures and the player's state dropped.
 pub fn send_to<'a>(
     stream: &TcpStream,
     packet: &(impl Parser<'a> + std::fmt::Display),
 ) -> Result<(), std::io::Error> {
-    let mut buf = Vec::new();
+    let buf = packet.encode_to_vec()?;
 
+    write_encoded(stream, packet, &buf)
+}
+
+/// Serialize a packet straight into a [`TcpStream`], without first building the whole packet in memory.
+///
+/// Long descriptions are written directly from the packet rather than copied, which keeps memory use down
+/// when sending large `ROOM`, `CHARACTER`, or `GAME` packets. The packet may reach the socket in several writes,
+/// so only use this when no other thread writes to the same stream; otherwise packets can interleave on the wire.
+/// A [`WriteHalf`] or [`send_to`] always writes each packet at once.
+///
+/// ```no_run
+/// use lurk_protocol::{PktRoom, PktType, send_streaming};
+/// use std::net::TcpStream;
+///
+/// let stream = TcpStream::connect("127.0.0.1:8080").unwrap();
+/// let description = "A vast cavern. ".repeat(4000);
+/// let room = PktRoom {
+///     packet_type: PktType::ROOM,
+///     room_number: 1,
+///     room_name: "Cavern".into(),
+///     description_len: description.len() as u16,
+///     description: description.into(),
+/// };
+///
+/// send_streaming(&stream, &room).unwrap();
+/// ```
+pub fn send_streaming<'a>(
+    stream: &TcpStream,
+    packet: &(impl Parser<'a> + std::fmt::Display),
+) -> Result<(), std::io::Error> {
     #[cfg(feature = "tracing")]
     tracing::info!("Sending packet: {}", packet);
 
-    packet.write_to(&mut buf)?;
+    let mut writer = std::io::BufWriter::new(stream);
+
+    packet
+        .write_to(&mut writer)
+        .and_then(|_| writer.flush())
+        .map_err(disconnected)
+}
+
+/// Write an already serialized packet to a [`TcpStream`], logging it when tracing is enabled.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn write_encoded(
+    stream: &TcpStream,
+    packet: &impl std::fmt::Display,
+    buf: &[u8],
+) -> Result<(), std::io::Error> {
+    #[cfg(feature = "tracing")]
+    tracing::info!("Sending packet: {}", packet);
 
     #[cfg(feature = "tracing")]
-    tracing::trace!("Packet:\n{}", PCap::build(buf.clone()));
+    tracing::trace!("Packet:\n{}", PCap::build(buf));
 
     let mut writer = stream;
-    writer.write_all(&buf)
+    writer.write_all(buf).map_err(disconnected)
+}
+
+/// Report a write error caused by the peer hanging up as [`ErrorKind::NotConnected`](std::io::ErrorKind::NotConnected).
+pub(crate) fn disconnected(e: std::io::Error) -> std::io::Error {
+    match e.kind() {
+        std::io::ErrorKind::BrokenPipe
+        | std::io::ErrorKind::ConnectionReset
+        | std::io::ErrorKind::ConnectionAborted => {
+            std::io::Error::new(std::io::ErrorKind::NotConnected, "Peer disconnected")
+        }
+        _ => e,
+    }
 }
@@ -1,8 +1,12 @@
 use serde::{Deserialize, Serialize};
 
 /// Represents possible error codes for the Lurk protocol.
+///
+/// Codes 0 through 8 are the complete set defined by the protocol; any other code received is read as [`LurkError::OTHER`].
+/// New codes may be added in minor releases, so matches need a wildcard arm.
 #[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
+#[non_exhaustive]
 pub enum LurkError {
     #[default]
     /// Not covered by any other error codes
@@ -24,6 +28,30 @@ pub enum LurkError {
     /// No player vs. player combat on the server. Servers do not have to support player-vs-player combat.
     NOPLAYERCOMBAT,
 }
+
+impl LurkError {
+    /// Every error code, in wire order.
+    ///
+    /// ```rust
+    /// use lurk_protocol::LurkError;
+    ///
+    /// for (code, err) in LurkError::ALL.iter().enumerate() {
+    ///     assert_eq!(u8::from(*err) as usize, code);
+    /// }
+    /// ```
+    pub const ALL: [LurkError; 9] = [
+        LurkError::OTHER,
+        LurkError::BADROOM,
+        LurkError::PLAYEREXISTS,
+        LurkError::BADMONSTER,
+        LurkError::STATERROR,
+        LurkError::NOTREADY,
+        LurkError::NOTARGET,
+        LurkError::NOFIGHT,
+        LurkError::NOPLAYERCOMBAT,
+    ];
+}
+
 impl From<LurkError> for u8 {
     /// Converts a `LurkError` enum variant into its corresponding `u8` value.
     ///     
@@ -86,6 +114,8 @@ impl std::fmt::Display for LurkError {
     }
 }
 
+impl std::error::Error for LurkError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +182,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn all_variants_roundtrip() {
+        for err in LurkError::ALL {
+            assert_eq!(
+                LurkError::from(u8::from(err)),
+                err,
+                "roundtrip failed for {}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn all_codes_are_distinct_and_contiguous() {
+        let codes: Vec<u8> = LurkError::ALL.iter().map(|&e| e.into()).collect();
+        assert_eq!(codes, (0..LurkError::ALL.len() as u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unknown_codes_map_to_other_and_back_to_zero() {
+        for code in 9u8..=255u8 {
+            assert_eq!(u8::from(LurkError::from(code)), 0, "code {}", code);
+        }
+    }
+
+    // ── std::error::Error ────────────────────────────────────────────
+    #[test]
+    fn boxes_as_dyn_error() {
+        fn refuse() -> Result<(), Box<dyn std::error::Error>> {
+            Err(LurkError::NOTREADY)?
+        }
+
+        let err = refuse().expect_err("Must fail");
+        assert_eq!(err.to_string(), "NotReady");
+        assert_eq!(err.downcast_ref::<LurkError>(), Some(&LurkError::NOTREADY));
+    }
+
     // ── Display ──────────────────────────────────────────────────────
     #[test]
     fn display_all_variants() {
@@ -165,4 +232,12 @@ mod tests {
         assert_eq!(format!("{}", LurkError::NOFIGHT), "NoFight");
         assert_eq!(format!("{}", LurkError::NOPLAYERCOMBAT), "NoPlayerCombat");
     }
+
+    #[test]
+    fn display_names_are_unique() {
+        let mut names: Vec<String> = LurkError::ALL.iter().map(|e| e.to_string()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), LurkError::ALL.len());
+    }
 }
@@ -0,0 +1,72 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::PktConnection;
+
+/// A client-side map of the rooms seen so far and the exits between them, built from incoming `CONNECTION` packets.
+///
+/// A `CONNECTION` names the destination of an exit but not the room it leaves from, so each one is applied
+/// together with the room the player was in when it arrived.
+///
+/// ```
+/// use lurk_protocol::{Map, PktConnection};
+///
+/// let mut map = Map::new();
+/// map.apply(1, &PktConnection::new(2, "Hallway", ""));
+/// map.apply(1, &PktConnection::new(3, "Cellar", ""));
+///
+/// assert!(map.can_move_to(1, 3));
+/// assert!(!map.can_move_to(2, 3));
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct Map {
+    exits: BTreeMap<u16, BTreeSet<u16>>,
+}
+
+impl Map {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the exit described by a `CONNECTION` received while in room `from`.
+    pub fn apply(&mut self, from: u16, connection: &PktConnection) {
+        self.exits
+            .entry(from)
+            .or_default()
+            .insert(connection.room_number);
+    }
+
+    /// Whether a known exit leads from `current` to `target`.
+    ///
+    /// Exits are one-way; the way back is only known once the server has sent it.
+    pub fn can_move_to(&self, current: u16, target: u16) -> bool {
+        self.exits
+            .get(&current)
+            .is_some_and(|exits| exits.contains(&target))
+    }
+
+    /// Iterate over the known exits from a room, in ascending order.
+    pub fn exits(&self, room: u16) -> impl Iterator<Item = u16> + '_ {
+        self.exits.get(&room).into_iter().flatten().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Exits are recorded per room and only in the direction they were announced.
+    #[test]
+    fn map_one_way_exits() {
+        let mut map = Map::new();
+        map.apply(1, &PktConnection::new(2, "Hallway", ""));
+        map.apply(2, &PktConnection::new(3, "Stairs", ""));
+        map.apply(1, &PktConnection::new(2, "Hallway", ""));
+
+        assert_eq!(map.exits(1).collect::<Vec<_>>(), [2]);
+        assert!(map.can_move_to(2, 3));
+        assert!(!map.can_move_to(2, 1));
+        assert!(!map.can_move_to(9, 1));
+        assert_eq!(map.exits(9).count(), 0);
+    }
+}
@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::LurkError;
+
+/// A server-side set of the player names in use, for refusing a second player with the same name.
+///
+/// Register a name when its `CHARACTER` is accepted and release it when the player disconnects, so the name can be
+/// taken again, e.g. by the same player reconnecting. Names are compared exactly unless the registry was created
+/// with [`NameRegistry::case_insensitive`].
+///
+/// ```
+/// use lurk_protocol::{LurkError, NameRegistry};
+///
+/// let mut names = NameRegistry::case_insensitive();
+/// names.try_register("Hero").unwrap();
+/// assert_eq!(names.try_register("HERO"), Err(LurkError::PLAYEREXISTS));
+///
+/// // The player disconnects
+/// names.release("Hero");
+/// assert!(names.try_register("HERO").is_ok());
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct NameRegistry {
+    names: HashSet<String>,
+    case_insensitive: bool,
+}
+
+impl NameRegistry {
+    /// Create an empty registry that compares names exactly.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty registry that treats names differing only in case as the same name.
+    pub fn case_insensitive() -> Self {
+        Self {
+            case_insensitive: true,
+            ..Self::default()
+        }
+    }
+
+    /// Claim `name`, failing with [`LurkError::PLAYEREXISTS`] if it is already in use.
+    pub fn try_register(&mut self, name: &str) -> Result<(), LurkError> {
+        let key = self.key(name).into_owned();
+
+        if !self.names.insert(key) {
+            return Err(LurkError::PLAYEREXISTS);
+        }
+
+        Ok(())
+    }
+
+    /// Release `name` so it can be registered again. Returns whether it was registered.
+    pub fn release(&mut self, name: &str) -> bool {
+        let key = self.key(name);
+
+        self.names.remove(key.as_ref())
+    }
+
+    /// Whether `name` is in use.
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(self.key(name).as_ref())
+    }
+
+    /// The number of names in use.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no names are in use.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    fn key<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if self.case_insensitive {
+            Cow::Owned(name.to_lowercase())
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// By default names differing only in case are distinct players.
+    #[test]
+    fn names_case_sensitive() {
+        let mut names = NameRegistry::new();
+
+        assert_eq!(names.try_register("Hero"), Ok(()));
+        assert_eq!(names.try_register("hero"), Ok(()));
+        assert_eq!(names.try_register("Hero"), Err(LurkError::PLAYEREXISTS));
+        assert_eq!(names.len(), 2);
+    }
+
+    /// A case-insensitive registry refuses a name differing only in case, and releases it whatever its case.
+    #[test]
+    fn names_case_insensitive() {
+        let mut names = NameRegistry::case_insensitive();
+
+        assert_eq!(names.try_register("Hero"), Ok(()));
+        assert_eq!(names.try_register("hErO"), Err(LurkError::PLAYEREXISTS));
+        assert!(names.contains("HERO"));
+
+        assert!(names.release("HERO"));
+        assert!(!names.contains("Hero"));
+    }
+
+    /// A released name can be taken again, as by a player reconnecting; releasing an unknown name does nothing.
+    #[test]
+    fn names_release_on_disconnect() {
+        let mut names = NameRegistry::new();
+        names.try_register("Hero").unwrap();
+
+        assert!(names.release("Hero"));
+        assert!(!names.release("Hero"));
+        assert!(names.is_empty());
+        assert_eq!(names.try_register("Hero"), Ok(()));
+    }
+}
@@ -1,9 +1,7 @@
-use std::io::ErrorKind::UnexpectedEof;
-use std::{
-    io::{Error, Read, Write},
-    net::TcpStream,
-    sync::Arc,
-};
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::ErrorKind::{InvalidData, InvalidInput, TimedOut, UnexpectedEof, WouldBlock};
+use std::io::{Error, Read, Write};
 
 #[cfg(feature = "tracing")]
 use crate::pcap::PCap;
@@ -12,6 +10,7 @@ use tracing::debug;
 #[cfg(feature = "tracing")]
 use tracing::trace;
 
+use crate::Protocol;
 use crate::pkt_type::PktType;
 
 /// Module for handling accept packets.
@@ -47,7 +46,7 @@ pub mod version;
 ///
 /// ```no_run
 /// use lurk_protocol::{Packet, Parser, PktType};
-/// use std::io::{Error, Write};
+/// use std::io::{Error, ErrorKind, Write};
 /// use serde::{Deserialize, Serialize};
 ///
 /// #[derive(Serialize, Deserialize)]
@@ -73,16 +72,21 @@ pub mod version;
 ///         Ok(())
 ///     }
 ///
-///     fn decode(packet: Packet) -> Self {
+///     fn decode(packet: Packet) -> Result<Self, Error> {
+///         let Some(name) = packet.body.get(0..32) else {
+///             return Err(Error::new(ErrorKind::UnexpectedEof, "LOOT body is too short"));
+///         };
+///
 ///         let message_type = packet.packet_type;
-///         let target_name = String::from_utf8_lossy(&packet.body[0..32])
-///             .trim_end_matches('\0')
-///             .into();
+///         let target_name = String::from_utf8_lossy(name)
+///             .split('\0')
+///             .take(1)
+///             .collect();
 ///
-///         Self {
+///         Ok(Self {
 ///             message_type,
 ///             target_name,
-///         }
+///         })
 ///     }
 /// }
 /// ```
@@ -107,8 +111,37 @@ pub trait Parser<'a>: Sized + 'a {
     /// ```
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
 
+    /// Serializes the packet like [`write_to`](Parser::write_to), handling names that are too long for their
+    /// fixed-width field according to `policy`. `write_to` uses [`NamePolicy::default()`].
+    ///
+    /// Packets without names ignore the policy.
+    ///
+    /// ```
+    /// use lurk_protocol::{NamePolicy, Parser, PktLoot};
+    ///
+    /// let loot = PktLoot::new("An exceedingly long monster name, even for a dragon");
+    ///
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// assert!(loot.write_with_policy(&mut buffer, NamePolicy::Error).is_err());
+    /// ```
+    fn write_with_policy<W: Write>(&self, writer: &mut W, policy: NamePolicy) -> Result<(), Error> {
+        let _ = policy;
+        self.write_to(writer)
+    }
+
     /// Deserializes a Packet into the implementing type.
     ///
+    /// Text fields (names, descriptions, messages) are decoded with [`String::from_utf8_lossy`],
+    /// so any bytes that are not valid UTF-8 (e.g. a Latin-1 room description) are replaced with `U+FFFD`.
+    /// Such packets will not re-serialize to the same bytes; servers that need the original bytes
+    /// should keep the raw buffer around themselves.
+    ///
+    /// A body shorter than the packet's fixed length fails with
+    /// [`ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) rather than
+    /// panicking, so a [`Packet`] built by hand from untrusted bytes is safe to decode. Trailing data past the
+    /// fixed part is taken as it is; the packet's `TryFrom<&[u8]>` and
+    /// [`Protocol::from_bytes`](crate::Protocol::from_bytes) also check it against the length field.
+    ///
     /// ```no_run
     /// use lurk_protocol::{Protocol, PktType, PktMessage, Packet, Parser};
     /// use std::io::{Read, Error, ErrorKind};
@@ -119,24 +152,177 @@ pub trait Parser<'a>: Sized + 'a {
     ///
     /// let mut buffer = [0; 1];
     /// stream.as_ref().read_exact(&mut buffer).unwrap();
-    /// let packet_type = PktType::from(&buffer);
+    /// let packet_type = PktType::try_from_byte(buffer[0]).unwrap();
     ///
     /// // Match the type of the packet to the enum Type
     /// let packet: Result<Protocol, Error> = match packet_type {
     ///     PktType::MESSAGE => {
     ///        let mut buffer = vec![0; 66];
     ///
-    ///        let pkt = Packet::read_extended(&stream, packet_type, &mut buffer, (0, 1)).unwrap();
+    ///        let pkt = Packet::read_extended(&mut stream.as_ref(), packet_type, &mut buffer, (0, 1)).unwrap();
     ///
-    ///        Ok(Protocol::Message(
-    ///            PktMessage::decode(pkt),
-    ///        ))
+    ///        PktMessage::decode(pkt).map(Protocol::Message)
     ///    },
     ///     _ => todo!("Handle other packet types"),
-    ///     PktType::DEFAULT => Err(Error::new(ErrorKind::Unsupported, "Invalid packet type")),
+    ///     PktType::DEFAULT => Err(Error::new(ErrorKind::Unsupported, format!("Invalid packet type {}", buffer[0]))),
     /// };
     /// ```
-    fn decode(packet: Packet) -> Self;
+    fn decode(packet: Packet) -> Result<Self, Error>;
+
+    /// Serializes the packet into a new byte vector.
+    ///
+    /// ```
+    /// use lurk_protocol::{Parser, PktChangeRoom};
+    ///
+    /// let bytes = PktChangeRoom::from(5u16).encode_to_vec().unwrap();
+    /// assert_eq!(bytes, vec![0x02, 0x05, 0x00]);
+    /// ```
+    fn encode_to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Serializes the packet into a compact, space-separated hex string.
+    ///
+    /// Handy for logging a packet inline without the full `PCap` table.
+    ///
+    /// ```
+    /// use lurk_protocol::{Parser, PktChangeRoom};
+    ///
+    /// let hex = PktChangeRoom::from(5u16).to_hex().unwrap();
+    /// assert_eq!(hex, "02 05 00");
+    /// ```
+    fn to_hex(&self) -> Result<String, Error> {
+        let bytes = self.encode_to_vec()?;
+        let mut hex = String::with_capacity(bytes.len() * 3);
+
+        for (i, byte) in bytes.iter().enumerate() {
+            if i > 0 {
+                hex.push(' ');
+            }
+            write!(hex, "{:02x}", byte).map_err(Error::other)?;
+        }
+
+        Ok(hex)
+    }
+}
+
+/// Decode a fixed-width name field, stopping at the first NUL byte.
+///
+/// Names are padded with NUL bytes on the wire, so everything from the first NUL onward is dropped,
+/// including any content after an interior NUL. Every packet with a name field decodes it this way.
+/// The name is borrowed from `bytes` when it is valid UTF-8.
+pub(crate) fn borrow_name(bytes: &[u8]) -> Cow<'_, str> {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..end])
+}
+
+/// How a name longer than its fixed-width field is handled when a packet is serialized.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NamePolicy {
+    #[default]
+    /// Cut the name at the last whole `char` that fits, so it stays valid UTF-8.
+    TruncateChar,
+    /// Cut the name at exactly the field width, even if that splits a multi-byte `char`.
+    TruncateByte,
+    /// Fail with [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput).
+    Error,
+}
+
+/// Encode a name into a NUL-padded field of `width` bytes, applying `policy` if it does not fit.
+pub(crate) fn encode_name(name: &str, width: usize, policy: NamePolicy) -> Result<Vec<u8>, Error> {
+    let mut end = name.len().min(width);
+
+    match policy {
+        _ if end == name.len() => {}
+        NamePolicy::TruncateChar => {
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+        }
+        NamePolicy::TruncateByte => {}
+        NamePolicy::Error => {
+            return Err(Error::new(
+                InvalidInput,
+                format!("Name \"{}\" is longer than {} bytes", name, width),
+            ));
+        }
+    }
+
+    let mut bytes = name.as_bytes()[..end].to_vec();
+    bytes.resize(width, 0x00);
+
+    Ok(bytes)
+}
+
+/// Truncate a variable-length field so its byte length fits in the `u16` length prefix.
+///
+/// The cut is made on a `char` boundary at or below `u16::MAX` bytes, so the result is still valid UTF-8
+/// and `result.len() as u16` never wraps.
+pub(crate) fn clamp_to_u16_len(text: &str) -> &str {
+    if text.len() <= u16::MAX as usize {
+        return text;
+    }
+
+    let mut end = u16::MAX as usize;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    &text[..end]
+}
+
+/// The little-endian `u16` length prefix of a variable-length field, computed from the text being written.
+///
+/// Serializers use this instead of the stored length field, so a stale length can never reach the wire.
+/// Fails with [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput) if the text does not fit.
+pub(crate) fn encode_len(text: &str) -> Result<[u8; 2], Error> {
+    u16::try_from(text.len())
+        .map(u16::to_le_bytes)
+        .map_err(|_| {
+            Error::new(
+                InvalidInput,
+                format!("Text of {} bytes does not fit a u16 length", text.len()),
+            )
+        })
+}
+
+/// Check that `body` holds at least the fixed part of a `packet_type` body, so its fields can be indexed.
+///
+/// Fails with [`ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) naming the type if it does not.
+pub(crate) fn check_body(body: &[u8], packet_type: PktType) -> Result<(), Error> {
+    let fixed_len = Protocol::body_layout(packet_type).map_or(0, |(fixed_len, _)| fixed_len);
+
+    if body.len() < fixed_len {
+        return Err(Error::new(
+            UnexpectedEof,
+            format!(
+                "{} body of {} bytes is shorter than its {} fixed bytes",
+                packet_type,
+                body.len(),
+                fixed_len
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Report a failure partway through a packet. A timeout keeps its kind, so it is not mistaken for the peer hanging up.
+pub(crate) fn partial_read(e: Error, what: &str) -> Error {
+    match e.kind() {
+        WouldBlock | TimedOut => Error::new(
+            TimedOut,
+            format!(
+                "Timed out partway through the {}; the rest of the packet is still in flight",
+                what
+            ),
+        ),
+        _ => Error::new(UnexpectedEof, format!("Failed to read {}: {}", what, e)),
+    }
 }
 
 /// Represents a network packet containing a reference to the TCP stream, packet type, and body.
@@ -158,21 +344,58 @@ impl<'a> Packet<'a> {
         }
     }
 
-    /// Read the stream into a packet with a fixed length.
+    /// Split a complete packet, type byte included, into a `Packet` of the `expected` type.
+    ///
+    /// Fails with [`ErrorKind::InvalidData`](std::io::ErrorKind::InvalidData) if the type byte does not match
+    /// `expected` or if `bytes` runs on past the end of the packet, and as [`Protocol::from_bytes`] does if the
+    /// packet is incomplete. The length is taken from [`Protocol::frame_len`], so the body always holds exactly
+    /// what its fixed length and length field describe.
+    pub(crate) fn parse(bytes: &'a [u8], expected: PktType) -> Result<Packet<'a>, Error> {
+        let Some(&type_byte) = bytes.first() else {
+            return Err(Error::new(UnexpectedEof, "Empty packet"));
+        };
+
+        if type_byte != u8::from(expected) {
+            return Err(Error::new(
+                InvalidData,
+                format!(
+                    "Expected {} packet, found type byte {}",
+                    expected, type_byte
+                ),
+            ));
+        }
+
+        let len = Protocol::frame_len(bytes)?;
+
+        if bytes.len() != len {
+            return Err(Error::new(
+                InvalidData,
+                format!(
+                    "{} packet is {} bytes, but {} bytes were given",
+                    expected,
+                    len,
+                    bytes.len()
+                ),
+            ));
+        }
+
+        Ok(Packet::new(expected, &bytes[1..]))
+    }
+
+    /// Read the reader into a packet with a fixed length, e.g. from a `&TcpStream` after the type byte.
     /// This function reads the packet body based on the provided buffer length.
-    pub fn read_into<'b>(
-        stream: &'b Arc<TcpStream>,
+    pub fn read_into<'b, R: Read>(
+        reader: &mut R,
         packet_type: PktType,
         buffer: &'b mut [u8],
     ) -> Result<Packet<'b>, Error> {
         // Read the remaining bytes for the packet
-        stream
-            .as_ref()
+        reader
             .read_exact(buffer)
-            .map_err(|e| Error::new(UnexpectedEof, format!("Failed to read packet body: {}", e)))?;
+            .map_err(|e| partial_read(e, "packet body"))?;
 
         #[cfg(feature = "tracing")]
-        trace!("Packet body:\n{}", PCap::build(buffer.to_vec()));
+        trace!("Packet body:\n{}", PCap::build(buffer));
 
         // Create a new packet with the read bytes
         let packet = Packet::new(packet_type, buffer);
@@ -183,29 +406,36 @@ impl<'a> Packet<'a> {
     /// Read the packet with a varied length.
     /// This function reads the packet body and then reads the extended description or data
     /// based on the provided index.
-    pub fn read_extended<'b>(
-        stream: &'b Arc<TcpStream>,
+    pub fn read_extended<'b, R: Read>(
+        reader: &mut R,
         packet_type: PktType,
         buffer: &'b mut Vec<u8>,
         index: (usize, usize),
     ) -> Result<Packet<'b>, Error> {
-        stream
-            .as_ref()
+        reader
             .read_exact(buffer)
-            .map_err(|e| Error::new(UnexpectedEof, format!("Failed to read packet body: {}", e)))?;
+            .map_err(|e| partial_read(e, "packet body"))?;
 
         // Get the description length from the buffer
         let length = u16::from_le_bytes([buffer[index.0], buffer[index.1]]) as usize;
+
+        // On 16-bit targets the body and description together may not fit in usize
+        if buffer.len().checked_add(length).is_none() {
+            return Err(Error::new(
+                InvalidData,
+                format!("Description length {} does not fit in usize", length),
+            ));
+        }
+
         let mut desc = vec![0u8; length];
 
         #[cfg(feature = "tracing")]
         debug!("Description len {}: ({}, {})", length, index.0, index.1);
 
         // Read the description from the stream
-        stream
-            .as_ref()
+        reader
             .read_exact(&mut desc)
-            .map_err(|e| Error::new(UnexpectedEof, format!("Failed to read descriptor: {}", e)))?;
+            .map_err(|e| partial_read(e, "descriptor"))?;
 
         #[cfg(feature = "tracing")]
         if !desc.is_empty() {
@@ -222,3 +452,361 @@ impl<'a> Packet<'a> {
         Ok(packet)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        InvalidData, InvalidInput, NamePolicy, Packet, UnexpectedEof, borrow_name, encode_name,
+    };
+    use crate::{
+        CharacterFlags, LurkError, Parser, PktAccept, PktChangeRoom, PktCharacter, PktConnection,
+        PktError, PktFight, PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight, PktRoom, PktStart,
+        PktType, PktVersion,
+    };
+
+    /// Serialize a packet and return its length on the wire, type byte included.
+    fn wire_len<'a>(packet: &impl Parser<'a>) -> usize {
+        packet.encode_to_vec().expect("Encoding failed").len()
+    }
+
+    // Each expected length is written as 1 (type byte) + the fixed body size read by
+    // `Protocol::recv` + the variable-length field, so a change to either side fails here.
+
+    #[test]
+    fn wire_len_message() {
+        let pkt = PktMessage::server("Recipient", "Hello");
+        assert_eq!(wire_len(&pkt), 1 + 66 + 5);
+
+        let pkt = PktMessage::narrator("Recipient", "Hello");
+        assert_eq!(wire_len(&pkt), 1 + 66 + 5);
+    }
+
+    #[test]
+    fn wire_len_change_room() {
+        assert_eq!(wire_len(&PktChangeRoom::from(1u16)), 1 + 2);
+    }
+
+    #[test]
+    fn wire_len_fight() {
+        assert_eq!(wire_len(&PktFight::default()), 1);
+    }
+
+    #[test]
+    fn wire_len_pvp_fight() {
+        assert_eq!(wire_len(&PktPVPFight::new("Target")), 1 + 32);
+    }
+
+    #[test]
+    fn wire_len_loot() {
+        assert_eq!(wire_len(&PktLoot::new("Target")), 1 + 32);
+    }
+
+    #[test]
+    fn wire_len_start() {
+        assert_eq!(wire_len(&PktStart::default()), 1);
+    }
+
+    #[test]
+    fn wire_len_error() {
+        let pkt = PktError::new(LurkError::BADROOM, "Bad room");
+        assert_eq!(wire_len(&pkt), 1 + 3 + 8);
+    }
+
+    #[test]
+    fn wire_len_accept() {
+        assert_eq!(wire_len(&PktAccept::new(PktType::CHARACTER)), 1 + 1);
+    }
+
+    #[test]
+    fn wire_len_room() {
+        let pkt = PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: 1,
+            room_name: "Hall".into(),
+            description_len: 11,
+            description: "A long hall".into(),
+        };
+        assert_eq!(wire_len(&pkt), 1 + 36 + 11);
+    }
+
+    #[test]
+    fn wire_len_character() {
+        let pkt = PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: "Hero".into(),
+            flags: CharacterFlags::reset(),
+            attack: 10,
+            defense: 10,
+            regen: 5,
+            health: 100,
+            gold: 0,
+            current_room: 0,
+            description_len: 7,
+            description: "A hero.".into(),
+        };
+        assert_eq!(wire_len(&pkt), 1 + 47 + 7);
+    }
+
+    #[test]
+    fn wire_len_game() {
+        let pkt = PktGame {
+            packet_type: PktType::GAME,
+            initial_points: 100,
+            stat_limit: 65535,
+            description_len: 9,
+            description: "Welcome!!".into(),
+        };
+        assert_eq!(wire_len(&pkt), 1 + 6 + 9);
+    }
+
+    #[test]
+    fn wire_len_leave() {
+        assert_eq!(wire_len(&PktLeave::default()), 1);
+    }
+
+    #[test]
+    fn wire_len_connection() {
+        let pkt = PktConnection {
+            packet_type: PktType::CONNECTION,
+            room_number: 2,
+            room_name: "Cellar".into(),
+            description_len: 5,
+            description: "Damp.".into(),
+        };
+        assert_eq!(wire_len(&pkt), 1 + 36 + 5);
+    }
+
+    #[test]
+    fn wire_len_version() {
+        let pkt = PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: 2,
+            minor_rev: 3,
+            extensions_len: 0,
+            extensions: None,
+        };
+        assert_eq!(wire_len(&pkt), 1 + 4);
+
+        let pkt = PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: 2,
+            minor_rev: 3,
+            extensions_len: 6,
+            extensions: Some(vec![0x04, 0x00, 0x54, 0x65, 0x73, 0x74]),
+        };
+        assert_eq!(wire_len(&pkt), 1 + 4 + 6);
+    }
+
+    /// Short names are padded and unaffected by the policy.
+    #[test]
+    fn encode_name_pads() {
+        for policy in [
+            NamePolicy::TruncateChar,
+            NamePolicy::TruncateByte,
+            NamePolicy::Error,
+        ] {
+            let bytes = encode_name("Bob", 8, policy).expect("Encoding failed");
+            assert_eq!(bytes, b"Bob\0\0\0\0\0");
+        }
+    }
+
+    /// A name that exactly fills the field is not an overflow.
+    #[test]
+    fn encode_name_exact_fit() {
+        let bytes = encode_name("Goblin", 6, NamePolicy::Error).expect("Encoding failed");
+        assert_eq!(bytes, b"Goblin");
+    }
+
+    /// The default policy never splits a multi-byte character.
+    #[test]
+    fn encode_name_truncate_char() {
+        // "é" is two bytes, straddling the end of the field
+        let bytes = encode_name("Café", 4, NamePolicy::TruncateChar).expect("Encoding failed");
+        assert_eq!(bytes, b"Caf\0");
+        assert!(std::str::from_utf8(&bytes).is_ok());
+    }
+
+    /// Byte truncation cuts at exactly the field width.
+    #[test]
+    fn encode_name_truncate_byte() {
+        let bytes = encode_name("Café", 4, NamePolicy::TruncateByte).expect("Encoding failed");
+        assert_eq!(bytes, &"Café".as_bytes()[..4]);
+    }
+
+    /// The strict policy rejects an overlong name.
+    #[test]
+    fn encode_name_error() {
+        let err = encode_name("Glorfindel", 4, NamePolicy::Error).expect_err("Must fail");
+        assert_eq!(err.kind(), InvalidInput);
+    }
+
+    /// Every name field is cut at the first NUL, dropping anything after an interior NUL. Only the NUL padding
+    /// is removed, so a trailing space is kept.
+    #[test]
+    fn borrow_name_interior_nul() {
+        let mut field = b"Alice\0Bob".to_vec();
+        field.resize(32, 0x00);
+        assert_eq!(borrow_name(&field), "Alice");
+
+        assert_eq!(borrow_name(b"Hall \0\0\0"), "Hall ");
+        assert_eq!(borrow_name(&[b'A'; 32]), "A".repeat(32));
+        assert!(matches!(
+            borrow_name(b"Bob\0\0"),
+            std::borrow::Cow::Borrowed("Bob")
+        ));
+        assert_eq!(borrow_name(b"G\xFFb\0"), "G\u{FFFD}b");
+    }
+
+    /// Read the `u16` length prefix at `index` of an encoded packet, type byte included.
+    fn len_at<'a>(packet: &impl Parser<'a>, index: usize) -> u16 {
+        let bytes = packet.encode_to_vec().expect("Encoding failed");
+        u16::from_le_bytes([bytes[index], bytes[index + 1]])
+    }
+
+    /// A stale length field is ignored; the length written is that of the text.
+    #[test]
+    fn encode_len_ignores_stale_field() {
+        let message = PktMessage {
+            message_len: 0,
+            ..PktMessage::server("Recipient", "Hello")
+        };
+        assert_eq!(len_at(&message, 1), 5);
+
+        let error = PktError {
+            message_len: 99,
+            ..PktError::new(LurkError::BADROOM, "Bad room")
+        };
+        assert_eq!(len_at(&error, 2), 8);
+
+        let room = PktRoom {
+            description_len: 0,
+            ..PktRoom::new(1, "Hall", "Wide.")
+        };
+        assert_eq!(len_at(&room, 35), 5);
+
+        let character = PktCharacter {
+            description_len: 1000,
+            ..PktCharacter::new("Hero", "Brave.")
+        };
+        assert_eq!(len_at(&character, 46), 6);
+
+        let game = PktGame {
+            description_len: 0,
+            ..PktGame::new(100, 200, "Caves.")
+        };
+        assert_eq!(len_at(&game, 5), 6);
+
+        let connection = PktConnection {
+            description_len: 2,
+            ..PktConnection::new(2, "Hallway", "Long.")
+        };
+        assert_eq!(len_at(&connection, 35), 5);
+    }
+
+    /// A body shorter than its fixed part fails as `UnexpectedEof` naming the type; longer bodies pass.
+    #[test]
+    fn check_body_short() {
+        let err = super::check_body(&[0; 35], PktType::ROOM).expect_err("Short body must fail");
+        assert_eq!(err.kind(), UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "Room body of 35 bytes is shorter than its 36 fixed bytes"
+        );
+
+        assert!(super::check_body(&[0; 40], PktType::ROOM).is_ok());
+        assert!(super::check_body(&[], PktType::FIGHT).is_ok());
+    }
+
+    /// Text too long for the prefix is rejected rather than written with a wrapped length.
+    #[test]
+    fn encode_len_too_long() {
+        let err = super::encode_len(&"A".repeat(u16::MAX as usize + 1)).expect_err("Must fail");
+        assert_eq!(err.kind(), InvalidInput);
+        assert_eq!(super::encode_len("Hi").ok(), Some([2, 0]));
+    }
+
+    /// `read_extended` reads the fixed body and then the trailing data from any reader.
+    #[test]
+    fn read_extended_from_reader() {
+        let bytes = PktError::new(LurkError::NOFIGHT, "No")
+            .encode_to_vec()
+            .unwrap();
+        let mut reader = &bytes[1..];
+        let mut buffer = vec![0; 3];
+
+        let packet = Packet::read_extended(&mut reader, PktType::ERROR, &mut buffer, (1, 2))
+            .expect("Read failed");
+
+        assert_eq!(packet.body, &bytes[1..]);
+        assert!(reader.is_empty());
+    }
+
+    /// A read that times out partway through a body keeps `TimedOut`; running out of bytes is `UnexpectedEof`.
+    #[test]
+    fn read_into_partial_read_kinds() {
+        struct Stalled;
+
+        impl std::io::Read for Stalled {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::WouldBlock.into())
+            }
+        }
+
+        let mut buffer = [0; 32];
+        let err = Packet::read_into(&mut Stalled, PktType::LOOT, &mut buffer)
+            .err()
+            .expect("Stalled read must fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let err = Packet::read_into(&mut &[0u8; 4][..], PktType::LOOT, &mut buffer)
+            .err()
+            .expect("Short read must fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    /// Check `TryFrom<&[u8]>` on a complete packet, a wrong type byte, a missing byte, and a trailing byte.
+    fn check_try_from<P>(packet: P)
+    where
+        P: for<'a> Parser<'a> + for<'b> TryFrom<&'b [u8], Error = std::io::Error>,
+    {
+        let bytes = packet.encode_to_vec().expect("Encoding failed");
+        let packet_type = PktType::from(bytes[0]);
+        let kind = |bytes: &[u8]| P::try_from(bytes).err().map(|e| e.kind());
+
+        assert_eq!(kind(&bytes), None, "{}", packet_type);
+
+        let mut wrong = bytes.clone();
+        wrong[0] = wrong[0] % 14 + 1;
+        assert_eq!(kind(&wrong), Some(InvalidData), "{}", packet_type);
+
+        assert_eq!(
+            kind(&bytes[..bytes.len() - 1]),
+            Some(UnexpectedEof),
+            "{}",
+            packet_type
+        );
+
+        let mut long = bytes.clone();
+        long.push(0);
+        assert_eq!(kind(&long), Some(InvalidData), "{}", packet_type);
+    }
+
+    /// Every packet's `TryFrom<&[u8]>` takes exactly one complete packet of its own type.
+    #[test]
+    fn try_from_every_type() {
+        check_try_from(PktMessage::new("Sender", "Recipient", "Hello"));
+        check_try_from(PktChangeRoom::new(3));
+        check_try_from(PktFight::new());
+        check_try_from(PktPVPFight::new("Rival"));
+        check_try_from(PktLoot::new("Goblin"));
+        check_try_from(PktStart::new());
+        check_try_from(PktError::new(LurkError::NOFIGHT, "No"));
+        check_try_from(PktAccept::new(PktType::FIGHT));
+        check_try_from(PktRoom::new(1, "Hall", "A wide hall."));
+        check_try_from(PktCharacter::new("Hero", "Brave."));
+        check_try_from(PktGame::new(100, 200, "Caves."));
+        check_try_from(PktLeave::new());
+        check_try_from(PktConnection::new(2, "Kitchen", "Warm."));
+        check_try_from(PktVersion::with_extensions(2, 3, ["SEQ"]).expect("Build failed"));
+    }
+}
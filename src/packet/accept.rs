@@ -1,3 +1,4 @@
+use crate::packet::check_body;
 use crate::pkt_type::PktType;
 use crate::{Packet, Parser};
 use serde::{Deserialize, Serialize};
@@ -7,7 +8,7 @@ use std::io::Write;
 ///
 /// This is not needed for actions which cause other results, such as changing rooms or beginning a fight.
 /// It should be sent in response to clients sending messages, setting character stats, etc.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PktAccept {
     /// The type of message for the `ACCEPT` packet. Default is 8.
     pub packet_type: PktType,
@@ -65,18 +66,31 @@ impl Parser<'_> for PktAccept {
         packet.extend(self.accept_type.to_le_bytes());
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        check_body(packet.body, PktType::ACCEPT)?;
+
+        Ok(Self {
             packet_type: packet.packet_type,
             accept_type: packet.body[0],
-        }
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktAccept {
+    type Error = std::io::Error;
+
+    /// Parse a complete `ACCEPT` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::ACCEPT`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::ACCEPT).and_then(PktAccept::decode)
     }
 }
 
@@ -93,7 +107,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktAccept
-        let message = PktAccept::decode(packet);
+        let message = PktAccept::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::ACCEPT);
@@ -113,7 +127,7 @@ mod tests {
     fn accept_parse_trace_character() {
         let body: &[u8] = &[0x0a]; // CHARACTER = 10
         let packet = Packet::new(PktType::ACCEPT, body);
-        let acc = PktAccept::decode(packet);
+        let acc = PktAccept::decode(packet).expect("Decoding failed");
 
         assert_eq!(acc.accept_type, 10);
         assert_eq!(acc.accept_type, u8::from(PktType::CHARACTER));
@@ -149,7 +163,7 @@ mod tests {
             acc.write_to(&mut buffer).expect("Encoding failed");
 
             let packet = Packet::new(PktType::ACCEPT, &buffer[1..]);
-            let deserialized = PktAccept::decode(packet);
+            let deserialized = PktAccept::decode(packet).expect("Decoding failed");
             assert_eq!(deserialized.accept_type, u8::from(pkt_type));
         }
     }
@@ -159,7 +173,7 @@ mod tests {
     fn accept_max_value() {
         let body: &[u8] = &[0xFF];
         let packet = Packet::new(PktType::ACCEPT, body);
-        let acc = PktAccept::decode(packet);
+        let acc = PktAccept::decode(packet).expect("Decoding failed");
 
         assert_eq!(acc.accept_type, 255);
     }
@@ -169,7 +183,7 @@ mod tests {
     fn accept_zero_value() {
         let body: &[u8] = &[0x00];
         let packet = Packet::new(PktType::ACCEPT, body);
-        let acc = PktAccept::decode(packet);
+        let acc = PktAccept::decode(packet).expect("Decoding failed");
 
         assert_eq!(acc.accept_type, 0);
     }
@@ -187,17 +201,16 @@ mod tests {
         assert_eq!(buffer[1], u8::from(PktType::MESSAGE));
 
         let packet = Packet::new(PktType::ACCEPT, &buffer[1..]);
-        let deserialized = PktAccept::decode(packet);
+        let deserialized = PktAccept::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.accept_type, u8::from(PktType::MESSAGE));
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn accept_empty_body_panics() {
+    fn accept_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::ACCEPT, body);
-        let _ = PktAccept::decode(packet);
+        assert!(PktAccept::decode(packet).is_err());
     }
 
     /// Extra trailing bytes should be ignored.
@@ -205,7 +218,7 @@ mod tests {
     fn accept_extra_trailing_bytes() {
         let body: &[u8] = &[0x0a, 0xFF, 0xFF, 0xFF];
         let packet = Packet::new(PktType::ACCEPT, body);
-        let acc = PktAccept::decode(packet);
+        let acc = PktAccept::decode(packet).expect("Decoding failed");
 
         assert_eq!(acc.accept_type, 10);
     }
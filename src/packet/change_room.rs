@@ -1,14 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::io::Write;
 
-use crate::packet::PktType;
-use crate::{Packet, Parser};
+use crate::packet::{PktType, check_body};
+use crate::{LurkError, Map, Packet, Parser};
 
 /// Sent by the client only, to change rooms.
 ///
 /// If the server changes the room a client is in, it should send an updated room, character, and connection message(s) to explain the new location.
 /// If not, for example because the client is not ready to start or specified an inappropriate choice, and error should be sent.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PktChangeRoom {
     /// The type of message for the `CHANGEROOM` packet. Default is 2.
     pub packet_type: PktType,
@@ -16,6 +16,35 @@ pub struct PktChangeRoom {
     pub room_number: u16,
 }
 
+impl PktChangeRoom {
+    /// Create a new `PktChangeRoom` packet requesting the given room. Equivalent to `PktChangeRoom::from(room_number)`.
+    pub fn new(room_number: u16) -> Self {
+        Self::from(room_number)
+    }
+
+    /// Check the move against the client's [`Map`] before sending it, from the room the player is in.
+    ///
+    /// Returns [`LurkError::BADROOM`] if no known exit leads from `current` to the requested room, which is the
+    /// error the server would send back. The server remains the authority; a move allowed here may still fail.
+    ///
+    /// ```
+    /// use lurk_protocol::{LurkError, Map, PktChangeRoom, PktConnection};
+    ///
+    /// let mut map = Map::new();
+    /// map.apply(1, &PktConnection::new(2, "Hallway", ""));
+    ///
+    /// assert!(PktChangeRoom::new(2).checked_against(&map, 1).is_ok());
+    /// assert_eq!(PktChangeRoom::new(5).checked_against(&map, 1).err(), Some(LurkError::BADROOM));
+    /// ```
+    pub fn checked_against(self, map: &Map, current: u16) -> Result<Self, LurkError> {
+        if !map.can_move_to(current, self.room_number) {
+            return Err(LurkError::BADROOM);
+        }
+
+        Ok(self)
+    }
+}
+
 impl From<u16> for PktChangeRoom {
     // Return PktChangeRoom from provided room number
     fn from(room_number: u16) -> Self {
@@ -72,21 +101,34 @@ impl Parser<'_> for PktChangeRoom {
         packet.extend(self.room_number.to_le_bytes());
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        check_body(packet.body, PktType::CHANGEROOM)?;
+
         let room_number = u16::from_le_bytes([packet.body[0], packet.body[1]]);
 
         // Implement deserialization logic here
-        Self {
+        Ok(Self {
             packet_type: packet.packet_type,
             room_number,
-        }
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktChangeRoom {
+    type Error = std::io::Error;
+
+    /// Parse a complete `CHANGEROOM` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::CHANGEROOM`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::CHANGEROOM).and_then(PktChangeRoom::decode)
     }
 }
 
@@ -103,7 +145,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktChangeRoom
-        let message = PktChangeRoom::decode(packet);
+        let message = PktChangeRoom::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::CHANGEROOM);
@@ -139,7 +181,7 @@ mod tests {
     fn changeroom_room_zero() {
         let body: &[u8] = &[0x00, 0x00];
         let packet = Packet::new(PktType::CHANGEROOM, body);
-        let cr = PktChangeRoom::decode(packet);
+        let cr = PktChangeRoom::decode(packet).expect("Decoding failed");
         assert_eq!(cr.room_number, 0);
     }
 
@@ -148,7 +190,7 @@ mod tests {
     fn changeroom_room_one() {
         let body: &[u8] = &[0x01, 0x00];
         let packet = Packet::new(PktType::CHANGEROOM, body);
-        let cr = PktChangeRoom::decode(packet);
+        let cr = PktChangeRoom::decode(packet).expect("Decoding failed");
         assert_eq!(cr.room_number, 1);
     }
 
@@ -157,7 +199,7 @@ mod tests {
     fn changeroom_max_room() {
         let body: &[u8] = &[0xFF, 0xFF];
         let packet = Packet::new(PktType::CHANGEROOM, body);
-        let cr = PktChangeRoom::decode(packet);
+        let cr = PktChangeRoom::decode(packet).expect("Decoding failed");
         assert_eq!(cr.room_number, u16::MAX);
     }
 
@@ -170,7 +212,7 @@ mod tests {
             cr.write_to(&mut buffer).expect("Encoding failed");
 
             let packet = Packet::new(PktType::CHANGEROOM, &buffer[1..]);
-            let deserialized = PktChangeRoom::decode(packet);
+            let deserialized = PktChangeRoom::decode(packet).expect("Decoding failed");
             assert_eq!(deserialized.room_number, room, "Failed for room: {}", room);
         }
     }
@@ -184,22 +226,20 @@ mod tests {
         assert_eq!(buffer.len(), 3);
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn changeroom_body_too_short_panics() {
+    fn changeroom_body_too_short_fails() {
         let body: &[u8] = &[0x00]; // Need 2 bytes
         let packet = Packet::new(PktType::CHANGEROOM, body);
-        let _ = PktChangeRoom::decode(packet);
+        assert!(PktChangeRoom::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn changeroom_empty_body_panics() {
+    fn changeroom_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::CHANGEROOM, body);
-        let _ = PktChangeRoom::decode(packet);
+        assert!(PktChangeRoom::decode(packet).is_err());
     }
 
     /// Extra trailing bytes should be ignored.
@@ -207,7 +247,7 @@ mod tests {
     fn changeroom_extra_trailing_bytes() {
         let body: &[u8] = &[0x05, 0x00, 0xFF, 0xFF];
         let packet = Packet::new(PktType::CHANGEROOM, body);
-        let cr = PktChangeRoom::decode(packet);
+        let cr = PktChangeRoom::decode(packet).expect("Decoding failed");
         assert_eq!(cr.room_number, 5);
     }
 
@@ -219,5 +259,39 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(&json_str).expect("Invalid JSON");
         assert_eq!(parsed["room_number"], 42);
     }
+
+    /// `new` builds the same packet as `From<u16>`.
+    #[test]
+    fn changeroom_new() {
+        let packet = PktChangeRoom::new(7);
+
+        assert_eq!(packet.packet_type, PktType::CHANGEROOM);
+        assert_eq!(packet.room_number, 7);
+        assert_eq!(
+            packet.encode_to_vec().ok(),
+            PktChangeRoom::from(7).encode_to_vec().ok()
+        );
+    }
+
+    /// Only moves along a known exit from the current room pass the check.
+    #[test]
+    fn changeroom_checked_against_map() {
+        let mut map = Map::new();
+        map.apply(1, &crate::PktConnection::new(2, "Hallway", ""));
+
+        let packet = PktChangeRoom::new(2)
+            .checked_against(&map, 1)
+            .expect("Adjacent room must pass");
+        assert_eq!(packet.room_number, 2);
+
+        assert_eq!(
+            PktChangeRoom::new(2).checked_against(&map, 2).err(),
+            Some(LurkError::BADROOM)
+        );
+        assert_eq!(
+            PktChangeRoom::new(3).checked_against(&map, 1).err(),
+            Some(LurkError::BADROOM)
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
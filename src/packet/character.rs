@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::{io::Write, sync::Arc};
 
+use crate::LurkError;
 use crate::Packet;
 use crate::Parser;
 use crate::flags::CharacterFlags;
 use crate::packet::PktType;
+use crate::packet::{NamePolicy, clamp_to_u16_len, encode_len, encode_name};
+use crate::protocol_ref::PktCharacterRef;
 
 #[derive(Clone, Serialize, Deserialize)]
 /// Sent by both the client and the server.
@@ -24,6 +27,8 @@ pub struct PktCharacter {
     /// The name of the character, up to 32 bytes.
     pub name: Arc<str>,
     /// The character's flags, represented as a bitfield.
+    ///
+    /// Serialized by name (e.g. `"ALIVE | BATTLE"`) so the JSON `Display` stays readable.
     pub flags: CharacterFlags,
     /// The character's attack stat.
     pub attack: u16,
@@ -37,13 +42,60 @@ pub struct PktCharacter {
     pub gold: u16,
     /// The character's current room.
     pub current_room: u16,
-    /// The length of the character's description.
+    /// The length of `description` as decoded. Serializing ignores it and writes the length of `description` itself.
     pub description_len: u16,
     /// The character's description.
     pub description: Box<str>,
 }
 
 impl PktCharacter {
+    /// Create a new `PktCharacter` with the given name and description, no flags, and every stat zero.
+    ///
+    /// Set the remaining fields with struct update syntax, e.g. `PktCharacter { attack: 10, ..PktCharacter::new("Hero", "") }`.
+    ///
+    /// Descriptions longer than `u16::MAX` bytes are truncated on a `char` boundary so `description_len` always matches.
+    pub fn new(name: &str, description: &str) -> Self {
+        let description = clamp_to_u16_len(description);
+
+        Self {
+            packet_type: PktType::CHARACTER,
+            name: Arc::from(name),
+            flags: CharacterFlags::empty(),
+            attack: 0,
+            defense: 0,
+            regen: 0,
+            health: 0,
+            gold: 0,
+            current_room: 0,
+            description_len: description.len() as u16,
+            description: Box::from(description),
+        }
+    }
+
+    /// Start building a character named `name`; see [`PktCharacterBuilder`].
+    ///
+    /// ```
+    /// use lurk_protocol::{CharacterFlags, PktCharacter};
+    ///
+    /// let goblin = PktCharacter::builder("Goblin")
+    ///     .flags(CharacterFlags::monster())
+    ///     .attack(5)
+    ///     .defense(5)
+    ///     .health(20)
+    ///     .gold(3)
+    ///     .room(1)
+    ///     .description("Small and green.")
+    ///     .build();
+    ///
+    /// assert!(goblin.flags.is_monster());
+    /// assert_eq!(goblin.description_len, 16);
+    /// ```
+    pub fn builder(name: &str) -> PktCharacterBuilder {
+        PktCharacterBuilder {
+            character: PktCharacter::new(name, ""),
+        }
+    }
+
     /// Creates a new `PktCharacter` with default values for health, gold, current_room, and flags, cloning other fields from the incoming character.
     pub fn with_defaults_from(incoming: &PktCharacter) -> Self {
         Self {
@@ -54,6 +106,169 @@ impl PktCharacter {
             ..incoming.clone()
         }
     }
+
+    /// Sort the character into a [`Player`] or a [`Monster`] by its `MONSTER` flag.
+    ///
+    /// ```
+    /// use lurk_protocol::{CharacterFlags, CharacterKind, PktCharacter};
+    ///
+    /// let goblin = PktCharacter::builder("Goblin")
+    ///     .flags(CharacterFlags::alive() | CharacterFlags::MONSTER)
+    ///     .gold(3)
+    ///     .build();
+    ///
+    /// match goblin.classify() {
+    ///     CharacterKind::Monster(monster) => assert_eq!(monster.gold, 3),
+    ///     CharacterKind::Player(_) => unreachable!(),
+    /// }
+    /// ```
+    pub fn classify(self) -> CharacterKind {
+        if self.flags.is_monster() {
+            CharacterKind::Monster(Monster(self))
+        } else {
+            CharacterKind::Player(Player(self))
+        }
+    }
+
+    /// Whether both characters are in the same room.
+    pub fn in_same_room_as(&self, other: &PktCharacter) -> bool {
+        self.current_room == other.current_room
+    }
+
+    /// Check the protocol's preconditions for this character attacking `target`, in `FIGHT` or `PVPFIGHT`.
+    ///
+    /// Returns [`LurkError::NOTARGET`] when the target is in a different room, and [`LurkError::NOFIGHT`] when
+    /// the target is already dead. Whether the server allows player-versus-player combat at all is up to the caller.
+    ///
+    /// ```
+    /// use lurk_protocol::{CharacterFlags, LurkError, PktCharacter};
+    ///
+    /// let hero = PktCharacter { flags: CharacterFlags::alive(), current_room: 1, ..PktCharacter::new("Hero", "") };
+    /// let goblin = PktCharacter { flags: CharacterFlags::alive(), current_room: 2, ..PktCharacter::new("Goblin", "") };
+    ///
+    /// assert_eq!(hero.can_fight(&goblin), Err(LurkError::NOTARGET));
+    /// ```
+    pub fn can_fight(&self, target: &PktCharacter) -> Result<(), LurkError> {
+        if !self.in_same_room_as(target) {
+            return Err(LurkError::NOTARGET);
+        }
+
+        if !target.flags.is_alive() {
+            return Err(LurkError::NOFIGHT);
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder for a [`PktCharacter`], created by [`PktCharacter::builder`].
+///
+/// Unless set, the character has no flags, every stat zero, room 0, and an empty description.
+pub struct PktCharacterBuilder {
+    character: PktCharacter,
+}
+
+impl PktCharacterBuilder {
+    /// Set the character's flags.
+    pub fn flags(mut self, flags: CharacterFlags) -> Self {
+        self.character.flags = flags;
+        self
+    }
+
+    /// Set the attack stat.
+    pub fn attack(mut self, attack: u16) -> Self {
+        self.character.attack = attack;
+        self
+    }
+
+    /// Set the defense stat.
+    pub fn defense(mut self, defense: u16) -> Self {
+        self.character.defense = defense;
+        self
+    }
+
+    /// Set the regeneration stat.
+    pub fn regen(mut self, regen: u16) -> Self {
+        self.character.regen = regen;
+        self
+    }
+
+    /// Set the health stat.
+    pub fn health(mut self, health: i16) -> Self {
+        self.character.health = health;
+        self
+    }
+
+    /// Set the gold amount.
+    pub fn gold(mut self, gold: u16) -> Self {
+        self.character.gold = gold;
+        self
+    }
+
+    /// Set the room the character is in.
+    pub fn room(mut self, room: u16) -> Self {
+        self.character.current_room = room;
+        self
+    }
+
+    /// Set the description, truncated like [`PktCharacter::new`] if longer than `u16::MAX` bytes.
+    pub fn description(mut self, description: &str) -> Self {
+        self.character.description = Box::from(clamp_to_u16_len(description));
+        self
+    }
+
+    /// Build the character, computing `description_len` from the description.
+    pub fn build(mut self) -> PktCharacter {
+        self.character.description_len = self.character.description.len() as u16;
+        self.character
+    }
+}
+
+/// A `CHARACTER` controlled by a player, i.e. without the `MONSTER` flag. Created by [`PktCharacter::classify`].
+#[derive(Clone)]
+pub struct Player(PktCharacter);
+
+/// A `CHARACTER` controlled by the server, i.e. with the `MONSTER` flag. Created by [`PktCharacter::classify`].
+#[derive(Clone)]
+pub struct Monster(PktCharacter);
+
+/// A [`PktCharacter`] sorted by whether it is a player or a monster.
+#[derive(Clone)]
+pub enum CharacterKind {
+    /// The character is a player.
+    Player(Player),
+    /// The character is a monster.
+    Monster(Monster),
+}
+
+impl Player {
+    /// Unwrap the underlying character.
+    pub fn into_inner(self) -> PktCharacter {
+        self.0
+    }
+}
+
+impl Monster {
+    /// Unwrap the underlying character.
+    pub fn into_inner(self) -> PktCharacter {
+        self.0
+    }
+}
+
+impl std::ops::Deref for Player {
+    type Target = PktCharacter;
+
+    fn deref(&self) -> &PktCharacter {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for Monster {
+    type Target = PktCharacter;
+
+    fn deref(&self) -> &PktCharacter {
+        &self.0
+    }
 }
 
 #[macro_export]
@@ -105,17 +320,24 @@ impl std::fmt::Display for PktCharacter {
 
 impl Parser<'_> for PktCharacter {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    fn write_with_policy<W: Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
         // Package into a byte array
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
         // Serialize the character name
-        let mut name_bytes = self.name.as_bytes().to_vec();
-        name_bytes.resize(32, 0x00); // Pad the name to 32 bytes
+        let name_bytes = encode_name(&self.name, 32, policy)?; // Pad the name to 32 bytes
 
         packet.extend(name_bytes);
 
         // Serialize the flags byte
-        packet.extend([self.flags.bits()]);
+        packet.extend([self.flags.to_raw()]);
 
         // Serialize the character stats
         packet.extend(self.attack.to_le_bytes());
@@ -124,45 +346,35 @@ impl Parser<'_> for PktCharacter {
         packet.extend(self.health.to_le_bytes());
         packet.extend(self.gold.to_le_bytes());
         packet.extend(self.current_room.to_le_bytes());
-        packet.extend(self.description_len.to_le_bytes());
-        packet.extend(self.description.as_bytes());
+        packet.extend(encode_len(&self.description)?);
 
-        // Write the packet to the buffer
+        // Write the fixed fields, then the description straight from `self` rather than copying it into `packet`
         writer
             .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+            .and_then(|_| writer.write_all(self.description.as_bytes()))
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+            })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let name = String::from_utf8_lossy(&packet.body[0..32])
-            .split('\0')
-            .take(1)
-            .collect::<String>();
-        let flags = CharacterFlags::from_bits_truncate(packet.body[32]); // Other bits are reserved for future use
-        let attack = u16::from_le_bytes([packet.body[33], packet.body[34]]);
-        let defense = u16::from_le_bytes([packet.body[35], packet.body[36]]);
-        let regen = u16::from_le_bytes([packet.body[37], packet.body[38]]);
-        let health = i16::from_le_bytes([packet.body[39], packet.body[40]]);
-        let gold = u16::from_le_bytes([packet.body[41], packet.body[42]]);
-        let current_room = u16::from_le_bytes([packet.body[43], packet.body[44]]);
-        let description_len = u16::from_le_bytes([packet.body[45], packet.body[46]]);
-        let description = String::from_utf8_lossy(&packet.body[47..]).into();
-
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
             packet_type: packet.packet_type,
-            name: Arc::from(name),
-            flags,
-            attack,
-            defense,
-            regen,
-            health,
-            gold,
-            current_room,
-            description_len,
-            description,
-        }
+            ..PktCharacterRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktCharacter {
+    type Error = std::io::Error;
+
+    /// Parse a complete `CHARACTER` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::CHARACTER`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::CHARACTER).and_then(PktCharacter::decode)
     }
 }
 
@@ -186,7 +398,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktCharacter
-        let message = PktCharacter::decode(packet);
+        let message = PktCharacter::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::CHARACTER);
@@ -209,6 +421,39 @@ mod tests {
         assert_eq!(buffer[0], u8::from(type_byte));
     }
 
+    /// Round-trip exact bytes for a dead monster, whose negative health and non-reset flags must survive unchanged.
+    #[test]
+    fn character_parse_and_serialize_negative_health() {
+        let original_bytes: &[u8; 52] = &[
+            0x0a, 0x47, 0x68, 0x6f, 0x75, 0x6c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x68, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0xc7, 0xff,
+            0x02, 0x00, 0x04, 0x00, 0x04, 0x00, 0x43, 0x6f, 0x6c, 0x64,
+        ];
+
+        let packet = Packet::new(PktType::CHARACTER, &original_bytes[1..]);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
+
+        assert_eq!(chr.name.as_ref(), "Ghoul");
+        assert_eq!(
+            chr.flags,
+            CharacterFlags::BATTLE | CharacterFlags::MONSTER | CharacterFlags::READY
+        );
+        assert_eq!(chr.attack, 3);
+        assert_eq!(chr.defense, 1);
+        assert_eq!(chr.regen, 0);
+        assert_eq!(chr.health, -57);
+        assert_eq!(chr.gold, 2);
+        assert_eq!(chr.current_room, 4);
+        assert_eq!(chr.description_len, 4);
+        assert_eq!(chr.description.as_ref(), "Cold");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        chr.write_to(&mut buffer).expect("Encoding failed");
+
+        assert_eq!(buffer, original_bytes);
+    }
+
     /// Parse a character with ALIVE | BATTLE | READY flags (from trace: Test initial).
     #[test]
     fn character_parse_trace_alive_battle_ready() {
@@ -229,7 +474,7 @@ mod tests {
         body.extend(0u16.to_le_bytes()); // description_len
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(chr.name.as_ref(), "Test");
         assert!(chr.flags.contains(CharacterFlags::ALIVE));
@@ -264,7 +509,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert!(chr.flags.contains(CharacterFlags::ALIVE));
         assert!(chr.flags.contains(CharacterFlags::BATTLE));
@@ -292,7 +537,7 @@ mod tests {
         body.extend(desc.as_bytes());
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(chr.name.as_ref(), "Deku Baba");
         assert!(chr.flags.contains(CharacterFlags::ALIVE));
@@ -326,7 +571,7 @@ mod tests {
         body.extend(desc.as_bytes());
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert!(!chr.flags.contains(CharacterFlags::ALIVE));
         assert!(chr.flags.contains(CharacterFlags::MONSTER));
@@ -350,7 +595,7 @@ mod tests {
         body.extend(0u16.to_le_bytes()); // description_len
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(chr.attack, u16::MAX);
         assert_eq!(chr.defense, u16::MAX);
@@ -377,7 +622,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(chr.health, i16::MIN);
     }
@@ -388,7 +633,7 @@ mod tests {
         let body: Vec<u8> = vec![0x00; 47]; // 32 name + 1 flags + 14 stats = 47
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(chr.name.as_ref(), "");
         assert!(chr.flags.is_empty());
@@ -417,7 +662,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(chr.name.as_ref(), &long_name);
     }
@@ -446,7 +691,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::CHARACTER, &buffer[1..]);
-        let deserialized = PktCharacter::decode(packet);
+        let deserialized = PktCharacter::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.name.as_ref(), "TestHero");
         assert_eq!(
@@ -476,28 +721,26 @@ mod tests {
         body.extend(vec![0u8; 14]); // stats
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         // Should contain replacement characters
         assert!(chr.name.contains('\u{FFFD}'));
     }
 
-    /// Body that is too short to parse stats should panic.
+    /// Body that is too short to parse stats should fail to decode.
     #[test]
-    #[should_panic]
-    fn character_body_too_short_panics() {
+    fn character_body_too_short_fails() {
         let body: &[u8] = &[0x41; 20]; // Only 20 bytes, need at least 47
         let packet = Packet::new(PktType::CHARACTER, body);
-        let _ = PktCharacter::decode(packet);
+        assert!(PktCharacter::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn character_empty_body_panics() {
+    fn character_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::CHARACTER, body);
-        let _ = PktCharacter::decode(packet);
+        assert!(PktCharacter::decode(packet).is_err());
     }
 
     /// Verify CharacterFlags helper methods work.
@@ -558,7 +801,7 @@ mod tests {
         let body: Vec<u8> = vec![0xFF; 47];
 
         let packet = Packet::new(PktType::CHARACTER, &body);
-        let chr = PktCharacter::decode(packet);
+        let chr = PktCharacter::decode(packet).expect("Decoding failed");
 
         // Flags are truncated to known bits
         assert!(chr.flags.contains(CharacterFlags::ALIVE));
@@ -597,6 +840,27 @@ mod tests {
         assert_eq!(parsed["attack"], 10);
     }
 
+    /// Flags are shown by name in the JSON output rather than as a number.
+    #[test]
+    fn character_display_named_flags() {
+        let chr = PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: Arc::from("TestChar"),
+            flags: CharacterFlags::alive(),
+            attack: 10,
+            defense: 10,
+            regen: 10,
+            health: 100,
+            gold: 0,
+            current_room: 0,
+            description_len: 0,
+            description: Box::from(""),
+        };
+        let parsed: serde_json::Value =
+            serde_json::from_str(&chr.to_string()).expect("Invalid JSON");
+        assert_eq!(parsed["flags"], "ALIVE | BATTLE | READY");
+    }
+
     /// Verify health underflow at i16 boundary values.
     #[test]
     fn character_health_boundary_values() {
@@ -625,7 +889,7 @@ mod tests {
             body.extend(0u16.to_le_bytes()); // desc_len
 
             let packet = Packet::new(PktType::CHARACTER, &body);
-            let chr = PktCharacter::decode(packet);
+            let chr = PktCharacter::decode(packet).expect("Decoding failed");
             assert_eq!(chr.health, health, "Failed for health value: {}", health);
         }
     }
@@ -648,9 +912,131 @@ mod tests {
             body.extend(0u16.to_le_bytes());
 
             let packet = Packet::new(PktType::CHARACTER, &body);
-            let chr = PktCharacter::decode(packet);
+            let chr = PktCharacter::decode(packet).expect("Decoding failed");
             assert_eq!(chr.gold, gold, "Failed for gold value: {}", gold);
         }
     }
+
+    /// Characters are classified by the MONSTER flag alone.
+    #[test]
+    fn character_classify() {
+        let character = |flags| PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: Arc::from("Someone"),
+            flags,
+            attack: 1,
+            defense: 1,
+            regen: 1,
+            health: 1,
+            gold: 0,
+            current_room: 0,
+            description_len: 0,
+            description: Box::from(""),
+        };
+
+        assert!(matches!(
+            character(CharacterFlags::alive()).classify(),
+            CharacterKind::Player(_)
+        ));
+        assert!(matches!(
+            character(CharacterFlags::MONSTER).classify(),
+            CharacterKind::Monster(_)
+        ));
+
+        let CharacterKind::Monster(monster) =
+            character(CharacterFlags::dead() | CharacterFlags::MONSTER).classify()
+        else {
+            panic!("Expected a monster");
+        };
+        assert_eq!(monster.name.as_ref(), "Someone");
+        assert!(monster.into_inner().flags.is_monster());
+    }
+
+    /// `new` zeroes every stat and derives the description length.
+    #[test]
+    fn character_new() {
+        let packet = PktCharacter::new("Hero", "Brave.");
+
+        assert_eq!(packet.packet_type, PktType::CHARACTER);
+        assert_eq!(&*packet.name, "Hero");
+        assert_eq!(packet.flags, CharacterFlags::empty());
+        assert_eq!((packet.attack, packet.health, packet.gold), (0, 0, 0));
+        assert_eq!(packet.description_len, 6);
+    }
+
+    /// A fight needs a living target in the attacker's room.
+    #[test]
+    fn character_can_fight() {
+        let at = |name: &str, room: u16, flags: CharacterFlags| PktCharacter {
+            flags,
+            current_room: room,
+            ..PktCharacter::new(name, "")
+        };
+        let hero = at("Hero", 1, CharacterFlags::alive());
+
+        assert!(hero.in_same_room_as(&at("Goblin", 1, CharacterFlags::alive())));
+        assert_eq!(
+            hero.can_fight(&at("Goblin", 1, CharacterFlags::alive())),
+            Ok(())
+        );
+        assert_eq!(
+            hero.can_fight(&at("Troll", 2, CharacterFlags::alive())),
+            Err(LurkError::NOTARGET)
+        );
+        assert_eq!(
+            hero.can_fight(&at("Corpse", 1, CharacterFlags::dead())),
+            Err(LurkError::NOFIGHT)
+        );
+    }
+
+    /// The builder sets every field and matches the equivalent struct literal on the wire.
+    #[test]
+    fn character_builder() {
+        let built = PktCharacter::builder("Goblin")
+            .flags(CharacterFlags::monster())
+            .attack(5)
+            .defense(6)
+            .regen(7)
+            .health(-8)
+            .gold(9)
+            .room(10)
+            .description("Small and green.")
+            .build();
+
+        let literal = PktCharacter {
+            flags: CharacterFlags::monster(),
+            attack: 5,
+            defense: 6,
+            regen: 7,
+            health: -8,
+            gold: 9,
+            current_room: 10,
+            ..PktCharacter::new("Goblin", "Small and green.")
+        };
+
+        assert_eq!(built.packet_type, PktType::CHARACTER);
+        assert_eq!(built.description_len, 16);
+        assert_eq!(built.encode_to_vec().ok(), literal.encode_to_vec().ok());
+    }
+
+    /// Reserved flag bits survive encode and decode, owned and borrowed alike.
+    #[test]
+    fn character_reserved_flag_bits() {
+        let hero = PktCharacter::builder("Hero")
+            .flags(CharacterFlags::from_raw(0b1000_0101))
+            .build();
+        let bytes = hero.encode_to_vec().expect("Encoding failed");
+        assert_eq!(bytes[33], 0b1000_0101);
+
+        let decoded = PktCharacter::try_from(bytes.as_slice()).expect("Parse failed");
+        assert_eq!(decoded.flags.to_raw(), 0b1000_0101);
+        assert_eq!(decoded.encode_to_vec().ok(), Some(bytes.clone()));
+
+        let (borrowed, _) = crate::ProtocolRef::from_bytes(&bytes).expect("Decode failed");
+        let crate::ProtocolRef::Character(borrowed) = borrowed else {
+            panic!("Expected a character");
+        };
+        assert_eq!(borrowed.flags.to_raw(), 0b1000_0101);
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -2,9 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::io::Write;
 
 use crate::packet::PktType;
+use crate::packet::{NamePolicy, clamp_to_u16_len, encode_len, encode_name};
+use crate::protocol_ref::PktConnectionRef;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Used by the server to describe rooms connected to the room the player is in.
 ///
 /// - The client should expect a series of these when changing rooms, but they may be sent at any time.
@@ -22,30 +24,48 @@ pub struct PktConnection {
     pub room_number: u16,
     /// The name of the room this connection leads to, up to 32 bytes.
     pub room_name: Box<str>,
-    /// The length of the room description.
+    /// The length of `description` as decoded. Serializing ignores it and writes the length of `description` itself.
     pub description_len: u16,
     /// The description of the room this connection leads to.
     pub description: Box<str>,
 }
 
+impl PktConnection {
+    /// Create a new `PktConnection` packet describing an exit to the given room.
+    ///
+    /// Descriptions longer than `u16::MAX` bytes are truncated on a `char` boundary so `description_len` always matches.
+    pub fn new(room_number: u16, room_name: &str, description: &str) -> Self {
+        let description = clamp_to_u16_len(description);
+
+        Self {
+            packet_type: PktType::CONNECTION,
+            room_number,
+            room_name: Box::from(room_name),
+            description_len: description.len() as u16,
+            description: Box::from(description),
+        }
+    }
+}
+
 #[macro_export]
 /// Send `PktConnection` over `TcpStream` to connected user
 ///
 /// ```no_run
-/// use lurk_protocol::{Protocol, PktConnection, PktType, send_connection};
+/// use lurk_protocol::{PktConnection, send_connection};
 /// use std::sync::Arc;
 /// use std::net::TcpStream;
 ///
 /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
-/// let connection = PktConnection {
-///     packet_type: PktType::CONNECTION,
-///     room_number: 0,
-///     room_name: "Test".into(),
-///     description_len: 0,
-///     description: "".into(),
-/// };
+/// let exits = [
+///     (2, "Hallway", "A long, dim corridor."),
+///     (3, "Cellar", "Damp stone steps lead down."),
+///     (4, "Tower", "A spiral stair winds upward."),
+/// ];
 ///
-/// send_connection!(stream.clone(), connection)
+/// // After a room change, send one CONNECTION per exit from the new room
+/// for (room_number, name, description) in exits {
+///     send_connection!(stream.clone(), PktConnection::new(room_number, name, description));
+/// }
 /// ```
 macro_rules! send_connection {
     ($stream:expr, $connection:expr) => {
@@ -68,43 +88,51 @@ impl std::fmt::Display for PktConnection {
 
 impl Parser<'_> for PktConnection {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    fn write_with_policy<W: Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
         // Package into a byte array
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
         packet.extend(self.room_number.to_le_bytes());
 
-        let mut room_name_bytes = self.room_name.as_bytes().to_vec();
-        room_name_bytes.resize(32, 0x00); // Pad the name to 32 bytes
+        let room_name_bytes = encode_name(&self.room_name, 32, policy)?; // Pad the name to 32 bytes
         packet.extend(room_name_bytes);
 
-        packet.extend(self.description_len.to_le_bytes());
-        packet.extend(self.description.as_bytes());
+        packet.extend(encode_len(&self.description)?);
 
-        // Write the packet to the buffer
+        // Write the fixed fields, then the description straight from `self` rather than copying it into `packet`
         writer
             .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+            .and_then(|_| writer.write_all(self.description.as_bytes()))
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+            })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let message_type = packet.packet_type;
-        let room_number = u16::from_le_bytes([packet.body[0], packet.body[1]]);
-        let room_name = String::from_utf8_lossy(&packet.body[2..34])
-            .split('\0')
-            .take(1)
-            .collect();
-        let description_len = u16::from_le_bytes([packet.body[34], packet.body[35]]);
-        let description = String::from_utf8_lossy(&packet.body[36..]).into();
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            packet_type: packet.packet_type,
+            ..PktConnectionRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
 
-        Self {
-            packet_type: message_type,
-            room_number,
-            room_name,
-            description_len,
-            description,
-        }
+impl TryFrom<&[u8]> for PktConnection {
+    type Error = std::io::Error;
+
+    /// Parse a complete `CONNECTION` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::CONNECTION`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::CONNECTION).and_then(PktConnection::decode)
     }
 }
 
@@ -128,7 +156,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktConnection
-        let message = PktConnection::decode(packet);
+        let message = PktConnection::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::CONNECTION);
@@ -163,7 +191,7 @@ mod tests {
         body.extend(desc.as_bytes());
 
         let packet = Packet::new(PktType::CONNECTION, &body);
-        let conn = PktConnection::decode(packet);
+        let conn = PktConnection::decode(packet).expect("Decoding failed");
 
         assert_eq!(conn.room_number, 1);
         assert_eq!(conn.room_name.as_ref(), room_name);
@@ -181,7 +209,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::CONNECTION, &body);
-        let conn = PktConnection::decode(packet);
+        let conn = PktConnection::decode(packet).expect("Decoding failed");
 
         assert_eq!(conn.description_len, 0);
         assert_eq!(conn.description.as_ref(), "");
@@ -198,7 +226,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::CONNECTION, &body);
-        let conn = PktConnection::decode(packet);
+        let conn = PktConnection::decode(packet).expect("Decoding failed");
 
         assert_eq!(conn.room_number, u16::MAX);
     }
@@ -218,7 +246,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::CONNECTION, &buffer[1..]);
-        let deserialized = PktConnection::decode(packet);
+        let deserialized = PktConnection::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.room_number, 7);
         assert_eq!(deserialized.room_name.as_ref(), "Secret Door");
@@ -238,7 +266,7 @@ mod tests {
         body.extend(desc.as_bytes());
 
         let packet = Packet::new(PktType::CONNECTION, &body);
-        let conn = PktConnection::decode(packet);
+        let conn = PktConnection::decode(packet).expect("Decoding failed");
 
         assert_eq!(conn.description.len(), 5000);
     }
@@ -255,28 +283,26 @@ mod tests {
         body.extend(&[0xFC, 0xFB]);
 
         let packet = Packet::new(PktType::CONNECTION, &body);
-        let conn = PktConnection::decode(packet);
+        let conn = PktConnection::decode(packet).expect("Decoding failed");
 
         assert!(conn.room_name.contains('\u{FFFD}'));
         assert!(conn.description.contains('\u{FFFD}'));
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn connection_body_too_short_panics() {
+    fn connection_body_too_short_fails() {
         let body: &[u8] = &[0x00, 0x00, 0x41]; // Need at least 36
         let packet = Packet::new(PktType::CONNECTION, body);
-        let _ = PktConnection::decode(packet);
+        assert!(PktConnection::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn connection_empty_body_panics() {
+    fn connection_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::CONNECTION, body);
-        let _ = PktConnection::decode(packet);
+        assert!(PktConnection::decode(packet).is_err());
     }
 
     /// All zeros body.
@@ -284,7 +310,7 @@ mod tests {
     fn connection_all_zeros_body() {
         let body: Vec<u8> = vec![0x00; 36];
         let packet = Packet::new(PktType::CONNECTION, &body);
-        let conn = PktConnection::decode(packet);
+        let conn = PktConnection::decode(packet).expect("Decoding failed");
 
         assert_eq!(conn.room_number, 0);
         assert_eq!(conn.room_name.as_ref(), "");
@@ -306,5 +332,16 @@ mod tests {
         assert_eq!(parsed["room_number"], 3);
         assert_eq!(parsed["room_name"], "Dungeon");
     }
+
+    /// `new` derives the description length from the description.
+    #[test]
+    fn connection_new() {
+        let packet = PktConnection::new(4, "Cellar", "Damp and dark.");
+
+        assert_eq!(packet.packet_type, PktType::CONNECTION);
+        assert_eq!(packet.room_number, 4);
+        assert_eq!(packet.description_len, 14);
+        assert_eq!(&*packet.room_name, "Cellar");
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -4,19 +4,20 @@ use std::io::Write;
 use tracing::error;
 
 use crate::lurk_error::LurkError;
-use crate::packet::PktType;
+use crate::packet::{PktType, clamp_to_u16_len, encode_len};
+use crate::protocol_ref::PktErrorRef;
 use crate::{Packet, Parser};
 
 /// Notify the client of an error.
 ///
 /// This is used to indicate stat violations, inappropriate room connections, attempts to loot nonexistent or living players, attempts to attack players or monsters in different rooms, etc.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PktError {
     /// The type of message for the `ERROR` packet. Defaults to 7.
     pub packet_type: PktType,
     /// The specific error code.
     pub error: LurkError,
-    /// The length of the error message.
+    /// The length of `message` as decoded. Serializing ignores it and writes the length of `message` itself.
     pub message_len: u16,
     /// The error message.
     pub message: Box<str>,
@@ -24,7 +25,11 @@ pub struct PktError {
 
 impl PktError {
     /// Create a new `PktError` with the specified error code and message.
+    ///
+    /// Messages longer than `u16::MAX` bytes are truncated on a `char` boundary so `message_len` matches the message.
     pub fn new(error: LurkError, message: &str) -> Self {
+        let message = clamp_to_u16_len(message);
+
         #[cfg(feature = "tracing")]
         error!("{}: {}", error, message);
 
@@ -35,6 +40,25 @@ impl PktError {
             message: Box::from(message),
         }
     }
+
+    /// A one-line summary for operators: the symbolic name, numeric code, and message.
+    ///
+    /// `Display` stays JSON, like the other packets.
+    ///
+    /// ```
+    /// use lurk_protocol::{LurkError, PktError};
+    ///
+    /// let err = PktError::new(LurkError::BADROOM, "There is no door that way.");
+    /// assert_eq!(err.describe(), "BadRoom (1): There is no door that way.");
+    /// ```
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ({}): {}",
+            self.error,
+            u8::from(self.error),
+            self.message
+        )
+    }
 }
 
 #[macro_export]
@@ -47,7 +71,10 @@ impl PktError {
 ///
 /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
 ///
-/// send_error!(stream.clone(), PktError::new(LurkError::NOTREADY, "Start the game first!"))
+/// send_error!(stream.clone(), PktError::new(LurkError::NOTREADY, "Start the game first!"));
+///
+/// // Or build the packet inline from an error code and message
+/// send_error!(stream.clone(), LurkError::BADROOM, "There is no door that way.");
 /// ```
 macro_rules! send_error {
     ($stream:expr, $pkt_error:expr) => {
@@ -55,6 +82,9 @@ macro_rules! send_error {
             eprintln!("Failed to send error packet: {}", e);
         }
     };
+    ($stream:expr, $error:expr, $message:expr) => {
+        $crate::send_error!($stream, $crate::PktError::new($error, $message))
+    };
 }
 
 impl std::fmt::Display for PktError {
@@ -73,32 +103,35 @@ impl Parser<'_> for PktError {
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
         packet.push(self.error.into());
-        packet.extend(self.message_len.to_le_bytes());
-        packet.extend(self.message.as_bytes());
+        packet.extend(encode_len(&self.message)?);
 
-        // Write the packet to the buffer
+        // Write the fixed fields, then the message straight from `self` rather than copying it into `packet`
         writer
             .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+            .and_then(|_| writer.write_all(self.message.as_bytes()))
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+            })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let message_type = packet.packet_type;
-        let error = LurkError::from(packet.body[0]);
-        let message_len = u16::from_le_bytes([packet.body[1], packet.body[2]]);
-        let message = String::from_utf8_lossy(&packet.body[3..])
-            .split('\0')
-            .take(1)
-            .collect();
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            packet_type: packet.packet_type,
+            ..PktErrorRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
 
-        Self {
-            packet_type: message_type,
-            error,
-            message_len,
-            message,
-        }
+impl TryFrom<&[u8]> for PktError {
+    type Error = std::io::Error;
+
+    /// Parse a complete `ERROR` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::ERROR`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::ERROR).and_then(PktError::decode)
     }
 }
 
@@ -118,7 +151,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktError
-        let message = PktError::decode(packet);
+        let message = PktError::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::ERROR);
@@ -168,7 +201,7 @@ mod tests {
             assert_eq!(buffer[1], i as u8); // Error code is sequential from 0
 
             let packet = Packet::new(PktType::ERROR, &buffer[1..]);
-            let deserialized = PktError::decode(packet);
+            let deserialized = PktError::decode(packet).expect("Decoding failed");
             assert_eq!(deserialized.error, *lurk_err);
         }
     }
@@ -182,7 +215,7 @@ mod tests {
         err.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::ERROR, &buffer[1..]);
-        let deserialized = PktError::decode(packet);
+        let deserialized = PktError::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.message_len, 0);
         assert_eq!(deserialized.message.as_ref(), "");
     }
@@ -197,7 +230,7 @@ mod tests {
         err.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::ERROR, &buffer[1..]);
-        let deserialized = PktError::decode(packet);
+        let deserialized = PktError::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.message.len(), 5000);
     }
 
@@ -210,26 +243,24 @@ mod tests {
         body.extend(b"test");
 
         let packet = Packet::new(PktType::ERROR, &body);
-        let err = PktError::decode(packet);
+        let err = PktError::decode(packet).expect("Decoding failed");
         assert_eq!(err.error, LurkError::OTHER);
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn error_body_too_short_panics() {
+    fn error_body_too_short_fails() {
         let body: &[u8] = &[0x00]; // Need at least 3
         let packet = Packet::new(PktType::ERROR, body);
-        let _ = PktError::decode(packet);
+        assert!(PktError::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn error_empty_body_panics() {
+    fn error_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::ERROR, body);
-        let _ = PktError::decode(packet);
+        assert!(PktError::decode(packet).is_err());
     }
 
     /// All zeros body should parse.
@@ -237,7 +268,7 @@ mod tests {
     fn error_all_zeros_body() {
         let body: &[u8] = &[0x00, 0x00, 0x00];
         let packet = Packet::new(PktType::ERROR, body);
-        let err = PktError::decode(packet);
+        let err = PktError::decode(packet).expect("Decoding failed");
 
         assert_eq!(err.error, LurkError::OTHER);
         assert_eq!(err.message_len, 0);
@@ -261,8 +292,47 @@ mod tests {
         body.extend(&[0xFF, 0xFE, 0xFD, 0xFC]);
 
         let packet = Packet::new(PktType::ERROR, &body);
-        let err = PktError::decode(packet);
+        let err = PktError::decode(packet).expect("Decoding failed");
         assert!(err.message.contains('\u{FFFD}'));
     }
+
+    /// An error message over 64KiB is truncated rather than wrapping `message_len`.
+    #[test]
+    fn error_new_oversized_does_not_wrap() {
+        let text = "E".repeat(70 * 1024);
+        let error = PktError::new(LurkError::OTHER, &text);
+
+        assert_eq!(error.message_len, u16::MAX);
+        assert_eq!(error.message.len(), error.message_len as usize);
+    }
+
+    /// Both forms of `send_error!` deliver the same packet.
+    #[test]
+    fn error_send_error_macro() {
+        let (client, server) = crate::test_common::socket_pair();
+
+        crate::send_error!(client, PktError::new(LurkError::BADROOM, "No door."));
+        crate::send_error!(client, LurkError::BADROOM, "No door.");
+
+        for _ in 0..2 {
+            let packet = crate::Protocol::recv(&server).expect("Recv failed");
+            let error = packet.as_error().expect("Expected Error");
+            assert_eq!(error.error, LurkError::BADROOM);
+            assert_eq!(error.message.as_ref(), "No door.");
+        }
+    }
+
+    /// `describe` gives name, code, and message, while `Display` stays JSON.
+    #[test]
+    fn error_describe() {
+        let err = PktError::new(LurkError::NOPLAYERCOMBAT, "No duels here.");
+
+        assert_eq!(err.describe(), "NoPlayerCombat (8): No duels here.");
+        assert!(err.to_string().starts_with('{'));
+        assert_eq!(
+            PktError::new(LurkError::OTHER, "").describe(),
+            "Other (0): "
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -4,7 +4,7 @@ use std::io::Write;
 use crate::packet::PktType;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Initiate a fight against monsters.
 ///
 /// - This will start a fight in the current room against the monsters which are presently in the room.
@@ -30,6 +30,13 @@ impl Default for PktFight {
     }
 }
 
+impl PktFight {
+    /// Create a new `PktFight` packet. Equivalent to `PktFight::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 #[macro_export]
 /// Send `PktFight` over `TcpStream` to connected user
 ///
@@ -66,17 +73,28 @@ impl Parser<'_> for PktFight {
         let packet: Vec<u8> = vec![self.packet_type.into()];
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
             packet_type: packet.packet_type,
-        }
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktFight {
+    type Error = std::io::Error;
+
+    /// Parse a complete `FIGHT` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::FIGHT`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::FIGHT).and_then(PktFight::decode)
     }
 }
 
@@ -93,7 +111,7 @@ mod tests {
         let packet = Packet::new(type_byte, &[]);
 
         // Deserialize the packet into a PktFight
-        let message = PktFight::decode(packet);
+        let message = PktFight::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::FIGHT);
@@ -133,7 +151,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::FIGHT, &[]);
-        let deserialized = PktFight::decode(packet);
+        let deserialized = PktFight::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.packet_type, PktType::FIGHT);
     }
 
@@ -142,7 +160,7 @@ mod tests {
     fn fight_extra_body_bytes() {
         let body: &[u8] = &[0xFF, 0xFF, 0xFF];
         let packet = Packet::new(PktType::FIGHT, body);
-        let fight = PktFight::decode(packet);
+        let fight = PktFight::decode(packet).expect("Decoding failed");
         assert_eq!(fight.packet_type, PktType::FIGHT);
     }
 
@@ -160,8 +178,18 @@ mod tests {
     fn fight_decode_uses_packet_type() {
         // Pass a non-FIGHT type to verify decode reads from the packet
         let packet = Packet::new(PktType::DEFAULT, &[]);
-        let fight = PktFight::decode(packet);
+        let fight = PktFight::decode(packet).expect("Decoding failed");
         assert_eq!(fight.packet_type, PktType::DEFAULT);
     }
+
+    /// `new` builds the same packet as `Default`.
+    #[test]
+    fn fight_new() {
+        assert_eq!(PktFight::new().packet_type, PktType::FIGHT);
+        assert_eq!(
+            PktFight::new().encode_to_vec().ok(),
+            PktFight::default().encode_to_vec().ok()
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::io::Write;
 
+use crate::lurk_error::LurkError;
 use crate::packet::PktType;
-use crate::{Packet, Parser};
+use crate::packet::{clamp_to_u16_len, encode_len};
+use crate::protocol_ref::PktGameRef;
+use crate::{Packet, Parser, PktCharacter, PktError};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Used by the server to describe the game.
 ///
 /// - The initial points is a combination of health, defense, and regen, and cannot be exceeded by the client when defining a new character.
@@ -19,12 +22,213 @@ pub struct PktGame {
     pub initial_points: u16,
     /// The maximum stat limit for any character.
     pub stat_limit: u16,
-    /// The length of the game description.
+    /// The length of `description` as decoded. Serializing ignores it and writes the length of `description` itself.
     pub description_len: u16,
     /// The description of the game.
     pub description: Box<str>,
 }
 
+/// Which of the game's limits a proposed character exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatLimitKind {
+    /// The `initial_points` available to a new character.
+    InitialPoints,
+    /// The server-wide `stat_limit`.
+    StatLimit,
+}
+
+/// How a proposed character exceeded the game's limits, as reported by [`PktGame::check_stats`].
+///
+/// The wire error is always [`LurkError::STATERROR`]; the `Display` output is meant for the message of the
+/// `ERROR` packet, and `PktError::from` builds the whole packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatViolation {
+    /// The sum of the character's attack, defense, and regen.
+    pub total: u32,
+    /// The limit that was exceeded.
+    pub limit: u16,
+    /// Which limit was exceeded.
+    pub kind: StatLimitKind,
+}
+
+impl std::fmt::Display for StatViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let limit = match self.kind {
+            StatLimitKind::InitialPoints => "initial points",
+            StatLimitKind::StatLimit => "stat limit",
+        };
+
+        write!(
+            f,
+            "You have {} stat points, but the {} allows at most {}.",
+            self.total, limit, self.limit
+        )
+    }
+}
+
+impl From<StatViolation> for PktError {
+    fn from(violation: StatViolation) -> Self {
+        PktError::new(LurkError::STATERROR, &violation.to_string())
+    }
+}
+
+impl PktGame {
+    /// Create a new `PktGame` packet.
+    ///
+    /// Descriptions longer than `u16::MAX` bytes are truncated on a `char` boundary so `description_len` always matches.
+    pub fn new(initial_points: u16, stat_limit: u16, description: &str) -> Self {
+        let description = clamp_to_u16_len(description);
+
+        Self {
+            packet_type: PktType::GAME,
+            initial_points,
+            stat_limit,
+            description_len: description.len() as u16,
+            description: Box::from(description),
+        }
+    }
+
+    /// The `stat_limit` value signalling that the server does not enforce a stat limit.
+    pub const UNUSED_STAT_LIMIT: u16 = u16::MAX;
+
+    /// The most points a new character may spend across attack, defense, and regen.
+    ///
+    /// This is the smaller of `initial_points` and `stat_limit`, ignoring `stat_limit` when it is unused.
+    fn new_character_allowance(&self) -> u16 {
+        match self.stat_limit {
+            PktGame::UNUSED_STAT_LIMIT => self.initial_points,
+            limit => self.initial_points.min(limit),
+        }
+    }
+
+    /// Check a client's proposed character against the game's limits, reporting the total and the limit it broke.
+    ///
+    /// This is [`PktGame::validate_character`] with enough detail to tell the player how to fix their character.
+    /// When both limits apply, the smaller one is reported.
+    ///
+    /// ```
+    /// use lurk_protocol::{PktCharacter, PktError, PktGame, StatLimitKind};
+    ///
+    /// let game = PktGame::new(100, 65535, "");
+    /// let player = PktCharacter { attack: 60, defense: 40, regen: 20, ..PktCharacter::new("Test", "") };
+    ///
+    /// let violation = game.check_stats(&player).unwrap_err();
+    /// assert_eq!((violation.total, violation.limit), (120, 100));
+    /// assert_eq!(violation.kind, StatLimitKind::InitialPoints);
+    ///
+    /// let error = PktError::from(violation);
+    /// assert_eq!(&*error.message, "You have 120 stat points, but the initial points allows at most 100.");
+    /// ```
+    pub fn check_stats(&self, character: &PktCharacter) -> Result<(), StatViolation> {
+        let total = character.attack as u32 + character.defense as u32 + character.regen as u32;
+        let limit = self.new_character_allowance();
+
+        if total <= limit as u32 {
+            return Ok(());
+        }
+
+        let kind = if limit < self.initial_points {
+            StatLimitKind::StatLimit
+        } else {
+            StatLimitKind::InitialPoints
+        };
+
+        Err(StatViolation { total, limit, kind })
+    }
+
+    /// Check a client's proposed character against the game's limits, rejecting it if they are exceeded.
+    ///
+    /// Returns [`LurkError::STATERROR`] when `attack + defense + regen` is greater than `initial_points`,
+    /// or greater than `stat_limit` when one is in use.
+    ///
+    /// Use this when the client should be told to fix their character, and [`PktGame::fit_new_character`]
+    /// when the server would rather quietly adjust the stats and carry on.
+    ///
+    /// ```
+    /// use lurk_protocol::{PktCharacter, PktGame, PktType, CharacterFlags, LurkError};
+    ///
+    /// let game = PktGame {
+    ///     packet_type: PktType::GAME,
+    ///     initial_points: 100,
+    ///     stat_limit: 65535,
+    ///     description_len: 0,
+    ///     description: "".into(),
+    /// };
+    /// let player = PktCharacter {
+    ///     packet_type: PktType::CHARACTER,
+    ///     name: "Test".into(),
+    ///     flags: CharacterFlags::reset(),
+    ///     attack: 60,
+    ///     defense: 30,
+    ///     regen: 20,
+    ///     health: 100,
+    ///     gold: 0,
+    ///     current_room: 0,
+    ///     description_len: 0,
+    ///     description: "".into(),
+    /// };
+    ///
+    /// assert_eq!(game.validate_character(&player), Err(LurkError::STATERROR));
+    /// ```
+    pub fn validate_character(&self, character: &PktCharacter) -> Result<(), LurkError> {
+        self.check_stats(character)
+            .map_err(|_| LurkError::STATERROR)
+    }
+
+    /// Adjust a client's proposed character to fit within the game's limits, rather than rejecting it.
+    ///
+    /// If `attack + defense + regen` exceeds the allowance (see [`PktGame::validate_character`]),
+    /// each stat is scaled down proportionally so the total fits. Flags, health, gold, and room are
+    /// reset as described by [`PktCharacter::with_defaults_from`].
+    ///
+    /// ```
+    /// use lurk_protocol::{PktCharacter, PktGame, PktType, CharacterFlags};
+    ///
+    /// let game = PktGame {
+    ///     packet_type: PktType::GAME,
+    ///     initial_points: 100,
+    ///     stat_limit: 65535,
+    ///     description_len: 0,
+    ///     description: "".into(),
+    /// };
+    /// let player = PktCharacter {
+    ///     packet_type: PktType::CHARACTER,
+    ///     name: "Test".into(),
+    ///     flags: CharacterFlags::all(),
+    ///     attack: 100,
+    ///     defense: 50,
+    ///     regen: 50,
+    ///     health: 500,
+    ///     gold: 1000,
+    ///     current_room: 7,
+    ///     description_len: 0,
+    ///     description: "".into(),
+    /// };
+    ///
+    /// let fitted = game.fit_new_character(&player);
+    ///
+    /// assert_eq!((fitted.attack, fitted.defense, fitted.regen), (50, 25, 25));
+    /// assert!(game.validate_character(&fitted).is_ok());
+    /// ```
+    pub fn fit_new_character(&self, incoming: &PktCharacter) -> PktCharacter {
+        let mut character = PktCharacter::with_defaults_from(incoming);
+
+        let allowance = self.new_character_allowance() as u32;
+        let total = character.attack as u32 + character.defense as u32 + character.regen as u32;
+
+        if total > allowance {
+            // Scale each stat by allowance / total, rounding down so the sum never exceeds the allowance
+            let scale = |stat: u16| (stat as u32 * allowance / total) as u16;
+
+            character.attack = scale(character.attack);
+            character.defense = scale(character.defense);
+            character.regen = scale(character.regen);
+        }
+
+        character
+    }
+}
+
 #[macro_export]
 /// Send `PktGame` over `TcpStream` to connected user
 ///
@@ -67,30 +271,35 @@ impl Parser<'_> for PktGame {
 
         packet.extend(self.initial_points.to_le_bytes());
         packet.extend(self.stat_limit.to_le_bytes());
-        packet.extend(self.description_len.to_le_bytes());
-        packet.extend(self.description.as_bytes());
+        packet.extend(encode_len(&self.description)?);
 
-        // Write the packet to the buffer
+        // Write the fixed fields, then the description straight from `self` rather than copying it into `packet`
         writer
             .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+            .and_then(|_| writer.write_all(self.description.as_bytes()))
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+            })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let initial_points = u16::from_le_bytes([packet.body[0], packet.body[1]]);
-        let stat_limit = u16::from_le_bytes([packet.body[2], packet.body[3]]);
-        let description_len = u16::from_le_bytes([packet.body[4], packet.body[5]]);
-        let description = String::from_utf8_lossy(&packet.body[6..]).into();
-
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
             packet_type: packet.packet_type,
-            initial_points,
-            stat_limit,
-            description_len,
-            description,
-        }
+            ..PktGameRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktGame {
+    type Error = std::io::Error;
+
+    /// Parse a complete `GAME` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::GAME`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::GAME).and_then(PktGame::decode)
     }
 }
 
@@ -110,7 +319,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktGame
-        let message = PktGame::decode(packet);
+        let message = PktGame::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::GAME);
@@ -136,7 +345,7 @@ mod tests {
         let mut body: Vec<u8> = vec![0x64, 0x00, 0xFF, 0xFF, 0x0B, 0x00];
         body.extend(b"Hello World");
         let packet = Packet::new(PktType::GAME, &body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.initial_points, 100);
         assert_eq!(game.stat_limit, 65535);
@@ -149,7 +358,7 @@ mod tests {
     fn game_empty_description() {
         let body: &[u8] = &[0x64, 0x00, 0xFF, 0xFF, 0x00, 0x00];
         let packet = Packet::new(PktType::GAME, body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.initial_points, 100);
         assert_eq!(game.stat_limit, 65535);
@@ -162,7 +371,7 @@ mod tests {
     fn game_max_u16_values() {
         let body: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00];
         let packet = Packet::new(PktType::GAME, body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.initial_points, u16::MAX);
         assert_eq!(game.stat_limit, u16::MAX);
@@ -173,7 +382,7 @@ mod tests {
     fn game_zero_values() {
         let body: &[u8] = &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
         let packet = Packet::new(PktType::GAME, body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.initial_points, 0);
         assert_eq!(game.stat_limit, 0);
@@ -195,7 +404,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::GAME, &buffer[1..]);
-        let deserialized = PktGame::decode(packet);
+        let deserialized = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.initial_points, 200);
         assert_eq!(deserialized.stat_limit, 1000);
@@ -212,7 +421,7 @@ mod tests {
         body.extend(desc_len.to_le_bytes());
         body.extend(desc.as_bytes());
         let packet = Packet::new(PktType::GAME, &body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.description_len, 5000);
         assert_eq!(game.description.len(), 5000);
@@ -224,29 +433,27 @@ mod tests {
         let mut body: Vec<u8> = vec![0x64, 0x00, 0xFF, 0xFF, 0x04, 0x00];
         body.extend(&[0xFF, 0xFE, 0xFD, 0xFC]); // invalid UTF-8
         let packet = Packet::new(PktType::GAME, &body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.description_len, 4);
         // Should contain replacement characters
         assert!(game.description.contains('\u{FFFD}'));
     }
 
-    /// Body too short for header fields should panic.
+    /// Body too short for header fields should fail to decode.
     #[test]
-    #[should_panic]
-    fn game_body_too_short_panics() {
+    fn game_body_too_short_fails() {
         let body: &[u8] = &[0x64, 0x00]; // Only 2 bytes, need at least 6
         let packet = Packet::new(PktType::GAME, body);
-        let _ = PktGame::decode(packet);
+        assert!(PktGame::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn game_empty_body_panics() {
+    fn game_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::GAME, body);
-        let _ = PktGame::decode(packet);
+        assert!(PktGame::decode(packet).is_err());
     }
 
     /// All 0xFF body should parse without panic.
@@ -254,7 +461,7 @@ mod tests {
     fn game_all_ones_body() {
         let body: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00];
         let packet = Packet::new(PktType::GAME, body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.initial_points, 65535);
         assert_eq!(game.stat_limit, 65535);
@@ -265,7 +472,7 @@ mod tests {
     fn game_all_zeros_body() {
         let body: &[u8] = &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
         let packet = Packet::new(PktType::GAME, body);
-        let game = PktGame::decode(packet);
+        let game = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(game.initial_points, 0);
         assert_eq!(game.stat_limit, 0);
@@ -305,9 +512,175 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::GAME, &buffer[1..]);
-        let deserialized = PktGame::decode(packet);
+        let deserialized = PktGame::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.description.as_ref(), desc);
     }
+
+    fn test_game(initial_points: u16, stat_limit: u16) -> PktGame {
+        PktGame {
+            packet_type: PktType::GAME,
+            initial_points,
+            stat_limit,
+            description_len: 0,
+            description: Box::from(""),
+        }
+    }
+
+    fn test_character(attack: u16, defense: u16, regen: u16) -> PktCharacter {
+        PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: "Hero".into(),
+            flags: crate::CharacterFlags::all(),
+            attack,
+            defense,
+            regen,
+            health: 500,
+            gold: 1000,
+            current_room: 7,
+            description_len: 4,
+            description: "desc".into(),
+        }
+    }
+
+    /// Stats summing to exactly initial_points are valid.
+    #[test]
+    fn game_validate_character_exact_limit() {
+        let game = test_game(100, 65535);
+        assert!(game.validate_character(&test_character(50, 25, 25)).is_ok());
+    }
+
+    /// One point over initial_points is rejected with STATERROR.
+    #[test]
+    fn game_validate_character_over_initial_points() {
+        let game = test_game(100, 65535);
+        assert_eq!(
+            game.validate_character(&test_character(50, 25, 26)),
+            Err(LurkError::STATERROR)
+        );
+    }
+
+    /// A stat_limit lower than initial_points caps the total.
+    #[test]
+    fn game_validate_character_over_stat_limit() {
+        let game = test_game(100, 80);
+        assert!(game.validate_character(&test_character(40, 20, 20)).is_ok());
+        assert_eq!(
+            game.validate_character(&test_character(40, 20, 21)),
+            Err(LurkError::STATERROR)
+        );
+    }
+
+    /// Large stats must not overflow the u16 sum.
+    #[test]
+    fn game_validate_character_no_overflow() {
+        let game = test_game(65535, 65535);
+        assert_eq!(
+            game.validate_character(&test_character(u16::MAX, u16::MAX, u16::MAX)),
+            Err(LurkError::STATERROR)
+        );
+    }
+
+    /// A character within the allowance keeps its stats but has its state reset.
+    #[test]
+    fn game_fit_new_character_within_limit() {
+        let game = test_game(100, 65535);
+        let fitted = game.fit_new_character(&test_character(10, 20, 30));
+
+        assert_eq!((fitted.attack, fitted.defense, fitted.regen), (10, 20, 30));
+        assert_eq!(fitted.flags, crate::CharacterFlags::reset());
+        assert_eq!(fitted.health, 100);
+        assert_eq!(fitted.gold, 0);
+        assert_eq!(fitted.current_room, 0);
+        assert_eq!(fitted.name.as_ref(), "Hero");
+        assert_eq!(fitted.description.as_ref(), "desc");
+    }
+
+    /// A character over the allowance is scaled down and then passes validation.
+    #[test]
+    fn game_fit_new_character_scales_down() {
+        let game = test_game(100, 65535);
+        let fitted = game.fit_new_character(&test_character(u16::MAX, u16::MAX, 1));
+
+        assert!(fitted.attack + fitted.defense + fitted.regen <= 100);
+        assert_eq!(fitted.attack, fitted.defense);
+        assert!(game.validate_character(&fitted).is_ok());
+    }
+
+    /// The stat_limit is respected when it is stricter than initial_points.
+    #[test]
+    fn game_fit_new_character_respects_stat_limit() {
+        let game = test_game(100, 40);
+        let fitted = game.fit_new_character(&test_character(60, 60, 0));
+
+        assert_eq!((fitted.attack, fitted.defense, fitted.regen), (20, 20, 0));
+        assert!(game.validate_character(&fitted).is_ok());
+    }
+
+    /// `new` derives the description length from the description.
+    #[test]
+    fn game_new() {
+        let packet = PktGame::new(100, 65535, "Deep Caves.");
+
+        assert_eq!(packet.packet_type, PktType::GAME);
+        assert_eq!(packet.initial_points, 100);
+        assert_eq!(packet.stat_limit, 65535);
+        assert_eq!(packet.description_len, 11);
+    }
+
+    /// A stat limit tighter than the initial points is reported as the limit that was exceeded.
+    #[test]
+    fn game_check_stats_stat_limit() {
+        let game = PktGame::new(100, 50, "");
+        let character = PktCharacter {
+            attack: 30,
+            defense: 30,
+            ..PktCharacter::new("Test", "")
+        };
+
+        assert_eq!(
+            game.check_stats(&character),
+            Err(StatViolation {
+                total: 60,
+                limit: 50,
+                kind: StatLimitKind::StatLimit,
+            })
+        );
+        assert_eq!(
+            game.validate_character(&character),
+            Err(LurkError::STATERROR)
+        );
+    }
+
+    /// A character exactly at the allowance passes.
+    #[test]
+    fn game_check_stats_at_limit() {
+        let game = PktGame::new(100, PktGame::UNUSED_STAT_LIMIT, "");
+        let character = PktCharacter {
+            attack: 50,
+            defense: 25,
+            regen: 25,
+            ..PktCharacter::new("Test", "")
+        };
+
+        assert_eq!(game.check_stats(&character), Ok(()));
+    }
+
+    /// The ERROR built from a violation carries `STATERROR` and the readable explanation.
+    #[test]
+    fn game_stat_violation_error() {
+        let violation = StatViolation {
+            total: 70,
+            limit: 50,
+            kind: StatLimitKind::StatLimit,
+        };
+        let error = PktError::from(violation);
+
+        assert_eq!(error.error, LurkError::STATERROR);
+        assert_eq!(
+            &*error.message,
+            "You have 70 stat points, but the stat limit allows at most 50."
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -4,7 +4,7 @@ use std::io::Write;
 use crate::packet::PktType;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Used by the client to leave the game. This is a graceful way to disconnect. The server never terminates, so it doesn't send `PktType::LEAVE`.
 pub struct PktLeave {
     /// The type of message for the `LEAVE` packet. Defaults to 12.
@@ -19,6 +19,13 @@ impl Default for PktLeave {
     }
 }
 
+impl PktLeave {
+    /// Create a new `PktLeave` packet. Equivalent to `PktLeave::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 #[macro_export]
 /// Send `PktLeave` over `TcpStream` to connected user
 ///
@@ -55,17 +62,28 @@ impl Parser<'_> for PktLeave {
         let packet: Vec<u8> = vec![self.packet_type.into()];
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
             packet_type: packet.packet_type,
-        }
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktLeave {
+    type Error = std::io::Error;
+
+    /// Parse a complete `LEAVE` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::LEAVE`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::LEAVE).and_then(PktLeave::decode)
     }
 }
 
@@ -82,7 +100,7 @@ mod tests {
         let packet = Packet::new(type_byte, &[]);
 
         // Deserialize the packet into a PktLeave
-        let message = PktLeave::decode(packet);
+        let message = PktLeave::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::LEAVE);
@@ -122,7 +140,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::LEAVE, &[]);
-        let deserialized = PktLeave::decode(packet);
+        let deserialized = PktLeave::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.packet_type, PktType::LEAVE);
     }
 
@@ -131,7 +149,7 @@ mod tests {
     fn leave_extra_body_bytes() {
         let body: &[u8] = &[0xFF, 0xFF];
         let packet = Packet::new(PktType::LEAVE, body);
-        let leave = PktLeave::decode(packet);
+        let leave = PktLeave::decode(packet).expect("Decoding failed");
         assert_eq!(leave.packet_type, PktType::LEAVE);
     }
 
@@ -149,8 +167,18 @@ mod tests {
     fn leave_decode_uses_packet_type() {
         // Pass a non-LEAVE type to verify decode reads from the packet
         let packet = Packet::new(PktType::DEFAULT, &[]);
-        let leave = PktLeave::decode(packet);
+        let leave = PktLeave::decode(packet).expect("Decoding failed");
         assert_eq!(leave.packet_type, PktType::DEFAULT);
     }
+
+    /// `new` builds the same packet as `Default`.
+    #[test]
+    fn leave_new() {
+        assert_eq!(PktLeave::new().packet_type, PktType::LEAVE);
+        assert_eq!(
+            PktLeave::new().encode_to_vec().ok(),
+            PktLeave::default().encode_to_vec().ok()
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
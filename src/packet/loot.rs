@@ -2,10 +2,12 @@ use serde::{Deserialize, Serialize};
 use std::io::Write;
 
 use crate::packet::PktType;
+use crate::packet::{NamePolicy, encode_name};
+use crate::protocol_ref::PktLootRef;
 use crate::{Packet, Parser};
 
 /// Represents a loot packet containing the message type and target name.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PktLoot {
     /// The type of the packet message.
     pub packet_type: PktType,
@@ -14,13 +16,19 @@ pub struct PktLoot {
 }
 
 impl PktLoot {
-    /// Create a new PktLoot packet from a given name
-    pub fn loot(name: &str) -> Self {
+    /// Create a new `PktLoot` packet targeting the given name.
+    pub fn new(target_name: &str) -> Self {
         Self {
             packet_type: PktType::LOOT,
-            target_name: Box::from(name),
+            target_name: Box::from(target_name),
         }
     }
+
+    /// Create a new PktLoot packet from a given name. Equivalent to [`PktLoot::new`].
+    #[deprecated(note = "use new")]
+    pub fn loot(name: &str) -> Self {
+        Self::new(name)
+    }
 }
 
 #[macro_export]
@@ -33,7 +41,7 @@ impl PktLoot {
 ///
 /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
 ///
-/// send_loot!(stream.clone(), PktLoot::loot("Test"))
+/// send_loot!(stream.clone(), PktLoot::new("Test"))
 /// ```
 macro_rules! send_loot {
     ($stream:expr, $pkt_loot:expr) => {
@@ -55,32 +63,44 @@ impl std::fmt::Display for PktLoot {
 
 impl Parser<'_> for PktLoot {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    fn write_with_policy<W: Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
         // Package into a byte array
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
-        let mut target_name_bytes = self.target_name.as_bytes().to_vec();
-        target_name_bytes.resize(32, 0x00); // Pad the name to 32 bytes
+        let target_name_bytes = encode_name(&self.target_name, 32, policy)?; // Pad the name to 32 bytes
         packet.extend(target_name_bytes);
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let message_type = packet.packet_type;
-        let target_name = String::from_utf8_lossy(&packet.body[0..32])
-            .split('\0')
-            .take(1)
-            .collect();
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            packet_type: packet.packet_type,
+            ..PktLootRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktLoot {
+    type Error = std::io::Error;
 
-        Self {
-            packet_type: message_type,
-            target_name,
-        }
+    /// Parse a complete `LOOT` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::LOOT`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::LOOT).and_then(PktLoot::decode)
     }
 }
 
@@ -101,7 +121,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktLoot
-        let message = PktLoot::decode(packet);
+        let message = PktLoot::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::LOOT);
@@ -125,7 +145,7 @@ mod tests {
         body.extend(&name);
 
         let packet = Packet::new(PktType::LOOT, &body);
-        let loot = PktLoot::decode(packet);
+        let loot = PktLoot::decode(packet).expect("Decoding failed");
 
         assert_eq!(loot.target_name.as_ref(), "Deku Baba");
     }
@@ -133,7 +153,7 @@ mod tests {
     /// PktLoot::loot helper constructs correctly.
     #[test]
     fn loot_helper() {
-        let loot = PktLoot::loot("Monster");
+        let loot = PktLoot::new("Monster");
         assert_eq!(loot.packet_type, PktType::LOOT);
         assert_eq!(loot.target_name.as_ref(), "Monster");
     }
@@ -143,7 +163,7 @@ mod tests {
     fn loot_empty_name() {
         let body: Vec<u8> = vec![0x00; 32];
         let packet = Packet::new(PktType::LOOT, &body);
-        let loot = PktLoot::decode(packet);
+        let loot = PktLoot::decode(packet).expect("Decoding failed");
 
         assert_eq!(loot.target_name.as_ref(), "");
     }
@@ -154,7 +174,7 @@ mod tests {
         let long_name = "M".repeat(32);
         let body: Vec<u8> = long_name.as_bytes().to_vec();
         let packet = Packet::new(PktType::LOOT, &body);
-        let loot = PktLoot::decode(packet);
+        let loot = PktLoot::decode(packet).expect("Decoding failed");
 
         assert_eq!(loot.target_name.as_ref(), &long_name);
     }
@@ -162,7 +182,7 @@ mod tests {
     /// Roundtrip.
     #[test]
     fn loot_roundtrip() {
-        let original = PktLoot::loot("DragonBoss");
+        let original = PktLoot::new("DragonBoss");
 
         let mut buffer: Vec<u8> = Vec::new();
         original.write_to(&mut buffer).expect("Encoding failed");
@@ -170,7 +190,7 @@ mod tests {
         assert_eq!(buffer.len(), 33); // type(1) + name(32)
 
         let packet = Packet::new(PktType::LOOT, &buffer[1..]);
-        let deserialized = PktLoot::decode(packet);
+        let deserialized = PktLoot::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.target_name.as_ref(), "DragonBoss");
     }
 
@@ -180,27 +200,25 @@ mod tests {
         let mut body = vec![0xFF, 0xFE, 0xFD];
         body.resize(32, 0x00);
         let packet = Packet::new(PktType::LOOT, &body);
-        let loot = PktLoot::decode(packet);
+        let loot = PktLoot::decode(packet).expect("Decoding failed");
 
         assert!(loot.target_name.contains('\u{FFFD}'));
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn loot_body_too_short_panics() {
+    fn loot_body_too_short_fails() {
         let body: &[u8] = &[0x41, 0x42]; // Only 2 bytes, need 32
         let packet = Packet::new(PktType::LOOT, body);
-        let _ = PktLoot::decode(packet);
+        assert!(PktLoot::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn loot_empty_body_panics() {
+    fn loot_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::LOOT, body);
-        let _ = PktLoot::decode(packet);
+        assert!(PktLoot::decode(packet).is_err());
     }
 
     /// All 0xFF body.
@@ -208,7 +226,7 @@ mod tests {
     fn loot_all_ones_body() {
         let body: Vec<u8> = vec![0xFF; 32];
         let packet = Packet::new(PktType::LOOT, &body);
-        let loot = PktLoot::decode(packet);
+        let loot = PktLoot::decode(packet).expect("Decoding failed");
 
         assert!(!loot.target_name.is_empty());
     }
@@ -216,10 +234,24 @@ mod tests {
     /// Display/JSON output should be valid JSON.
     #[test]
     fn loot_display_valid_json() {
-        let loot = PktLoot::loot("Goblin");
+        let loot = PktLoot::new("Goblin");
         let json_str = format!("{}", loot);
         let parsed: serde_json::Value = serde_json::from_str(&json_str).expect("Invalid JSON");
         assert_eq!(parsed["target_name"], "Goblin");
     }
+
+    /// `new` and the older `loot` constructor build the same packet.
+    #[test]
+    #[allow(deprecated)]
+    fn loot_new() {
+        let packet = PktLoot::new("Goblin");
+
+        assert_eq!(packet.packet_type, PktType::LOOT);
+        assert_eq!(&*packet.target_name, "Goblin");
+        assert_eq!(
+            packet.encode_to_vec().ok(),
+            PktLoot::loot("Goblin").encode_to_vec().ok()
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -2,9 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::io::Write;
 
 use crate::packet::PktType;
+use crate::packet::{NamePolicy, clamp_to_u16_len, encode_len, encode_name};
+use crate::protocol_ref::PktMessageRef;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Sent by the client to message other players.
 ///
 /// - Can also be used by the server to send "presentable" information to the client (information that can be displayed to the user with no further processing).
@@ -13,11 +15,11 @@ use crate::{Packet, Parser};
 pub struct PktMessage {
     /// The type of message for the `MESSAGE` packet. Defaults to 1.
     pub packet_type: PktType,
-    /// The length of the message.
+    /// The length of `message` as decoded. Serializing ignores it and writes the length of `message` itself.
     pub message_len: u16,
     /// The recipient of the message, up to 32 bytes.
     pub recipient: Box<str>,
-    /// The sender of the message, up to 30 bytes.
+    /// The sender of the message, up to 32 bytes, or 30 for narration, whose last two bytes hold the narration marker.
     pub sender: Box<str>,
     /// Whether the message is narration (from the narrator) or not (from a player or the server).
     pub narration: bool,
@@ -26,10 +28,17 @@ pub struct PktMessage {
 }
 
 impl PktMessage {
+    /// Create a new `PktMessage` from `sender` to `recipient`. Equivalent to [`PktMessage::player`].
+    pub fn new(sender: &str, recipient: &str, message: &str) -> Self {
+        Self::player(sender, recipient, message)
+    }
+
     /// Create a new `PktMessage` from the server to a specific recipient.
     /// The sender will be "Server" and the narration flag will be false.
     /// This is used for system messages, such as "You have been disconnected" or "Welcome to the game".
     pub fn server(recipient: &str, message: &str) -> Self {
+        let message = clamp_to_u16_len(message);
+
         Self {
             packet_type: PktType::MESSAGE,
             message_len: message.len() as u16,
@@ -44,6 +53,8 @@ impl PktMessage {
     /// The sender will be "Narrator" and the narration flag will be true.
     /// This is used for room descriptions and other narrative messages.
     pub fn narrator(recipient: &str, message: &str) -> Self {
+        let message = clamp_to_u16_len(message);
+
         Self {
             packet_type: PktType::MESSAGE,
             message_len: message.len() as u16,
@@ -54,9 +65,20 @@ impl PktMessage {
         }
     }
 
+    /// Create a new narration `PktMessage` under a custom narrator name, e.g. `"The Dungeon"`.
+    /// Like [`PktMessage::narrator`], the narration flag will be true, so the name is limited to 30 bytes.
+    pub fn narrated(sender: &str, recipient: &str, message: &str) -> Self {
+        Self {
+            sender: Box::from(sender),
+            ..Self::narrator(recipient, message)
+        }
+    }
+
     /// Create a new `PktMessage` from a given player to a specific recipient.
     /// This is used for player to player messaging.
     pub fn player(sender: &str, recipient: &str, message: &str) -> Self {
+        let message = clamp_to_u16_len(message);
+
         Self {
             packet_type: PktType::MESSAGE,
             message_len: message.len() as u16,
@@ -66,6 +88,39 @@ impl PktMessage {
             message: message.into(),
         }
     }
+
+    /// Who the message is from, as distinguished by the constructors.
+    ///
+    /// Narration is always from the narrator; otherwise a sender of `"Server"` is the server and anything else is a player.
+    ///
+    /// ```
+    /// use lurk_protocol::{MessageSource, PktMessage};
+    ///
+    /// assert_eq!(PktMessage::narrator("Bob", "It is dark.").source(), MessageSource::Narrator);
+    /// assert_eq!(PktMessage::server("Bob", "Welcome!").source(), MessageSource::Server);
+    /// assert_eq!(
+    ///     PktMessage::player("Alice", "Bob", "Hi!").source(),
+    ///     MessageSource::Player("Alice".into())
+    /// );
+    /// ```
+    pub fn source(&self) -> MessageSource {
+        match (self.narration, self.sender.as_ref()) {
+            (true, _) => MessageSource::Narrator,
+            (false, "Server") => MessageSource::Server,
+            (false, sender) => MessageSource::Player(sender.into()),
+        }
+    }
+}
+
+/// The kind of sender of a [`PktMessage`], from [`PktMessage::source`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MessageSource {
+    /// Sent by the named player.
+    Player(Box<str>),
+    /// A system message from the server.
+    Server,
+    /// Narration describing the game world.
+    Narrator,
 }
 
 #[macro_export]
@@ -109,72 +164,58 @@ impl std::fmt::Display for PktMessage {
 
 impl Parser<'_> for PktMessage {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    fn write_with_policy<W: Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
         // Package into a byte array
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
-        packet.extend(self.message_len.to_le_bytes());
+        packet.extend(encode_len(&self.message)?);
 
-        let mut r_bytes = self.recipient.as_bytes().to_vec();
-        let mut s_bytes = self.sender.as_bytes().to_vec();
-
-        // Pad the recipient and sender names to 32 bytes
-        r_bytes.resize(32, 0x00);
-        s_bytes.resize(30, 0x00);
+        // Pad the recipient to 32 bytes. A narrator's name gets 30, leaving room for the narration marker
+        let r_bytes = encode_name(&self.recipient, 32, policy)?;
+        let sender_width = if self.narration { 30 } else { 32 };
+        let mut s_bytes = encode_name(&self.sender, sender_width, policy)?;
 
         // If the sender is a narrator, append 0x00 0x01 to the end of the sender name
         if self.narration {
             s_bytes.extend_from_slice(&[0x00, 0x01]);
-        } else {
-            s_bytes.resize(32, 0x00);
         }
         packet.extend(r_bytes);
         packet.extend(s_bytes);
 
-        // Append the message
-        packet.extend(self.message.as_bytes());
-
-        // Write the packet to the buffer
+        // Write the fixed fields, then the message straight from `self` rather than copying it into `packet`
         writer
             .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+            .and_then(|_| writer.write_all(self.message.as_bytes()))
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+            })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let message_len = u16::from_le_bytes([packet.body[0], packet.body[1]]);
-
-        // Process the names for recipient and sender
-        let r_bytes = packet.body[2..34].to_vec();
-        let mut s_bytes = packet.body[34..66].to_vec();
-
-        // If the last 2 bytes of the sender is 0x00 0x01, it means the sender is a narrator
-        let narration = match s_bytes.get(30..32) {
-            Some(&[0x00, 0x01]) => {
-                s_bytes.truncate(30); // Remove the narration marker bytes
-                true
-            }
-            _ => false,
-        };
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            packet_type: packet.packet_type,
+            ..PktMessageRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
 
-        let recipient = String::from_utf8_lossy(&r_bytes)
-            .split('\0')
-            .take(1)
-            .collect();
-        let sender = String::from_utf8_lossy(&s_bytes)
-            .split('\0')
-            .take(1)
-            .collect();
-        let message = String::from_utf8_lossy(&packet.body[66..]).into();
+impl TryFrom<&[u8]> for PktMessage {
+    type Error = std::io::Error;
 
-        Self {
-            packet_type: packet.packet_type,
-            message_len,
-            recipient,
-            sender,
-            narration,
-            message,
-        }
+    /// Parse a complete `MESSAGE` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::MESSAGE`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::MESSAGE).and_then(PktMessage::decode)
     }
 }
 
@@ -198,7 +239,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktMessage
-        let message = PktMessage::decode(packet);
+        let message = PktMessage::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::MESSAGE);
@@ -231,7 +272,7 @@ mod tests {
         body.extend(b"Sup");
 
         let packet = Packet::new(PktType::MESSAGE, &body);
-        let msg = PktMessage::decode(packet);
+        let msg = PktMessage::decode(packet).expect("Decoding failed");
 
         assert_eq!(msg.message_len, 3);
         assert_eq!(msg.recipient.as_ref(), "Player1");
@@ -255,7 +296,7 @@ mod tests {
         body.extend(msg_text.as_bytes());
 
         let packet = Packet::new(PktType::MESSAGE, &body);
-        let msg = PktMessage::decode(packet);
+        let msg = PktMessage::decode(packet).expect("Decoding failed");
 
         assert_eq!(msg.recipient.as_ref(), "Player1");
         assert_eq!(msg.sender.as_ref(), "Server");
@@ -303,11 +344,67 @@ mod tests {
 
         // Deserialize and verify
         let packet = Packet::new(PktType::MESSAGE, &buffer[1..]);
-        let deserialized = PktMessage::decode(packet);
+        let deserialized = PktMessage::decode(packet).expect("Decoding failed");
         assert!(deserialized.narration);
         assert_eq!(deserialized.sender.as_ref(), "Narrator");
     }
 
+    /// A narrator name filling all 30 bytes sits directly against the marker; both are read back intact.
+    #[test]
+    fn message_narration_full_width_sender() {
+        let sender = "N".repeat(30);
+        let mut body = vec![0x02, 0x00];
+        body.extend(b"Player1");
+        body.resize(34, 0x00);
+        body.extend(sender.as_bytes());
+        body.extend([0x00, 0x01]);
+        body.extend(b"Hi");
+
+        let msg =
+            PktMessage::decode(Packet::new(PktType::MESSAGE, &body)).expect("Decoding failed");
+        assert!(msg.narration);
+        assert_eq!(msg.sender.as_ref(), sender);
+        assert_eq!(msg.recipient.as_ref(), "Player1");
+        assert_eq!(msg.message.as_ref(), "Hi");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        msg.write_to(&mut buffer).expect("Encoding failed");
+        assert_eq!(&buffer[1..], body.as_slice());
+    }
+
+    /// A player name filling all 32 bytes is not mistaken for the narration marker.
+    #[test]
+    fn message_full_width_sender_not_narration() {
+        let sender = "P".repeat(32);
+        let msg = PktMessage::player(&sender, "Player1", "Hi");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        msg.write_to(&mut buffer).expect("Encoding failed");
+
+        let decoded = PktMessage::decode(Packet::new(PktType::MESSAGE, &buffer[1..]))
+            .expect("Decoding failed");
+        assert!(!decoded.narration);
+        assert_eq!(decoded.sender.as_ref(), sender);
+    }
+
+    /// A player message with a multi-word sender, and narration under a custom name, survive the wire.
+    #[test]
+    fn message_player_and_narrated_roundtrip() {
+        for msg in [
+            PktMessage::player("Sir Robin the Brave", "King Arthur", "Run away!"),
+            PktMessage::narrated("The Old Crone", "King Arthur", "A shrubbery!"),
+        ] {
+            let buffer = msg.encode_to_vec().expect("Encoding failed");
+            let decoded = PktMessage::decode(Packet::new(PktType::MESSAGE, &buffer[1..]))
+                .expect("Decoding failed");
+
+            assert_eq!(decoded.sender, msg.sender);
+            assert_eq!(decoded.recipient.as_ref(), "King Arthur");
+            assert_eq!(decoded.narration, msg.narration);
+            assert_eq!(decoded.message, msg.message);
+        }
+    }
+
     /// Non-narration message should not have the marker.
     #[test]
     fn message_non_narration_roundtrip() {
@@ -317,7 +414,7 @@ mod tests {
         msg.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::MESSAGE, &buffer[1..]);
-        let deserialized = PktMessage::decode(packet);
+        let deserialized = PktMessage::decode(packet).expect("Decoding failed");
         assert!(!deserialized.narration);
         assert_eq!(deserialized.sender.as_ref(), "Server");
         assert_eq!(deserialized.message.as_ref(), "Hello.");
@@ -332,7 +429,7 @@ mod tests {
         msg.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::MESSAGE, &buffer[1..]);
-        let deserialized = PktMessage::decode(packet);
+        let deserialized = PktMessage::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.message_len, 0);
         assert_eq!(deserialized.message.as_ref(), "");
     }
@@ -347,7 +444,7 @@ mod tests {
         msg.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::MESSAGE, &buffer[1..]);
-        let deserialized = PktMessage::decode(packet);
+        let deserialized = PktMessage::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.message_len, 5000);
         assert_eq!(deserialized.message.len(), 5000);
     }
@@ -369,26 +466,24 @@ mod tests {
         msg.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::MESSAGE, &buffer[1..]);
-        let deserialized = PktMessage::decode(packet);
+        let deserialized = PktMessage::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.recipient.as_ref(), &long_name);
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn message_body_too_short_panics() {
+    fn message_body_too_short_fails() {
         let body: &[u8] = &[0x00, 0x00, 0x41]; // Only 3 bytes, need at least 66
         let packet = Packet::new(PktType::MESSAGE, body);
-        let _ = PktMessage::decode(packet);
+        assert!(PktMessage::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn message_empty_body_panics() {
+    fn message_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::MESSAGE, body);
-        let _ = PktMessage::decode(packet);
+        assert!(PktMessage::decode(packet).is_err());
     }
 
     /// All-zero 66-byte body should parse without panic.
@@ -396,7 +491,7 @@ mod tests {
     fn message_all_zeros_body() {
         let body: Vec<u8> = vec![0x00; 66];
         let packet = Packet::new(PktType::MESSAGE, &body);
-        let msg = PktMessage::decode(packet);
+        let msg = PktMessage::decode(packet).expect("Decoding failed");
 
         assert_eq!(msg.message_len, 0);
         assert_eq!(msg.recipient.as_ref(), "");
@@ -409,7 +504,7 @@ mod tests {
     fn message_all_ones_body() {
         let body: Vec<u8> = vec![0xFF; 66];
         let packet = Packet::new(PktType::MESSAGE, &body);
-        let msg = PktMessage::decode(packet);
+        let msg = PktMessage::decode(packet).expect("Decoding failed");
 
         assert_eq!(msg.message_len, u16::MAX);
         // Recipient and sender will contain replacement chars for invalid UTF-8
@@ -430,7 +525,7 @@ mod tests {
         body.extend(&sender);
 
         let packet = Packet::new(PktType::MESSAGE, &body);
-        let msg = PktMessage::decode(packet);
+        let msg = PktMessage::decode(packet).expect("Decoding failed");
 
         assert!(msg.recipient.contains('\u{FFFD}'));
         assert!(msg.sender.contains('\u{FFFD}'));
@@ -468,7 +563,7 @@ mod tests {
         msg.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::MESSAGE, &buffer[1..]);
-        let deserialized = PktMessage::decode(packet);
+        let deserialized = PktMessage::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.message_len, 3);
         assert_eq!(deserialized.recipient.as_ref(), "Player1");
@@ -476,5 +571,87 @@ mod tests {
         assert!(!deserialized.narration);
         assert_eq!(deserialized.message.as_ref(), "Sup");
     }
+
+    /// Recipient and sender are cut at the first NUL, matching every other name field.
+    #[test]
+    fn message_names_interior_nul() {
+        let mut body: Vec<u8> = vec![0x00, 0x00];
+        let mut recipient = b"Alice\0Bob".to_vec();
+        recipient.resize(32, 0x00);
+        let mut sender = b"Carol\0Dave".to_vec();
+        sender.resize(32, 0x00);
+        body.extend(recipient);
+        body.extend(sender);
+        let packet = Packet::new(PktType::MESSAGE, &body);
+        let message = PktMessage::decode(packet).expect("Decoding failed");
+
+        assert_eq!(message.recipient.as_ref(), "Alice");
+        assert_eq!(message.sender.as_ref(), "Carol");
+    }
+
+    /// A message over 64KiB is truncated rather than wrapping `message_len`.
+    #[test]
+    fn message_server_oversized_does_not_wrap() {
+        let text = "A".repeat(70 * 1024);
+        let message = PktMessage::server("Player", &text);
+
+        assert_eq!(message.message_len, u16::MAX);
+        assert_eq!(message.message.len(), message.message_len as usize);
+    }
+
+    /// Truncation of an oversized message never splits a multi-byte character.
+    #[test]
+    fn message_narrator_oversized_char_boundary() {
+        let text = "€".repeat(30_000); // 3 bytes each, 90,000 bytes total
+        let message = PktMessage::narrator("Player", &text);
+
+        assert_eq!(message.message.len(), message.message_len as usize);
+        assert!(message.message.chars().all(|c| c == '€'));
+        assert_eq!(message.message_len, 65_535);
+    }
+
+    /// The source survives a round trip through the wire format.
+    #[test]
+    fn message_source_after_decode() {
+        for (message, source) in [
+            (
+                PktMessage::narrator("Bob", "Dusk."),
+                MessageSource::Narrator,
+            ),
+            (PktMessage::server("Bob", "Hi."), MessageSource::Server),
+            (
+                PktMessage::player("Alice", "Bob", "Yo."),
+                MessageSource::Player("Alice".into()),
+            ),
+        ] {
+            let bytes = message.encode_to_vec().expect("Encoding failed");
+            let decoded = PktMessage::try_from(bytes.as_slice()).expect("Decoding failed");
+            assert_eq!(decoded.source(), source);
+        }
+    }
+
+    /// A narrated message from a player-named sender is still narration.
+    #[test]
+    fn message_source_narration_wins() {
+        let mut message = PktMessage::player("Alice", "Bob", "The lights go out.");
+        message.narration = true;
+        assert_eq!(message.source(), MessageSource::Narrator);
+    }
+
+    /// `new` builds a player message with the length derived from the text.
+    #[test]
+    fn message_new() {
+        let packet = PktMessage::new("Alice", "Bob", "Hello!");
+
+        assert_eq!(packet.packet_type, PktType::MESSAGE);
+        assert_eq!(packet.message_len, 6);
+        assert!(!packet.narration);
+        assert_eq!(
+            packet.encode_to_vec().ok(),
+            PktMessage::player("Alice", "Bob", "Hello!")
+                .encode_to_vec()
+                .ok()
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
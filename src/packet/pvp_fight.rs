@@ -2,9 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::io::Write;
 
 use crate::packet::PktType;
+use crate::packet::{NamePolicy, encode_name};
+use crate::protocol_ref::PktPVPFightRef;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Initiate a fight against another player.
 ///
 /// - The server will determine the results of the fight, and allocate damage and rewards appropriately.
@@ -21,13 +23,19 @@ pub struct PktPVPFight {
 }
 
 impl PktPVPFight {
-    /// Create a new PktPVPFight packet from a given name
-    pub fn fight(name: &str) -> Self {
+    /// Create a new `PktPVPFight` packet targeting the given player.
+    pub fn new(target_name: &str) -> Self {
         Self {
             packet_type: PktType::PVPFIGHT,
-            target_name: Box::from(name),
+            target_name: Box::from(target_name),
         }
     }
+
+    /// Create a new PktPVPFight packet from a given name. Equivalent to [`PktPVPFight::new`].
+    #[deprecated(note = "use new")]
+    pub fn fight(name: &str) -> Self {
+        Self::new(name)
+    }
 }
 
 #[macro_export]
@@ -40,7 +48,7 @@ impl PktPVPFight {
 ///
 /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
 ///
-/// send_pvp!(stream.clone(), PktPVPFight::fight("Test"))
+/// send_pvp!(stream.clone(), PktPVPFight::new("Test"))
 /// ```
 macro_rules! send_pvp {
     ($stream:expr, $pkt_pvp:expr) => {
@@ -63,32 +71,44 @@ impl std::fmt::Display for PktPVPFight {
 
 impl Parser<'_> for PktPVPFight {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    fn write_with_policy<W: Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
         // Package into a byte array
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
-        let mut target_name_bytes = self.target_name.as_bytes().to_vec();
-        target_name_bytes.resize(32, 0x00); // Pad the name to 32 bytes
+        let target_name_bytes = encode_name(&self.target_name, 32, policy)?; // Pad the name to 32 bytes
         packet.extend(target_name_bytes);
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let message_type = packet.packet_type;
-        let target_name = String::from_utf8_lossy(&packet.body[0..32])
-            .split('\0')
-            .take(1)
-            .collect();
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            packet_type: packet.packet_type,
+            ..PktPVPFightRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktPVPFight {
+    type Error = std::io::Error;
 
-        Self {
-            packet_type: message_type,
-            target_name,
-        }
+    /// Parse a complete `PVPFIGHT` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::PVPFIGHT`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::PVPFIGHT).and_then(PktPVPFight::decode)
     }
 }
 
@@ -109,7 +129,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktPVPFight
-        let message = PktPVPFight::decode(packet);
+        let message = PktPVPFight::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::PVPFIGHT);
@@ -127,7 +147,7 @@ mod tests {
     /// PktPVPFight::fight helper constructs correctly.
     #[test]
     fn pvp_fight_helper() {
-        let pvp = PktPVPFight::fight("EnemyPlayer");
+        let pvp = PktPVPFight::new("EnemyPlayer");
         assert_eq!(pvp.packet_type, PktType::PVPFIGHT);
         assert_eq!(pvp.target_name.as_ref(), "EnemyPlayer");
     }
@@ -137,7 +157,7 @@ mod tests {
     fn pvp_fight_empty_name() {
         let body: Vec<u8> = vec![0x00; 32];
         let packet = Packet::new(PktType::PVPFIGHT, &body);
-        let pvp = PktPVPFight::decode(packet);
+        let pvp = PktPVPFight::decode(packet).expect("Decoding failed");
 
         assert_eq!(pvp.target_name.as_ref(), "");
     }
@@ -148,7 +168,7 @@ mod tests {
         let long_name = "P".repeat(32);
         let body: Vec<u8> = long_name.as_bytes().to_vec();
         let packet = Packet::new(PktType::PVPFIGHT, &body);
-        let pvp = PktPVPFight::decode(packet);
+        let pvp = PktPVPFight::decode(packet).expect("Decoding failed");
 
         assert_eq!(pvp.target_name.as_ref(), &long_name);
     }
@@ -156,7 +176,7 @@ mod tests {
     /// Roundtrip.
     #[test]
     fn pvp_fight_roundtrip() {
-        let original = PktPVPFight::fight("Rival");
+        let original = PktPVPFight::new("Rival");
 
         let mut buffer: Vec<u8> = Vec::new();
         original.write_to(&mut buffer).expect("Encoding failed");
@@ -164,7 +184,7 @@ mod tests {
         assert_eq!(buffer.len(), 33); // type(1) + name(32)
 
         let packet = Packet::new(PktType::PVPFIGHT, &buffer[1..]);
-        let deserialized = PktPVPFight::decode(packet);
+        let deserialized = PktPVPFight::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.target_name.as_ref(), "Rival");
     }
 
@@ -174,27 +194,25 @@ mod tests {
         let mut body = vec![0xFF, 0xFE, 0xFD];
         body.resize(32, 0x00);
         let packet = Packet::new(PktType::PVPFIGHT, &body);
-        let pvp = PktPVPFight::decode(packet);
+        let pvp = PktPVPFight::decode(packet).expect("Decoding failed");
 
         assert!(pvp.target_name.contains('\u{FFFD}'));
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn pvp_fight_body_too_short_panics() {
+    fn pvp_fight_body_too_short_fails() {
         let body: &[u8] = &[0x41, 0x42]; // Only 2 bytes, need 32
         let packet = Packet::new(PktType::PVPFIGHT, body);
-        let _ = PktPVPFight::decode(packet);
+        assert!(PktPVPFight::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn pvp_fight_empty_body_panics() {
+    fn pvp_fight_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::PVPFIGHT, body);
-        let _ = PktPVPFight::decode(packet);
+        assert!(PktPVPFight::decode(packet).is_err());
     }
 
     /// All 0xFF body.
@@ -202,7 +220,7 @@ mod tests {
     fn pvp_fight_all_ones_body() {
         let body: Vec<u8> = vec![0xFF; 32];
         let packet = Packet::new(PktType::PVPFIGHT, &body);
-        let pvp = PktPVPFight::decode(packet);
+        let pvp = PktPVPFight::decode(packet).expect("Decoding failed");
 
         assert!(!pvp.target_name.is_empty());
     }
@@ -210,10 +228,24 @@ mod tests {
     /// Display/JSON output should be valid JSON.
     #[test]
     fn pvp_fight_display_valid_json() {
-        let pvp = PktPVPFight::fight("Enemy");
+        let pvp = PktPVPFight::new("Enemy");
         let json_str = format!("{}", pvp);
         let parsed: serde_json::Value = serde_json::from_str(&json_str).expect("Invalid JSON");
         assert_eq!(parsed["target_name"], "Enemy");
     }
+
+    /// `new` and the older `fight` constructor build the same packet.
+    #[test]
+    #[allow(deprecated)]
+    fn pvp_fight_new() {
+        let packet = PktPVPFight::new("Bob");
+
+        assert_eq!(packet.packet_type, PktType::PVPFIGHT);
+        assert_eq!(&*packet.target_name, "Bob");
+        assert_eq!(
+            packet.encode_to_vec().ok(),
+            PktPVPFight::fight("Bob").encode_to_vec().ok()
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -2,9 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::io::Write;
 
 use crate::packet::PktType;
-use crate::{Packet, Parser};
+use crate::packet::{NamePolicy, clamp_to_u16_len, encode_len, encode_name};
+use crate::protocol_ref::PktRoomRef;
+use crate::{Packet, Parser, PktMessage};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Sent by the server to describe the room that the player is in.
 ///
 /// - This should be an expected response to `PktType::CHANGEROOM` or `PktType::START`.
@@ -18,12 +20,44 @@ pub struct PktRoom {
     pub room_number: u16,
     /// The name of the room, up to 32 bytes.
     pub room_name: Box<str>,
-    /// The length of the room description.
+    /// The length of `description` as decoded. Serializing ignores it and writes the length of `description` itself.
     pub description_len: u16,
     /// The room description.
     pub description: Box<str>,
 }
 
+impl PktRoom {
+    /// Create a new `PktRoom` packet.
+    ///
+    /// Descriptions longer than `u16::MAX` bytes are truncated on a `char` boundary so `description_len` always matches.
+    pub fn new(room_number: u16, room_name: &str, description: &str) -> Self {
+        let description = clamp_to_u16_len(description);
+
+        Self {
+            packet_type: PktType::ROOM,
+            room_number,
+            room_name: Box::from(room_name),
+            description_len: description.len() as u16,
+            description: Box::from(description),
+        }
+    }
+
+    /// Build a narrator `MESSAGE` carrying this room's description, for servers that show the room text as narration.
+    ///
+    /// ```
+    /// use lurk_protocol::PktRoom;
+    ///
+    /// let room = PktRoom::new(1, "Cellar", "Damp and dark.");
+    ///
+    /// let message = room.as_narration("Player");
+    /// assert!(message.narration);
+    /// assert_eq!(message.message.as_ref(), "Damp and dark.");
+    /// ```
+    pub fn as_narration(&self, recipient: &str) -> PktMessage {
+        PktMessage::narrator(recipient, &self.description)
+    }
+}
+
 #[macro_export]
 /// Send `PktRoom` over `TcpStream` to connected user
 ///
@@ -63,43 +97,51 @@ impl std::fmt::Display for PktRoom {
 
 impl Parser<'_> for PktRoom {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    fn write_with_policy<W: Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
         // Package into a byte array
         let mut packet: Vec<u8> = vec![self.packet_type.into()];
 
         packet.extend(self.room_number.to_le_bytes());
 
-        let mut room_name_bytes = self.room_name.as_bytes().to_vec();
-        room_name_bytes.resize(32, 0); // Pad with zeros to 32 bytes
+        let room_name_bytes = encode_name(&self.room_name, 32, policy)?; // Pad with zeros to 32 bytes
         packet.extend(room_name_bytes);
 
-        packet.extend(self.description_len.to_le_bytes());
-        packet.extend(self.description.as_bytes());
+        packet.extend(encode_len(&self.description)?);
 
-        // Write the packet to the buffer
+        // Write the fixed fields, then the description straight from `self` rather than copying it into `packet`
         writer
             .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+            .and_then(|_| writer.write_all(self.description.as_bytes()))
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+            })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        let message_type = packet.packet_type;
-        let room_number = u16::from_le_bytes([packet.body[0], packet.body[1]]);
-        let room_name = String::from_utf8_lossy(&packet.body[2..34])
-            .split('\0')
-            .take(1)
-            .collect();
-        let description_len = u16::from_le_bytes([packet.body[34], packet.body[35]]);
-        let description = String::from_utf8_lossy(&packet.body[36..]).into();
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            packet_type: packet.packet_type,
+            ..PktRoomRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
 
-        Self {
-            packet_type: message_type,
-            room_number,
-            room_name,
-            description_len,
-            description,
-        }
+impl TryFrom<&[u8]> for PktRoom {
+    type Error = std::io::Error;
+
+    /// Parse a complete `ROOM` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::ROOM`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::ROOM).and_then(PktRoom::decode)
     }
 }
 
@@ -122,7 +164,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktRoom
-        let message = PktRoom::decode(packet);
+        let message = PktRoom::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::ROOM);
@@ -157,7 +199,7 @@ mod tests {
         body.extend(desc.as_bytes());
 
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(room.room_number, 0);
         assert_eq!(room.room_name.as_ref(), room_name);
@@ -175,7 +217,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(room.room_number, 5);
         assert_eq!(room.room_name.as_ref(), "Empty");
@@ -194,7 +236,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(room.room_number, u16::MAX);
     }
@@ -209,7 +251,7 @@ mod tests {
         body.extend(0u16.to_le_bytes());
 
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(room.room_name.as_ref(), &long_name);
     }
@@ -229,7 +271,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::ROOM, &buffer[1..]);
-        let deserialized = PktRoom::decode(packet);
+        let deserialized = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.room_number, 42);
         assert_eq!(deserialized.room_name.as_ref(), "Treasure Room");
@@ -249,7 +291,7 @@ mod tests {
         body.extend(desc.as_bytes());
 
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(room.description.len(), 5000);
     }
@@ -266,28 +308,26 @@ mod tests {
         body.extend(&[0xFC, 0xFB, 0xFA]);
 
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert!(room.room_name.contains('\u{FFFD}'));
         assert!(room.description.contains('\u{FFFD}'));
     }
 
-    /// Body too short should panic.
+    /// Body too short should fail to decode.
     #[test]
-    #[should_panic]
-    fn room_body_too_short_panics() {
+    fn room_body_too_short_fails() {
         let body: &[u8] = &[0x00, 0x00]; // Need at least 36
         let packet = Packet::new(PktType::ROOM, body);
-        let _ = PktRoom::decode(packet);
+        assert!(PktRoom::decode(packet).is_err());
     }
 
-    /// Empty body should panic.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn room_empty_body_panics() {
+    fn room_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::ROOM, body);
-        let _ = PktRoom::decode(packet);
+        assert!(PktRoom::decode(packet).is_err());
     }
 
     /// All zeros body (36 bytes min header).
@@ -295,7 +335,7 @@ mod tests {
     fn room_all_zeros_body() {
         let body: Vec<u8> = vec![0x00; 36];
         let packet = Packet::new(PktType::ROOM, &body);
-        let room = PktRoom::decode(packet);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
 
         assert_eq!(room.room_number, 0);
         assert_eq!(room.room_name.as_ref(), "");
@@ -317,5 +357,50 @@ mod tests {
         assert_eq!(parsed["room_number"], 1);
         assert_eq!(parsed["room_name"], "Hall");
     }
+
+    /// Lossy UTF-8 decoding of a Latin-1 description does not round-trip byte-for-byte.
+    #[test]
+    fn room_latin1_description_is_lossy() {
+        let mut body: Vec<u8> = vec![0x01, 0x00];
+        let mut name = b"Cafe".to_vec();
+        name.resize(32, 0x00);
+        body.extend(name);
+        body.extend([0x05, 0x00]);
+        body.extend(b"Caf\xe9!"); // "Café!" in Latin-1
+        let packet = Packet::new(PktType::ROOM, &body);
+        let room = PktRoom::decode(packet).expect("Decoding failed");
+
+        assert_eq!(room.description.as_ref(), "Caf\u{FFFD}!");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        room.write_to(&mut buffer).expect("Encoding failed");
+
+        // The replacement character is 3 bytes where the original was 1
+        assert_ne!(&buffer[1..], body.as_slice());
+        assert_eq!(&buffer[37..], "Caf\u{FFFD}!".as_bytes());
+    }
+
+    /// The narration carries the description to the given recipient.
+    #[test]
+    fn room_as_narration() {
+        let room = PktRoom::new(3, "Library", "Dusty shelves tower.");
+
+        let message = room.as_narration("Reader");
+        assert_eq!(message.recipient.as_ref(), "Reader");
+        assert_eq!(message.sender.as_ref(), "Narrator");
+        assert_eq!(message.message.as_ref(), "Dusty shelves tower.");
+        assert_eq!(message.message_len, 20);
+    }
+
+    /// `new` derives the description length from the description.
+    #[test]
+    fn room_new() {
+        let packet = PktRoom::new(3, "Library", "Dusty shelves.");
+
+        assert_eq!(packet.packet_type, PktType::ROOM);
+        assert_eq!(packet.room_number, 3);
+        assert_eq!(packet.description_len, 14);
+        assert_eq!(&*packet.description, "Dusty shelves.");
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
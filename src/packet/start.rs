@@ -4,7 +4,7 @@ use std::io::Write;
 use crate::packet::PktType;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Start playing the game.
 ///
 /// - A client will send a `PktType::CHARACTER` message to the server to explain character stats, which the server may either accept or deny (by use of an `PktType::ERROR` message).
@@ -24,6 +24,13 @@ impl Default for PktStart {
     }
 }
 
+impl PktStart {
+    /// Create a new `PktStart` packet. Equivalent to `PktStart::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 #[macro_export]
 /// Send `PktStart` over `TcpStream` to connected user
 ///
@@ -60,17 +67,28 @@ impl Parser<'_> for PktStart {
         let packet: Vec<u8> = vec![self.packet_type.into()];
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
             packet_type: packet.packet_type,
-        }
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktStart {
+    type Error = std::io::Error;
+
+    /// Parse a complete `START` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::START`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::START).and_then(PktStart::decode)
     }
 }
 
@@ -86,7 +104,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktStart
-        let message = PktStart::decode(packet);
+        let message = PktStart::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::START);
@@ -126,7 +144,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::START, &[]);
-        let deserialized = PktStart::decode(packet);
+        let deserialized = PktStart::decode(packet).expect("Decoding failed");
         assert_eq!(deserialized.packet_type, PktType::START);
     }
 
@@ -135,7 +153,7 @@ mod tests {
     fn start_extra_body_bytes() {
         let body: &[u8] = &[0xFF, 0xFF];
         let packet = Packet::new(PktType::START, body);
-        let start = PktStart::decode(packet);
+        let start = PktStart::decode(packet).expect("Decoding failed");
         assert_eq!(start.packet_type, PktType::START);
     }
 
@@ -153,8 +171,18 @@ mod tests {
     fn start_decode_uses_packet_type() {
         // Pass a non-START type to verify decode reads from the packet
         let packet = Packet::new(PktType::DEFAULT, &[]);
-        let start = PktStart::decode(packet);
+        let start = PktStart::decode(packet).expect("Decoding failed");
         assert_eq!(start.packet_type, PktType::DEFAULT);
     }
+
+    /// `new` builds the same packet as `Default`.
+    #[test]
+    fn start_new() {
+        assert_eq!(PktStart::new().packet_type, PktType::START);
+        assert_eq!(
+            PktStart::new().encode_to_vec().ok(),
+            PktStart::default().encode_to_vec().ok()
+        );
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::io::Write;
+use std::io::{Error, ErrorKind, Write};
 
 use crate::packet::PktType;
+use crate::protocol_ref::PktVersionRef;
 use crate::{Packet, Parser};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 /// Sent by the server upon initial connection along with `PktType::GAME`.
 pub struct PktVersion {
     /// The type of message for the `VERSION` packet. Defaults to 14.
@@ -13,34 +14,143 @@ pub struct PktVersion {
     pub major_rev: u8,
     /// The minor revision number of the server.
     pub minor_rev: u8,
-    /// The length of the extensions field.
+    /// The length of `extensions` as decoded. Serializing ignores it and writes the length of `extensions` itself.
     pub extensions_len: u16,
     /// The extensions field:
     /// - 0-1 Length of the first extension, as an unsigned 16-bit integer.
     /// - 2+ First extension
     ///
     /// At the end of the first extension, if there are more extensions, the length of the second extension will be found, then the second extension, and so on.
-    /// The length of the list of extensions must be the same as `extension_len`.
     /// Note that servers and clients are not required to support any extensions at all, and in this case are free to ignore the list.
     pub extensions: Option<Vec<u8>>, // 0-1 length, 2+ extension;
 }
 
+impl PktVersion {
+    /// Create a new `PktVersion` packet advertising no extensions.
+    ///
+    /// Use [`ExtensionSet::to_version`](crate::ExtensionSet::to_version) to advertise extensions.
+    pub fn new(major_rev: u8, minor_rev: u8) -> Self {
+        Self {
+            packet_type: PktType::VERSION,
+            major_rev,
+            minor_rev,
+            extensions_len: 0,
+            extensions: None,
+        }
+    }
+
+    /// Create a new `PktVersion` advertising `extensions` in the given order, each prefixed with its length.
+    ///
+    /// [`ExtensionSet::to_version`](crate::ExtensionSet::to_version) does the same for a deduplicated set.
+    /// Fails with [`ErrorKind::InvalidInput`] if an extension or the encoded list is longer than 65535 bytes.
+    ///
+    /// ```
+    /// use lurk_protocol::PktVersion;
+    ///
+    /// let version = PktVersion::with_extensions(2, 3, [b"SEQ".as_slice(), b"OK"]).unwrap();
+    ///
+    /// assert_eq!(version.extensions_len, 9);
+    /// assert_eq!(version.extension(0), Some(b"SEQ".as_slice()));
+    /// ```
+    pub fn with_extensions<I>(major_rev: u8, minor_rev: u8, extensions: I) -> Result<Self, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut list: Vec<u8> = Vec::new();
+
+        for extension in extensions {
+            let extension = extension.as_ref();
+            let len = u16::try_from(extension.len())
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Extension is too long"))?;
+
+            list.extend(len.to_le_bytes());
+            list.extend(extension);
+        }
+
+        let extensions_len = u16::try_from(list.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidInput, "Extension list is too long"))?;
+
+        Ok(Self {
+            extensions_len,
+            extensions: (!list.is_empty()).then_some(list),
+            ..Self::new(major_rev, minor_rev)
+        })
+    }
+
+    /// A `VERSION` packet advertising [`PROTOCOL_VERSION`](crate::PROTOCOL_VERSION), the revision this crate
+    /// implements, with no extensions.
+    ///
+    /// ```
+    /// use lurk_protocol::{PROTOCOL_VERSION, PktVersion};
+    ///
+    /// let version = PktVersion::current();
+    /// assert_eq!((version.major_rev, version.minor_rev), PROTOCOL_VERSION);
+    /// ```
+    pub fn current() -> Self {
+        let (major_rev, minor_rev) = crate::PROTOCOL_VERSION;
+
+        Self::new(major_rev, minor_rev)
+    }
+
+    /// The number of extensions in the list.
+    ///
+    /// A trailing extension whose length runs past the end of the list is not counted.
+    pub fn extension_count(&self) -> usize {
+        self.extension_blobs().count()
+    }
+
+    /// The `i`th extension in the list, without its length prefix.
+    ///
+    /// ```
+    /// use lurk_protocol::{PktType, PktVersion};
+    ///
+    /// let version = PktVersion {
+    ///     packet_type: PktType::VERSION,
+    ///     major_rev: 2,
+    ///     minor_rev: 3,
+    ///     extensions_len: 9,
+    ///     extensions: Some(vec![0x03, 0x00, b'S', b'E', b'Q', 0x02, 0x00, b'O', b'K']),
+    /// };
+    ///
+    /// assert_eq!(version.extension_count(), 2);
+    /// assert_eq!(version.extension(1), Some(b"OK".as_slice()));
+    /// assert_eq!(version.extension(2), None);
+    /// ```
+    pub fn extension(&self, i: usize) -> Option<&[u8]> {
+        self.extension_blobs().nth(i)
+    }
+
+    /// Walk the length-prefixed extension list, stopping at the first truncated entry.
+    fn extension_blobs(&self) -> impl Iterator<Item = &[u8]> {
+        let mut rest = self.extensions.as_deref().unwrap_or_default();
+
+        std::iter::from_fn(move || {
+            let (len, tail) = rest.split_first_chunk::<2>()?;
+            let len = u16::from_le_bytes(*len) as usize;
+
+            if tail.len() < len {
+                return None;
+            }
+
+            let (extension, tail) = tail.split_at(len);
+            rest = tail;
+
+            Some(extension)
+        })
+    }
+}
+
 #[macro_export]
 /// Send `PktVersion` over `TcpStream` to connected user
 ///
 /// ```no_run
-/// use lurk_protocol::{Protocol, PktVersion, PktType, send_version};
+/// use lurk_protocol::{PktVersion, send_version};
 /// use std::sync::Arc;
 /// use std::net::TcpStream;
 ///
 /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
-/// let version = PktVersion {
-///     packet_type: PktType::VERSION,
-///     major_rev: 2,
-///     minor_rev: 3,
-///     extensions_len: 0,
-///     extensions: None,
-/// };
+/// let version = PktVersion::new(2, 3);
 ///
 /// send_version!(stream.clone(), version)
 /// ```
@@ -68,28 +178,45 @@ impl Parser<'_> for PktVersion {
 
         packet.extend(self.major_rev.to_le_bytes());
         packet.extend(self.minor_rev.to_le_bytes());
-        packet.extend(self.extensions_len.to_le_bytes());
 
-        if let Some(extensions) = &self.extensions {
-            packet.extend(extensions);
-        }
+        let extensions = self.extensions.as_deref().unwrap_or_default();
+        let extensions_len = u16::try_from(extensions.len()).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Extension list of {} bytes does not fit a u16 length",
+                    extensions.len()
+                ),
+            )
+        })?;
+
+        packet.extend(extensions_len.to_le_bytes());
+        packet.extend(extensions);
 
         // Write the packet to the buffer
-        writer
-            .write_all(&packet)
-            .map_err(|_| std::io::Error::other("Failed to write packet to buffer"))?;
+        writer.write_all(&packet).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to write packet to buffer: {}", e))
+        })?;
 
         Ok(())
     }
 
-    fn decode(packet: Packet) -> Self {
-        Self {
+    fn decode(packet: Packet) -> Result<Self, std::io::Error> {
+        Ok(Self {
             packet_type: packet.packet_type,
-            major_rev: packet.body[0],
-            minor_rev: packet.body[1],
-            extensions_len: 0,
-            extensions: None, // Server currently does not use extensions
-        }
+            ..PktVersionRef::decode(packet.body)?.to_packet()
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for PktVersion {
+    type Error = std::io::Error;
+
+    /// Parse a complete `VERSION` packet, including its leading type byte.
+    ///
+    /// Fails if the type byte is not `PktType::VERSION`, or if `bytes` is not exactly one complete packet.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Packet::parse(bytes, PktType::VERSION).and_then(PktVersion::decode)
     }
 }
 
@@ -106,7 +233,7 @@ mod tests {
         let packet = Packet::new(type_byte, &original_bytes[1..]);
 
         // Deserialize the packet into a PktVersion
-        let message = PktVersion::decode(packet);
+        let message = PktVersion::decode(packet).expect("Decoding failed");
 
         // Assert the fields were parsed correctly
         assert_eq!(message.packet_type, PktType::VERSION);
@@ -130,7 +257,7 @@ mod tests {
         // From trace: 0e 02 03 00 00
         let body: &[u8] = &[0x02, 0x03, 0x00, 0x00];
         let packet = Packet::new(PktType::VERSION, body);
-        let ver = PktVersion::decode(packet);
+        let ver = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(ver.major_rev, 2);
         assert_eq!(ver.minor_rev, 3);
@@ -143,7 +270,7 @@ mod tests {
     fn version_max_revisions() {
         let body: &[u8] = &[0xFF, 0xFF, 0x00, 0x00];
         let packet = Packet::new(PktType::VERSION, body);
-        let ver = PktVersion::decode(packet);
+        let ver = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(ver.major_rev, 255);
         assert_eq!(ver.minor_rev, 255);
@@ -154,7 +281,7 @@ mod tests {
     fn version_zero_revisions() {
         let body: &[u8] = &[0x00, 0x00, 0x00, 0x00];
         let packet = Packet::new(PktType::VERSION, body);
-        let ver = PktVersion::decode(packet);
+        let ver = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(ver.major_rev, 0);
         assert_eq!(ver.minor_rev, 0);
@@ -184,6 +311,32 @@ mod tests {
         assert_eq!(&buffer[5..], &[0x05, 0x00, 0x41, 0x42, 0x43]);
     }
 
+    /// The length written is that of `extensions`, not a stale `extensions_len`.
+    #[test]
+    fn version_serialize_ignores_stale_len() {
+        let ver = PktVersion {
+            extensions_len: 99,
+            ..PktVersion::with_extensions(2, 3, [b"SEQ"]).unwrap()
+        };
+
+        let buffer = ver.encode_to_vec().expect("Encoding failed");
+        assert_eq!(&buffer[3..5], &[0x05, 0x00]);
+        assert_eq!(buffer.len(), 10);
+
+        let stale = PktVersion {
+            extensions_len: 4,
+            ..PktVersion::new(2, 3)
+        };
+        assert_eq!(stale.encode_to_vec().expect("Encoding failed").len(), 5);
+
+        let huge = PktVersion {
+            extensions: Some(vec![0; u16::MAX as usize + 1]),
+            ..PktVersion::new(2, 3)
+        };
+        let err = huge.encode_to_vec().expect_err("Encoding must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
     /// Serialize with no extensions and verify compact output.
     #[test]
     fn version_serialize_no_extensions() {
@@ -217,7 +370,7 @@ mod tests {
         original.write_to(&mut buffer).expect("Encoding failed");
 
         let packet = Packet::new(PktType::VERSION, &buffer[1..]);
-        let deserialized = PktVersion::decode(packet);
+        let deserialized = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(deserialized.major_rev, 10);
         assert_eq!(deserialized.minor_rev, 42);
@@ -228,28 +381,26 @@ mod tests {
     fn version_extra_trailing_bytes() {
         let body: &[u8] = &[0x02, 0x03, 0x00, 0x00, 0xFF, 0xFF, 0xFF];
         let packet = Packet::new(PktType::VERSION, body);
-        let ver = PktVersion::decode(packet);
+        let ver = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(ver.major_rev, 2);
         assert_eq!(ver.minor_rev, 3);
     }
 
-    /// Too-short body should panic during deserialization (index out of bounds).
+    /// Too-short body should fail to decode.
     #[test]
-    #[should_panic]
-    fn version_body_too_short_panics() {
+    fn version_body_too_short_fails() {
         let body: &[u8] = &[0x02]; // Only 1 byte, need at least 2
         let packet = Packet::new(PktType::VERSION, body);
-        let _ = PktVersion::decode(packet);
+        assert!(PktVersion::decode(packet).is_err());
     }
 
-    /// Empty body should panic during deserialization.
+    /// Empty body should fail to decode.
     #[test]
-    #[should_panic]
-    fn version_empty_body_panics() {
+    fn version_empty_body_fails() {
         let body: &[u8] = &[];
         let packet = Packet::new(PktType::VERSION, body);
-        let _ = PktVersion::decode(packet);
+        assert!(PktVersion::decode(packet).is_err());
     }
 
     /// All 0xFF bytes in body.
@@ -257,7 +408,7 @@ mod tests {
     fn version_all_ones_body() {
         let body: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF];
         let packet = Packet::new(PktType::VERSION, body);
-        let ver = PktVersion::decode(packet);
+        let ver = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(ver.major_rev, 255);
         assert_eq!(ver.minor_rev, 255);
@@ -268,7 +419,7 @@ mod tests {
     fn version_all_zeros_body() {
         let body: &[u8] = &[0x00, 0x00, 0x00, 0x00];
         let packet = Packet::new(PktType::VERSION, body);
-        let ver = PktVersion::decode(packet);
+        let ver = PktVersion::decode(packet).expect("Decoding failed");
 
         assert_eq!(ver.major_rev, 0);
         assert_eq!(ver.minor_rev, 0);
@@ -289,5 +440,99 @@ mod tests {
         assert_eq!(parsed["major_rev"], 2);
         assert_eq!(parsed["minor_rev"], 3);
     }
+
+    /// The extension list is kept on decode rather than dropped.
+    #[test]
+    fn version_decode_extensions() {
+        let body: &[u8] = &[
+            0x02, 0x03, 0x09, 0x00, 0x03, 0x00, b'S', b'E', b'Q', 0x02, 0x00, b'O', b'K',
+        ];
+        let ver = PktVersion::decode(Packet::new(PktType::VERSION, body)).expect("Decoding failed");
+
+        assert_eq!(ver.extensions_len, 9);
+        assert_eq!(ver.extensions.as_deref(), Some(&body[4..]));
+    }
+
+    /// A version without extensions has none to count.
+    #[test]
+    fn version_extension_count_empty() {
+        let ver = PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: 2,
+            minor_rev: 3,
+            extensions_len: 0,
+            extensions: None,
+        };
+
+        assert_eq!(ver.extension_count(), 0);
+        assert_eq!(ver.extension(0), None);
+    }
+
+    /// Zero-length extensions are counted, and a truncated trailing entry is not.
+    #[test]
+    fn version_extension_truncated() {
+        let ver = PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: 2,
+            minor_rev: 3,
+            extensions_len: 7,
+            extensions: Some(vec![0x00, 0x00, 0x01, 0x00, 0x41, 0x05, 0x00]),
+        };
+
+        assert_eq!(ver.extension_count(), 2);
+        assert_eq!(ver.extension(0), Some(&[][..]));
+        assert_eq!(ver.extension(1), Some(&b"A"[..]));
+        assert_eq!(ver.extension(2), None);
+    }
+
+    /// `new` advertises no extensions.
+    #[test]
+    fn version_new() {
+        let packet = PktVersion::new(2, 3);
+
+        assert_eq!(packet.packet_type, PktType::VERSION);
+        assert_eq!((packet.major_rev, packet.minor_rev), (2, 3));
+        assert_eq!(packet.extensions_len, 0);
+        assert!(packet.extensions.is_none());
+    }
+
+    /// `current` advertises the implemented revision and no extensions.
+    #[test]
+    fn version_current() {
+        let version = PktVersion::current();
+
+        assert_eq!(version.packet_type, PktType::VERSION);
+        assert_eq!((version.major_rev, version.minor_rev), (2, 3));
+        assert_eq!(version.extensions_len, 0);
+        assert!(version.extensions.is_none());
+    }
+
+    /// `with_extensions` keeps the given order, length-prefixes each entry, and counts the whole list.
+    #[test]
+    fn version_with_extensions_layout() {
+        let version = PktVersion::with_extensions(2, 3, [b"ZLIB".as_slice(), b"", b"SEQ"])
+            .expect("Build failed");
+
+        assert_eq!(
+            version.extensions.as_deref(),
+            Some(
+                [
+                    0x04, 0x00, b'Z', b'L', b'I', b'B', 0x00, 0x00, 0x03, 0x00, b'S', b'E', b'Q'
+                ]
+                .as_slice()
+            )
+        );
+        assert_eq!(version.extensions_len, 13);
+        assert_eq!(version.extension_count(), 3);
+
+        let empty = PktVersion::with_extensions(2, 3, Vec::<Vec<u8>>::new()).expect("Build failed");
+        assert_eq!(empty.extensions_len, 0);
+        assert!(empty.extensions.is_none());
+
+        let err = PktVersion::with_extensions(2, 3, [vec![0; 65536]])
+            .err()
+            .expect("Oversized extension must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
 }
 ////////////////////////////////////////////////////////////////////////////////
@@ -1,9 +1,11 @@
+#[cfg(feature = "pcap-table")]
 use tabled::{
     Table, Tabled,
     settings::{Remove, Style, object::Rows},
 };
 
-#[derive(Tabled, Debug, Clone)]
+#[cfg_attr(feature = "pcap-table", derive(Tabled))]
+#[derive(Debug, Clone)]
 #[doc(hidden)]
 struct PCapLine {
     address: String,
@@ -47,20 +49,23 @@ impl PCapLine {
 
 #[derive(Debug, Clone)]
 /// A utility struct for formatting and displaying a vector of bytes.
+///
+/// Each line shows the offset, up to 16 bytes in hex and their printable ASCII. With the `pcap-table` feature the
+/// columns are laid out by `tabled`; without it they are padded to fixed widths.
 pub struct PCap;
 
 impl PCap {
-    /// Builds a formatted string representation of the provided byte vector.
+    /// Builds a formatted string representation of the provided bytes.
     ///
     /// ```no_run
     /// use lurk_protocol::pcap::PCap;
     ///
     /// let data = vec![0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x2c, 0x20, 0x57, 0x6f, 0x72, 0x6c, 0x64, 0x21]; // "Hello, World!"
-    /// let formatted = PCap::build(data);
+    /// let formatted = PCap::build(&data);
     ///
     /// println!("{}", formatted);
     /// ```
-    pub fn build(data: Vec<u8>) -> String {
+    pub fn build(data: &[u8]) -> String {
         let mut lines = Vec::new();
         let chunks = data.chunks(16);
 
@@ -71,11 +76,25 @@ impl PCap {
             lines.push(line);
         });
 
+        Self::render(lines)
+    }
+
+    #[cfg(feature = "pcap-table")]
+    fn render(lines: Vec<PCapLine>) -> String {
         Table::new(lines)
             .with(Remove::row(Rows::first()))
             .with(Style::blank())
             .to_string()
     }
+
+    #[cfg(not(feature = "pcap-table"))]
+    fn render(lines: Vec<PCapLine>) -> String {
+        lines
+            .iter()
+            .map(|line| format!(" {}  {:<48}  {}", line.address, line.hex, line.ascii))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 #[cfg(test)]
@@ -85,7 +104,7 @@ mod tests {
     #[test]
     fn build_nonempty_for_nonempty_data() {
         let data = vec![0x48, 0x65, 0x6c, 0x6c, 0x6f]; // "Hello"
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         assert!(
             !result.is_empty(),
             "PCap::build must return non-empty string"
@@ -95,7 +114,7 @@ mod tests {
     #[test]
     fn build_contains_hex_bytes() {
         let data = vec![0xDE, 0xAD];
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         // Result must contain the hex representation of the bytes
         assert!(
             result.contains("de"),
@@ -112,7 +131,7 @@ mod tests {
     #[test]
     fn build_empty_data() {
         let data = vec![];
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         // Empty input should still produce a string (possibly empty table)
         // Just verify it doesn't panic and is a valid string
         let _ = result;
@@ -121,7 +140,7 @@ mod tests {
     #[test]
     fn build_contains_ascii_printable() {
         let data = vec![0x41, 0x42, 0x43]; // "ABC"
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         assert!(
             result.contains("ABC"),
             "output should contain ASCII 'ABC': {}",
@@ -132,7 +151,7 @@ mod tests {
     #[test]
     fn build_non_printable_shown_as_dot() {
         let data = vec![0x00, 0x01, 0x02];
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         assert!(
             result.contains("..."),
             "non-printable bytes should be shown as dots: {}",
@@ -143,7 +162,7 @@ mod tests {
     #[test]
     fn build_address_starts_at_zero() {
         let data = vec![0xFF];
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         assert!(
             result.contains("00000000"),
             "address should start at 00000000: {}",
@@ -155,11 +174,32 @@ mod tests {
     fn build_multiple_lines_for_large_data() {
         // 17 bytes = 2 chunks of 16, so 2 address lines
         let data = vec![0x41; 17];
-        let result = PCap::build(data);
+        let result = PCap::build(&data);
         assert!(result.contains("00000000"), "should have first address");
         assert!(
             result.contains("00000010"),
             "should have second address for offset 16"
         );
     }
+
+    #[test]
+    fn build_one_line_per_chunk_with_aligned_columns() {
+        let mut data = vec![0x41; 33];
+        data.extend_from_slice(b"xyz");
+        let result = PCap::build(&data);
+        let lines: Vec<_> = result.lines().collect();
+
+        assert_eq!(
+            lines.len(),
+            3,
+            "36 bytes should take three lines: {}",
+            result
+        );
+        assert_eq!(
+            lines[0].find("AAAA"),
+            lines[2].find("Axyz"),
+            "the ASCII column should line up: {}",
+            result
+        );
+    }
 }
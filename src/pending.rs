@@ -0,0 +1,95 @@
+use crate::{CharacterFlags, LurkError, PktCharacter};
+
+/// A server-side holder for the character a client has proposed, between its `CHARACTER` being accepted and its `START`.
+///
+/// The protocol has the client send `CHARACTER`, the server validate it and reply with `ACCEPT`, and the client
+/// then send `START` to enter the game. A rejected character may be re-sent, so a later [`accept`](PendingCharacter::accept)
+/// replaces the earlier one.
+///
+/// ```
+/// use lurk_protocol::{LurkError, PendingCharacter, PktCharacter};
+///
+/// let mut pending = PendingCharacter::new();
+/// assert_eq!(PendingCharacter::new().start().err(), Some(LurkError::NOTREADY));
+///
+/// pending.accept(PktCharacter::new("Hero", "Brave."));
+///
+/// let player = pending.start().unwrap();
+/// assert!(player.flags.is_alive() && player.flags.is_started());
+/// ```
+#[derive(Default, Clone)]
+pub struct PendingCharacter {
+    character: Option<PktCharacter>,
+}
+
+impl PendingCharacter {
+    /// Create a holder with no accepted character.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a character the server has validated and answered with `ACCEPT`, replacing any earlier one.
+    pub fn accept(&mut self, character: PktCharacter) {
+        self.character = Some(character);
+    }
+
+    /// The accepted character, if any.
+    pub fn get(&self) -> Option<&PktCharacter> {
+        self.character.as_ref()
+    }
+
+    /// Whether a character has been accepted, i.e. whether a `START` can be honoured.
+    pub fn is_accepted(&self) -> bool {
+        self.character.is_some()
+    }
+
+    /// Handle the client's `START`, returning the accepted character ready to spawn.
+    ///
+    /// The character's flags are set to [`CharacterFlags::alive`] with [`CharacterFlags::STARTED`]; every other
+    /// field is kept as accepted. Fails with [`LurkError::NOTREADY`] if no character was accepted first.
+    pub fn start(self) -> Result<PktCharacter, LurkError> {
+        let mut character = self.character.ok_or(LurkError::NOTREADY)?;
+        character.flags = CharacterFlags::alive() | CharacterFlags::STARTED;
+
+        Ok(character)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `START` before any `CHARACTER` is reported as `NOTREADY`.
+    #[test]
+    fn pending_start_without_character() {
+        let pending = PendingCharacter::new();
+
+        assert!(!pending.is_accepted());
+        assert_eq!(pending.start().err(), Some(LurkError::NOTREADY));
+    }
+
+    /// The latest accepted character is the one started, with its stats intact.
+    #[test]
+    fn pending_start_latest_character() {
+        let mut pending = PendingCharacter::new();
+        pending.accept(PktCharacter {
+            attack: 10,
+            ..PktCharacter::new("First", "")
+        });
+        pending.accept(PktCharacter {
+            attack: 20,
+            flags: CharacterFlags::MONSTER,
+            ..PktCharacter::new("Second", "")
+        });
+
+        assert_eq!(pending.get().map(|c| c.name.as_ref()), Some("Second"));
+
+        let character = pending.start().expect("Start failed");
+        assert_eq!(&*character.name, "Second");
+        assert_eq!(character.attack, 20);
+        assert_eq!(
+            character.flags,
+            CharacterFlags::alive() | CharacterFlags::STARTED
+        );
+    }
+}
@@ -116,6 +116,59 @@ impl From<&[u8; 1]> for PktType {
     }
 }
 
+impl PktType {
+    /// Converts a type byte into its `PktType`, failing on bytes no packet type uses.
+    ///
+    /// Unlike `From<u8>`, which maps every unknown byte to [`PktType::DEFAULT`], only `0` becomes `DEFAULT` here;
+    /// `15..=255` fail with [`ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported) naming the byte. (A
+    /// `TryFrom<u8>` impl is not possible alongside `From<u8>`.)
+    ///
+    /// ```rust
+    /// use lurk_protocol::pkt_type::PktType;
+    ///
+    /// assert_eq!(PktType::try_from_byte(3).unwrap(), PktType::FIGHT);
+    /// assert_eq!(PktType::try_from_byte(0).unwrap(), PktType::DEFAULT);
+    /// assert!(PktType::try_from_byte(15).is_err());
+    /// ```
+    pub fn try_from_byte(value: u8) -> Result<Self, std::io::Error> {
+        match PktType::from(value) {
+            PktType::DEFAULT if value != 0 => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Invalid packet type {}", value),
+            )),
+            packet_type => Ok(packet_type),
+        }
+    }
+
+    /// A short, lowercase label for the packet type, e.g. `"change_room"`, for metrics and other hot paths
+    /// where formatting with `Display` would be too costly. [`PktType::DEFAULT`] is labelled `"unknown"`.
+    ///
+    /// ```rust
+    /// use lurk_protocol::pkt_type::PktType;
+    ///
+    /// assert_eq!(PktType::PVPFIGHT.as_str(), "pvp_fight");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PktType::DEFAULT => "unknown",
+            PktType::MESSAGE => "message",
+            PktType::CHANGEROOM => "change_room",
+            PktType::FIGHT => "fight",
+            PktType::PVPFIGHT => "pvp_fight",
+            PktType::LOOT => "loot",
+            PktType::START => "start",
+            PktType::ERROR => "error",
+            PktType::ACCEPT => "accept",
+            PktType::ROOM => "room",
+            PktType::CHARACTER => "character",
+            PktType::GAME => "game",
+            PktType::LEAVE => "leave",
+            PktType::CONNECTION => "connection",
+            PktType::VERSION => "version",
+        }
+    }
+}
+
 impl std::fmt::Display for PktType {
     /// Formats the `PktType` enum variant as a human-readable string.
     /// # Example
@@ -334,6 +387,23 @@ mod tests {
         assert_eq!(PktType::from(&[255u8]), PktType::DEFAULT);
     }
 
+    // ── PktType::try_from_byte ───────────────────────────────────────
+    #[test]
+    fn try_from_byte_known_and_zero() {
+        for i in 0u8..=14u8 {
+            assert_eq!(PktType::try_from_byte(i).ok(), Some(PktType::from(i)));
+        }
+    }
+
+    #[test]
+    fn try_from_byte_unknown_names_value() {
+        for i in [15u8, 99, 255] {
+            let err = PktType::try_from_byte(i).expect_err("Unknown byte must fail");
+            assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+            assert!(err.to_string().contains(&i.to_string()), "{}", err);
+        }
+    }
+
     // ── roundtrip u8 ↔ PktType ──────────────────────────────────────
     #[test]
     fn roundtrip_u8_all() {
@@ -374,4 +444,18 @@ mod tests {
         assert_eq!(format!("{}", PktType::CONNECTION), "Connection");
         assert_eq!(format!("{}", PktType::VERSION), "Version");
     }
+
+    // ── as_str ───────────────────────────────────────────────────────
+    #[test]
+    fn as_str_labels_are_unique() {
+        let labels: std::collections::HashSet<_> =
+            (0..=14u8).map(|b| PktType::from(b).as_str()).collect();
+        assert_eq!(labels.len(), 15);
+    }
+
+    #[test]
+    fn as_str_snake_case() {
+        assert_eq!(PktType::CHANGEROOM.as_str(), "change_room");
+        assert_eq!(PktType::DEFAULT.as_str(), "unknown");
+    }
 }
@@ -1,20 +1,31 @@
+use std::any::Any;
 use std::io::Read as _;
+use std::io::Write as _;
 use std::io::{Error, ErrorKind};
 use std::net::TcpStream;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(feature = "tracing")]
-use tracing::info;
+use tracing::{info, warn};
 
+use crate::packet::partial_read;
 use crate::{
-    Packet, Parser, PktAccept, PktChangeRoom, PktCharacter, PktConnection, PktError, PktFight,
-    PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight, PktRoom, PktStart, PktType, PktVersion,
+    BufferPool, LurkError, NamePolicy, Packet, Parser, PktAccept, PktChangeRoom, PktCharacter,
+    PktConnection, PktError, PktFight, PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight,
+    PktRoom, PktStart, PktType, PktTypeRegistry, PktVersion,
 };
 
 /// Represents all possible protocol packets exchanged between the client and server.
 ///
 /// Each variant wraps the deserialized packet data as plain Rust structs,
 /// providing a pure wire-format translation layer with no connection state.
+///
+/// Packets are cheap to clone, e.g. to fan one received packet out to several workers. New variants may be
+/// added in minor releases, so matches need a wildcard arm.
+#[derive(Clone)]
+#[non_exhaustive]
 pub enum Protocol {
     /// Packet containing a message sent between client and server.
     Message(PktMessage),
@@ -74,6 +85,72 @@ pub enum Protocol {
     /// );
     /// ```
     Version(PktVersion),
+    /// Packet of a custom type registered in a [`PktTypeRegistry`], with its type byte and parsed body.
+    ///
+    /// Only [`Protocol::recv_with_registry`] produces this variant. Its [`packet_type`](Protocol::packet_type) is
+    /// [`PktType::DEFAULT`], and it cannot be serialized. The body is shared, so cloning the packet does not copy it.
+    Custom(u8, Arc<dyn Any + Send + Sync>),
+}
+
+/// What [`Protocol::recv_with_config`] does when a packet starts with an unknown type byte.
+///
+/// An unknown packet cannot be sized, so there is no way to step over it exactly; the stream is out of sync
+/// from that point unless the peer sent a stray byte.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownTypePolicy {
+    #[default]
+    /// Consume the byte and return an [`ErrorKind::Unsupported`] error, like [`Protocol::recv`].
+    Error,
+    /// Discard bytes one at a time until a known type byte is found, then receive that packet.
+    /// Tolerant of stray bytes; intended for development servers.
+    Skip,
+    /// Shut down the stream and return an [`ErrorKind::ConnectionAborted`] error.
+    Close,
+}
+
+/// The error payload of [`Protocol::from_bytes`] when the buffer ends partway through a valid packet.
+///
+/// The buffer is not corrupt; the rest of the packet has not arrived yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Incomplete {
+    /// How many more bytes are needed at least. Once a packet's length field has arrived this is exact;
+    /// before that it only covers the fixed part of the packet.
+    pub needed: usize,
+}
+
+impl Incomplete {
+    /// The `Incomplete` wrapped by an error from [`Protocol::from_bytes`], if it is one.
+    pub fn from_error(error: &Error) -> Option<&Incomplete> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for Incomplete {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Incomplete packet, {} more bytes needed", self.needed)
+    }
+}
+
+impl std::error::Error for Incomplete {}
+
+/// Options for [`Protocol::recv_with_config`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvConfig {
+    /// How to handle an unknown packet type byte.
+    pub on_unknown_type: UnknownTypePolicy,
+    /// The largest packet accepted, in total bytes including the type byte, or `None` for no limit.
+    ///
+    /// The size is worked out from the fixed part and the declared length before the variable part is read or
+    /// allocated. A larger packet fails with [`ErrorKind::InvalidData`] and leaves its body unread, so the
+    /// connection should be closed.
+    pub max_packet_bytes: Option<usize>,
+}
+
+/// Options for sending packets through a [`Connection`](crate::Connection).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendConfig {
+    /// How to handle names that are too long for their field.
+    pub name_policy: NamePolicy,
 }
 
 impl std::fmt::Display for Protocol {
@@ -103,11 +180,218 @@ impl std::fmt::Display for Protocol {
             Protocol::Leave(leave) => write!(f, "{}", leave),
             Protocol::Connection(connection) => write!(f, "{}", connection),
             Protocol::Version(version) => write!(f, "{}", version),
+            Protocol::Custom(type_byte, _) => write!(f, "Custom packet of type {}", type_byte),
         }
     }
 }
 
+/// Generate a borrowing `as_*` and a consuming `into_*` accessor for each `Protocol` variant, and a `From` impl wrapping the packet.
+macro_rules! protocol_accessors {
+    ($($variant:ident => $pkt:ty, $as_fn:ident, $into_fn:ident;)*) => {
+        impl Protocol {
+            $(
+                #[doc = concat!("Borrow the inner [`", stringify!($pkt), "`] if this is a `Protocol::", stringify!($variant), "`.")]
+                pub fn $as_fn(&self) -> Option<&$pkt> {
+                    match self {
+                        Protocol::$variant(pkt) => Some(pkt),
+                        _ => None,
+                    }
+                }
+
+                #[doc = concat!("Take the inner [`", stringify!($pkt), "`] if this is a `Protocol::", stringify!($variant), "`,")]
+                /// otherwise hand back the unexpected packet unchanged.
+                pub fn $into_fn(self) -> Result<$pkt, Protocol> {
+                    match self {
+                        Protocol::$variant(pkt) => Ok(pkt),
+                        other => Err(other),
+                    }
+                }
+            )*
+        }
+
+        $(
+            impl From<$pkt> for Protocol {
+                #[doc = concat!("Wrap a [`", stringify!($pkt), "`] as a `Protocol::", stringify!($variant), "`.")]
+                fn from(pkt: $pkt) -> Self {
+                    Protocol::$variant(pkt)
+                }
+            }
+        )*
+    };
+}
+
+protocol_accessors! {
+    Message => PktMessage, as_message, into_message;
+    ChangeRoom => PktChangeRoom, as_change_room, into_change_room;
+    Fight => PktFight, as_fight, into_fight;
+    PVPFight => PktPVPFight, as_pvp_fight, into_pvp_fight;
+    Loot => PktLoot, as_loot, into_loot;
+    Start => PktStart, as_start, into_start;
+    Error => PktError, as_error, into_error;
+    Accept => PktAccept, as_accept, into_accept;
+    Room => PktRoom, as_room, into_room;
+    Character => PktCharacter, as_character, into_character;
+    Game => PktGame, as_game, into_game;
+    Leave => PktLeave, as_leave, into_leave;
+    Connection => PktConnection, as_connection, into_connection;
+    Version => PktVersion, as_version, into_version;
+}
+
 impl Protocol {
+    /// The packet type of the wrapped packet.
+    ///
+    /// ```
+    /// use lurk_protocol::{Protocol, PktFight, PktType};
+    ///
+    /// assert_eq!(Protocol::Fight(PktFight::default()).packet_type(), PktType::FIGHT);
+    /// ```
+    pub fn packet_type(&self) -> PktType {
+        match self {
+            Protocol::Message(_) => PktType::MESSAGE,
+            Protocol::ChangeRoom(_) => PktType::CHANGEROOM,
+            Protocol::Fight(_) => PktType::FIGHT,
+            Protocol::PVPFight(_) => PktType::PVPFIGHT,
+            Protocol::Loot(_) => PktType::LOOT,
+            Protocol::Start(_) => PktType::START,
+            Protocol::Error(_) => PktType::ERROR,
+            Protocol::Accept(_) => PktType::ACCEPT,
+            Protocol::Room(_) => PktType::ROOM,
+            Protocol::Character(_) => PktType::CHARACTER,
+            Protocol::Game(_) => PktType::GAME,
+            Protocol::Leave(_) => PktType::LEAVE,
+            Protocol::Connection(_) => PktType::CONNECTION,
+            Protocol::Version(_) => PktType::VERSION,
+            Protocol::Custom(..) => PktType::DEFAULT,
+        }
+    }
+
+    /// A short, static label for the packet, e.g. `"change_room"`, for use as a metrics label. See [`PktType::as_str`].
+    ///
+    /// ```
+    /// use lurk_protocol::{PktChangeRoom, Protocol};
+    ///
+    /// assert_eq!(Protocol::ChangeRoom(PktChangeRoom::new(1)).label(), "change_room");
+    /// ```
+    pub fn label(&self) -> &'static str {
+        self.packet_type().as_str()
+    }
+
+    /// Serialize the wrapped packet to any writer, without consuming it.
+    ///
+    /// This is the same as calling [`Parser::write_to`] on the inner packet, so a list of mixed packets, such as
+    /// those built by [`server_events`](crate::server_events), can be written to any transport that implements [`Write`](std::io::Write).
+    ///
+    /// ```
+    /// use lurk_protocol::{PktLeave, Protocol};
+    ///
+    /// let mut buffer = Vec::new();
+    /// Protocol::Leave(PktLeave::new()).write_to(&mut buffer).unwrap();
+    ///
+    /// assert_eq!(buffer, [12]);
+    /// ```
+    pub fn write_to<W: std::io::Write>(&self, writer: &mut W) -> Result<(), std::io::Error> {
+        self.write_with_policy(writer, NamePolicy::default())
+    }
+
+    /// Serialize the wrapped packet like [`Protocol::write_to`], handling overlong names according to `policy`.
+    pub fn write_with_policy<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        policy: NamePolicy,
+    ) -> Result<(), std::io::Error> {
+        match self {
+            Protocol::Message(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::ChangeRoom(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Fight(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::PVPFight(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Loot(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Start(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Error(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Accept(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Room(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Character(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Game(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Leave(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Connection(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Version(pkt) => pkt.write_with_policy(writer, policy),
+            Protocol::Custom(..) => Err(Error::new(
+                ErrorKind::Unsupported,
+                "Custom packets cannot be serialized",
+            )),
+        }
+    }
+
+    /// Serialize the wrapped packet into a new buffer.
+    pub fn encode_to_vec(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Write the packet to another stream, e.g. to pass it on from a proxy.
+    ///
+    /// The packet is re-serialized from its fields, so the bytes sent are the canonical encoding of what was
+    /// decoded. A [`Protocol::Custom`] packet cannot be forwarded and fails with [`ErrorKind::Unsupported`].
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Protocol;
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let client = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    /// let server = Arc::new(TcpStream::connect("127.0.0.1:5000").unwrap());
+    ///
+    /// while let Ok(packet) = Protocol::recv(&client) {
+    ///     println!("Client -> server: {}", packet);
+    ///     packet.forward(&server).unwrap();
+    /// }
+    /// ```
+    pub fn forward(&self, to: &Arc<TcpStream>) -> Result<(), std::io::Error> {
+        let bytes = self.encode_to_vec()?;
+
+        crate::write_encoded(to, self, &bytes)
+    }
+
+    /// Write a burst of packets with a single `write_all`, so they arrive contiguously even when other threads
+    /// write to the same stream, e.g. the `ROOM`, `CONNECTION`, and `CHARACTER` packets after a room change.
+    ///
+    /// Every packet is serialized before anything is written. If one fails, such as a [`Protocol::Custom`], its
+    /// error is returned and nothing is sent. A peer that has hung up is reported as [`ErrorKind::NotConnected`].
+    /// To build a burst from individual packet types instead, see [`Batch`](crate::Batch).
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{PktConnection, PktRoom, Protocol};
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// Protocol::send_all(
+    ///     &stream,
+    ///     &[
+    ///         Protocol::Room(PktRoom::new(1, "Hall", "A wide hall.")),
+    ///         Protocol::Connection(PktConnection::new(2, "Kitchen", "It smells of bread.")),
+    ///     ],
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn send_all(stream: &Arc<TcpStream>, packets: &[Protocol]) -> Result<(), std::io::Error> {
+        let mut bytes = Vec::new();
+
+        for packet in packets {
+            packet.write_to(&mut bytes)?;
+        }
+
+        #[cfg(feature = "tracing")]
+        info!("Sending burst of {} packets", packets.len());
+
+        stream
+            .as_ref()
+            .write_all(&bytes)
+            .map_err(crate::disconnected)
+    }
+
     /// Receive one packet from the connected TcpStream
     ///
     /// ```no_run
@@ -137,99 +421,641 @@ impl Protocol {
     /// }
     /// ```
     pub fn recv(stream: &Arc<TcpStream>) -> Result<Protocol, std::io::Error> {
+        let bytes = Protocol::read_raw(stream)?;
+
+        #[cfg(feature = "tracing")]
+        info!("Read packet type: {}", PktType::from(bytes[0]));
+
+        Protocol::decode_bytes(&bytes)
+    }
+
+    /// Receive one packet like [`Protocol::recv`], handling an unknown type byte and enforcing the size limit as configured.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Protocol, RecvConfig, UnknownTypePolicy};
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    /// let config = RecvConfig {
+    ///     on_unknown_type: UnknownTypePolicy::Close,
+    ///     max_packet_bytes: Some(4096),
+    /// };
+    ///
+    /// while let Ok(packet) = Protocol::recv_with_config(&stream, &config) {
+    ///     println!("{}", packet);
+    /// }
+    /// ```
+    pub fn recv_with_config(
+        stream: &Arc<TcpStream>,
+        config: &RecvConfig,
+    ) -> Result<Protocol, std::io::Error> {
+        loop {
+            match Protocol::peek_type(stream) {
+                Err(e) if e.kind() == ErrorKind::Unsupported => {}
+                Err(e) => return Err(e),
+                Ok(_) => {
+                    let Some(max_len) = config.max_packet_bytes else {
+                        return Protocol::recv(stream);
+                    };
+
+                    let mut bytes = Vec::new();
+                    Protocol::read_raw_capped(stream.as_ref(), &mut bytes, max_len)?;
+
+                    return Protocol::decode_bytes(&bytes);
+                }
+            }
+
+            match config.on_unknown_type {
+                UnknownTypePolicy::Error => return Protocol::recv(stream),
+                UnknownTypePolicy::Skip => {
+                    let mut buffer = [0; 1];
+                    stream.as_ref().read_exact(&mut buffer)?;
+
+                    #[cfg(feature = "tracing")]
+                    warn!("Skipped unknown packet type byte: {}", buffer[0]);
+                }
+                UnknownTypePolicy::Close => {
+                    stream.shutdown(std::net::Shutdown::Both)?;
+
+                    return Err(Error::new(
+                        ErrorKind::ConnectionAborted,
+                        "Closed connection after an unknown packet type",
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Receive one packet like [`Protocol::recv`], decoding the custom types in `registry` as [`Protocol::Custom`].
+    ///
+    /// Built-in types are received as usual, and an unknown byte that is not registered fails with
+    /// [`ErrorKind::Unsupported`]. See [`PktTypeRegistry`] for an example.
+    pub fn recv_with_registry(
+        stream: &Arc<TcpStream>,
+        registry: &PktTypeRegistry,
+    ) -> Result<Protocol, std::io::Error> {
         let mut buffer = [0; 1];
+
+        if stream.peek(&mut buffer)? == 0 {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Peer closed the connection",
+            ));
+        }
+
+        if Protocol::known_type(buffer[0]).is_ok() || !registry.contains(buffer[0]) {
+            return Protocol::recv(stream);
+        }
+
         stream.as_ref().read_exact(&mut buffer)?;
-        let packet_type = PktType::from(&buffer);
 
-        #[cfg(feature = "tracing")]
-        info!("Read packet type: {}", packet_type);
+        Ok(Protocol::Custom(
+            buffer[0],
+            registry.read(stream, buffer[0])?,
+        ))
+    }
+
+    /// Receive one packet like [`Protocol::recv`], giving up once no data has arrived for `timeout`.
+    ///
+    /// The timeout applies to each read, so a packet trickling in keeps the receive alive. Timing out before the
+    /// packet starts fails with [`ErrorKind::TimedOut`] and leaves the stream ready for the next packet. Timing out
+    /// partway through also fails with [`ErrorKind::TimedOut`], never with a partial packet, but the rest of the
+    /// packet is still in flight, so the connection should be closed.
+    ///
+    /// The stream's previous read timeout is restored afterwards, so other users of the shared stream are not
+    /// affected. Fails if `timeout` is zero.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Protocol;
+    /// use std::io::ErrorKind;
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// match Protocol::recv_timeout(&stream, Duration::from_secs(30)) {
+    ///     Ok(packet) => println!("{}", packet),
+    ///     Err(e) if e.kind() == ErrorKind::TimedOut => println!("Peer went quiet"),
+    ///     Err(e) => eprintln!("Error receiving packet: {}", e),
+    /// }
+    /// ```
+    pub fn recv_timeout(
+        stream: &Arc<TcpStream>,
+        timeout: Duration,
+    ) -> Result<Protocol, std::io::Error> {
+        let previous = stream.read_timeout()?;
+        stream.set_read_timeout(Some(timeout))?;
+
+        let read = Protocol::read_raw(stream);
+        let restored = stream.set_read_timeout(previous);
+
+        let bytes = read.map_err(|e| match e.kind() {
+            ErrorKind::WouldBlock => {
+                Error::new(ErrorKind::TimedOut, "Timed out waiting for a packet")
+            }
+            _ => e,
+        })?;
+        restored?;
+
+        Protocol::decode_bytes(&bytes)
+    }
+
+    /// Look at the type of the next packet without consuming it.
+    ///
+    /// The type byte is left in the socket's receive buffer via [`TcpStream::peek`], so a following
+    /// [`Protocol::recv`] still reads the whole packet, exactly once. Blocks until at least one byte arrives.
+    /// Only the type byte is peeked; nothing about the body is checked.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Protocol, PktType};
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// if Protocol::peek_type(&stream).unwrap() == PktType::LEAVE {
+    ///     // Tidy up before the LEAVE is processed
+    /// }
+    ///
+    /// let packet = Protocol::recv(&stream).unwrap();
+    /// ```
+    pub fn peek_type(stream: &Arc<TcpStream>) -> Result<PktType, std::io::Error> {
+        let mut buffer = [0; 1];
+
+        if stream.peek(&mut buffer)? == 0 {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Peer closed the connection",
+            ));
+        }
+
+        Protocol::known_type(buffer[0])
+    }
+
+    /// Read and discard exactly one packet, returning its type.
+    ///
+    /// The body is sized from the type byte and any length field, so the stream stays in sync without the packet
+    /// being decoded. Useful for politely ignoring packets a server does not support.
+    ///
+    /// An unknown type byte cannot be sized, so it fails with [`ErrorKind::Unsupported`]. The byte is peeked rather
+    /// than read, so it is still the next byte on the stream and the caller can choose how to recover, e.g. with
+    /// [`UnknownTypePolicy`].
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{Protocol, PktType};
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// if Protocol::peek_type(&stream).unwrap() == PktType::LOOT {
+    ///     Protocol::skip(&stream).unwrap();
+    /// }
+    /// ```
+    pub fn skip(stream: &Arc<TcpStream>) -> Result<PktType, std::io::Error> {
+        Protocol::peek_type(stream)?;
+
+        let bytes = Protocol::read_raw(stream)?;
+
+        Protocol::known_type(bytes[0])
+    }
+
+    /// Receive one packet like [`Protocol::recv`], reading it into a buffer borrowed from `pool`.
+    ///
+    /// The buffer goes back to the pool once the packet is decoded, so a server sharing one pool across all of its
+    /// connections reuses a handful of allocations instead of making new ones for every packet. The returned
+    /// packet owns its data and does not borrow from the pool.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{BufferPool, Protocol};
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let pool = Arc::new(BufferPool::new(64));
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// while let Ok(packet) = Protocol::recv_pooled(&stream, &pool) {
+    ///     println!("{}", packet);
+    /// }
+    /// ```
+    pub fn recv_pooled(
+        stream: &Arc<TcpStream>,
+        pool: &BufferPool,
+    ) -> Result<Protocol, std::io::Error> {
+        let mut bytes = pool.take();
+        let result = Protocol::read_raw_into(stream, &mut bytes)
+            .and_then(|_| Protocol::decode_bytes(&bytes));
+
+        pool.give(bytes);
+        result
+    }
+
+    /// Send an `ERROR` explaining a fatal protocol violation, then shut down the writing side of the stream.
+    ///
+    /// The peer receives the error followed by end of stream. The read side is left open so any packets already in
+    /// flight can still be drained; drop the stream to close it completely. If sending fails, that error is
+    /// returned and the stream is still shut down.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::{LurkError, Protocol};
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// Protocol::reject(&stream, LurkError::OTHER, "Unsupported protocol version.").unwrap();
+    /// ```
+    pub fn reject(
+        stream: &Arc<TcpStream>,
+        error: LurkError,
+        message: &str,
+    ) -> Result<(), std::io::Error> {
+        let sent = crate::send_to(stream, &PktError::new(error, message));
+        let shutdown = stream.shutdown(std::net::Shutdown::Write);
+
+        sent.and(shutdown)
+    }
+
+    /// Receive one packet like [`Protocol::recv`], converting a panic while parsing into an error.
+    ///
+    /// This is a safety net so that a bug in a packet decoder cannot take down a server thread; it is not a
+    /// substitute for decoders that validate their input. The panic message is still reported by the panic hook,
+    /// and nothing is caught when the crate is built with `panic = "abort"`.
+    ///
+    /// After a caught panic the stream position is unknown, so the connection should be closed.
+    ///
+    /// ```no_run
+    /// use lurk_protocol::Protocol;
+    /// use std::net::TcpStream;
+    /// use std::sync::Arc;
+    ///
+    /// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+    ///
+    /// while let Ok(packet) = Protocol::recv_safe(&stream) {
+    ///     println!("{}", packet);
+    /// }
+    /// ```
+    pub fn recv_safe(stream: &Arc<TcpStream>) -> Result<Protocol, std::io::Error> {
+        Protocol::catch_parse_panic(|| Protocol::recv(stream))
+    }
+
+    /// Run `parse`, converting a panic into an [`ErrorKind::InvalidData`] error for [`Protocol::recv_safe`].
+    fn catch_parse_panic(
+        parse: impl FnOnce() -> Result<Protocol, std::io::Error>,
+    ) -> Result<Protocol, std::io::Error> {
+        std::panic::catch_unwind(AssertUnwindSafe(parse)).unwrap_or_else(|panic| {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Panicked while parsing packet: {}", reason),
+            ))
+        })
+    }
+
+    /// Parse the packet at the start of `bytes`, returning it with the number of bytes it used.
+    ///
+    /// This is for callers that reassemble the stream themselves, e.g. from non-blocking reads:
+    ///
+    /// - If `bytes` holds the start of a valid packet but not all of it, the error has kind
+    ///   [`ErrorKind::UnexpectedEof`] and wraps an [`Incomplete`] giving the bytes still needed. Read more and try again.
+    /// - If the type byte is unknown, the packet can never be parsed and the error has kind [`ErrorKind::Unsupported`].
+    ///   The stream is corrupt from that point.
+    /// - If the length field describes a packet too long to fit in `usize`, which can only happen where `usize` is
+    ///   16 bits, the packet can never be parsed either and the error has kind [`ErrorKind::InvalidData`].
+    ///
+    /// ```
+    /// use lurk_protocol::{Incomplete, Parser, PktLoot, Protocol};
+    ///
+    /// let bytes = PktLoot::new("Goblin").encode_to_vec().unwrap();
+    ///
+    /// let err = Protocol::from_bytes(&bytes[..10]).err().unwrap();
+    /// assert_eq!(Incomplete::from_error(&err).map(|i| i.needed), Some(23));
+    ///
+    /// let (packet, used) = Protocol::from_bytes(&bytes).unwrap();
+    /// assert!(packet.as_loot().is_some());
+    /// assert_eq!(used, 33);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Protocol, usize), std::io::Error> {
+        let len = Protocol::frame_len(bytes)?;
+
+        let packet = Protocol::decode_bytes(&bytes[..len])?;
 
-        match packet_type {
-            PktType::MESSAGE => {
-                let mut buffer = vec![0; 66];
+        Ok((packet, len))
+    }
+
+    /// The length of the complete packet at the start of `bytes`, type byte included.
+    ///
+    /// Fails as [`Protocol::from_bytes`] does for a partial packet or an unknown type byte.
+    pub(crate) fn frame_len(bytes: &[u8]) -> Result<usize, Error> {
+        let incomplete =
+            |needed: usize| Error::new(ErrorKind::UnexpectedEof, Incomplete { needed });
+
+        let Some(&type_byte) = bytes.first() else {
+            return Err(incomplete(1));
+        };
 
-                let pkt = Packet::read_extended(stream, packet_type, &mut buffer, (0, 1))?;
+        let (fixed_len, extended) = Protocol::layout_of(type_byte)?;
+
+        if bytes.len() < 1 + fixed_len {
+            return Err(incomplete(1 + fixed_len - bytes.len()));
+        }
+
+        let len = match extended {
+            Some((lo, hi)) => Protocol::extended_len(
+                fixed_len,
+                u16::from_le_bytes([bytes[1 + lo], bytes[1 + hi]]),
+            )?,
+            None => 1 + fixed_len,
+        };
+
+        if bytes.len() < len {
+            return Err(incomplete(len - bytes.len()));
+        }
+
+        Ok(len)
+    }
+
+    /// Parse every complete packet at the start of `bytes`, returning them with the number of bytes used.
+    ///
+    /// Parsing stops at a trailing partial packet, which should be kept and completed by the next read. A packet
+    /// that can never be parsed fails as it does for [`Protocol::from_bytes`].
+    ///
+    /// ```
+    /// use lurk_protocol::{Parser, PktFight, PktLeave, Protocol};
+    ///
+    /// let mut buffer = PktFight::new().encode_to_vec().unwrap();
+    /// buffer.extend(PktLeave::new().encode_to_vec().unwrap());
+    /// buffer.push(1); // The first byte of a MESSAGE
+    ///
+    /// let (packets, used) = Protocol::parse_all(&buffer).unwrap();
+    /// assert_eq!(packets.len(), 2);
+    /// buffer.drain(..used);
+    /// assert_eq!(buffer, [1]);
+    /// ```
+    pub fn parse_all(bytes: &[u8]) -> Result<(Vec<Protocol>, usize), std::io::Error> {
+        let mut packets = Vec::new();
+        let mut used = 0;
 
-                Ok(Protocol::Message(PktMessage::decode(pkt)))
+        loop {
+            match Protocol::from_bytes(&bytes[used..]) {
+                Ok((packet, len)) => {
+                    packets.push(packet);
+                    used += len;
+                }
+                Err(e) if Incomplete::from_error(&e).is_some() => return Ok((packets, used)),
+                Err(e) => return Err(e),
             }
-            PktType::CHANGEROOM => {
-                let mut buffer = vec![0; 2];
+        }
+    }
+
+    /// Receive one packet from any reader, such as a `BufReader`, a Unix socket, a TLS stream, or an in-memory buffer.
+    ///
+    /// Reads exactly one packet, sized from its type byte and length field, and decodes it as [`Protocol::recv`]
+    /// would. An unknown type byte fails with [`ErrorKind::Unsupported`] after consuming only that byte.
+    ///
+    /// ```
+    /// use lurk_protocol::{Parser, PktFight, PktLoot, Protocol};
+    /// use std::io::Cursor;
+    ///
+    /// let mut bytes = PktLoot::new("Goblin").encode_to_vec().unwrap();
+    /// PktFight::new().write_to(&mut bytes).unwrap();
+    ///
+    /// let mut reader = Cursor::new(bytes);
+    /// assert!(Protocol::from_reader(&mut reader).unwrap().as_loot().is_some());
+    /// assert!(Protocol::from_reader(&mut reader).unwrap().as_fight().is_some());
+    /// ```
+    pub fn from_reader<R: std::io::Read>(reader: &mut R) -> Result<Protocol, std::io::Error> {
+        let mut bytes = Vec::new();
+        Protocol::read_raw_capped(reader, &mut bytes, usize::MAX)?;
+
+        Protocol::decode_bytes(&bytes)
+    }
+
+    /// Parse one packet from a transport that delivers whole frames of a known length, such as WebSocket messages.
+    ///
+    /// Exactly `len` bytes are read from `reader`. If the packet they hold does not end exactly at `len`, the
+    /// frame is rejected with [`ErrorKind::InvalidData`] so a desynchronised transport is caught at once rather
+    /// than several packets later. An unknown type byte is reported as [`ErrorKind::Unsupported`].
+    ///
+    /// ```
+    /// use lurk_protocol::{Parser, PktLoot, Protocol};
+    ///
+    /// let frame = PktLoot::new("Goblin").encode_to_vec().unwrap();
+    /// let packet = Protocol::from_reader_bounded(&mut frame.as_slice(), frame.len()).unwrap();
+    ///
+    /// assert!(packet.as_loot().is_some());
+    /// ```
+    pub fn from_reader_bounded<R: std::io::Read>(
+        reader: &mut R,
+        len: usize,
+    ) -> Result<Protocol, std::io::Error> {
+        if len == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Empty frame"));
+        }
+
+        let mut bytes = vec![0; len];
+        reader.read_exact(&mut bytes)?;
+
+        let (fixed_len, extended) = Protocol::layout_of(bytes[0])?;
+
+        if len < 1 + fixed_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Frame of {} bytes is shorter than the packet header of {} bytes",
+                    len,
+                    1 + fixed_len
+                ),
+            ));
+        }
 
-                let packet = Packet::read_into(stream, packet_type, &mut buffer)?;
+        let expected = match extended {
+            Some((lo, hi)) => Protocol::extended_len(
+                fixed_len,
+                u16::from_le_bytes([bytes[1 + lo], bytes[1 + hi]]),
+            )?,
+            None => 1 + fixed_len,
+        };
+
+        if expected != len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Frame of {} bytes does not match packet length of {} bytes",
+                    len, expected
+                ),
+            ));
+        }
+
+        Protocol::decode_bytes(&bytes)
+    }
+
+    /// Decode a complete, well-formed packet (type byte included) that this crate serialized itself.
+    ///
+    /// Fails as [`Protocol::known_type`] does for an unknown type byte, and with [`ErrorKind::UnexpectedEof`] for an
+    /// empty buffer or a body shorter than its fixed part.
+    pub(crate) fn decode_bytes(bytes: &[u8]) -> Result<Protocol, Error> {
+        let Some((&type_byte, body)) = bytes.split_first() else {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "Empty packet"));
+        };
+        let packet_type = Protocol::known_type(type_byte)?;
+        let packet = Packet::new(packet_type, body);
 
-                Ok(Protocol::ChangeRoom(PktChangeRoom::decode(packet)))
-            }
-            PktType::FIGHT => Ok(Protocol::Fight(PktFight::default())),
-            PktType::PVPFIGHT => {
-                let mut buffer = vec![0; 32];
+        let protocol = match packet_type {
+            PktType::MESSAGE => Protocol::Message(PktMessage::decode(packet)?),
+            PktType::CHANGEROOM => Protocol::ChangeRoom(PktChangeRoom::decode(packet)?),
+            PktType::FIGHT => Protocol::Fight(PktFight::decode(packet)?),
+            PktType::PVPFIGHT => Protocol::PVPFight(PktPVPFight::decode(packet)?),
+            PktType::LOOT => Protocol::Loot(PktLoot::decode(packet)?),
+            PktType::START => Protocol::Start(PktStart::decode(packet)?),
+            PktType::ERROR => Protocol::Error(PktError::decode(packet)?),
+            PktType::ACCEPT => Protocol::Accept(PktAccept::decode(packet)?),
+            PktType::ROOM => Protocol::Room(PktRoom::decode(packet)?),
+            PktType::CHARACTER => Protocol::Character(PktCharacter::decode(packet)?),
+            PktType::GAME => Protocol::Game(PktGame::decode(packet)?),
+            PktType::LEAVE => Protocol::Leave(PktLeave::decode(packet)?),
+            PktType::CONNECTION => Protocol::Connection(PktConnection::decode(packet)?),
+            PktType::VERSION => Protocol::Version(PktVersion::decode(packet)?),
+            PktType::DEFAULT => unreachable!("known_type rejects the default type"),
+        };
 
-                let packet = Packet::read_into(stream, packet_type, &mut buffer)?;
+        Ok(protocol)
+    }
 
-                Ok(Protocol::PVPFight(PktPVPFight::decode(packet)))
-            }
-            PktType::LOOT => {
-                let mut buffer = vec![0; 32];
+    /// The packet type named by a type byte, checked with [`PktType::try_from_byte`].
+    ///
+    /// Fails with [`ErrorKind::Unsupported`] naming the byte if no packet type uses it, `0` included.
+    pub(crate) fn known_type(type_byte: u8) -> Result<PktType, Error> {
+        match PktType::try_from_byte(type_byte)? {
+            PktType::DEFAULT => Err(unknown_type(type_byte)),
+            packet_type => Ok(packet_type),
+        }
+    }
 
-                let packet = Packet::read_into(stream, packet_type, &mut buffer)?;
+    /// The body layout of the packet type named by a type byte, failing as [`Protocol::known_type`] does.
+    pub(crate) fn layout_of(type_byte: u8) -> Result<(usize, Option<(usize, usize)>), Error> {
+        Protocol::body_layout(Protocol::known_type(type_byte)?)
+            .ok_or_else(|| unknown_type(type_byte))
+    }
 
-                Ok(Protocol::Loot(PktLoot::decode(packet)))
-            }
-            PktType::START => Ok(Protocol::Start(PktStart::default())),
-            PktType::ERROR => {
-                let mut buffer = vec![0; 3];
+    /// The total length, type byte included, of a packet with a `fixed_len` body followed by `length` bytes.
+    ///
+    /// On targets where `usize` is 16 bits the sum can overflow; that is reported as [`ErrorKind::InvalidData`]
+    /// rather than wrapping to a short length.
+    pub(crate) fn extended_len(fixed_len: usize, length: u16) -> Result<usize, Error> {
+        (1 + fixed_len)
+            .checked_add(usize::from(length))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Packet length {} does not fit in usize", length),
+                )
+            })
+    }
 
-                let packet = Packet::read_extended(stream, packet_type, &mut buffer, (1, 2))?;
+    /// The fixed body length of a packet type and, for variable-length packets, the indices within the body
+    /// of the little-endian length of the trailing data. Returns `None` for an unknown type.
+    pub(crate) fn body_layout(packet_type: PktType) -> Option<(usize, Option<(usize, usize)>)> {
+        let layout = match packet_type {
+            PktType::MESSAGE => (66, Some((0, 1))),
+            PktType::CHANGEROOM => (2, None),
+            PktType::FIGHT => (0, None),
+            PktType::PVPFIGHT => (32, None),
+            PktType::LOOT => (32, None),
+            PktType::START => (0, None),
+            PktType::ERROR => (3, Some((1, 2))),
+            PktType::ACCEPT => (1, None),
+            PktType::ROOM => (36, Some((34, 35))),
+            PktType::CHARACTER => (47, Some((45, 46))),
+            PktType::GAME => (6, Some((4, 5))),
+            PktType::LEAVE => (0, None),
+            PktType::CONNECTION => (36, Some((34, 35))),
+            PktType::VERSION => (4, Some((2, 3))),
+            PktType::DEFAULT => return None,
+        };
 
-                Ok(Protocol::Error(PktError::decode(packet)))
-            }
-            PktType::ACCEPT => {
-                let mut buffer = vec![0; 1];
+        Some(layout)
+    }
 
-                let packet = Packet::read_into(stream, packet_type, &mut buffer)?;
+    /// Read the bytes of exactly one packet, type byte included, without decoding it.
+    pub(crate) fn read_raw(stream: &TcpStream) -> Result<Vec<u8>, std::io::Error> {
+        let mut bytes = Vec::new();
+        Protocol::read_raw_into(stream, &mut bytes)?;
 
-                Ok(Protocol::Accept(PktAccept::decode(packet)))
-            }
-            PktType::ROOM => {
-                let mut buffer = vec![0; 36];
+        Ok(bytes)
+    }
 
-                let packet = Packet::read_extended(stream, packet_type, &mut buffer, (34, 35))?;
+    /// Read the bytes of exactly one packet into `bytes`, replacing its contents but reusing its allocation.
+    fn read_raw_into(stream: &TcpStream, bytes: &mut Vec<u8>) -> Result<(), std::io::Error> {
+        Protocol::read_raw_capped(stream, bytes, usize::MAX)
+    }
 
-                Ok(Protocol::Room(PktRoom::decode(packet)))
-            }
-            PktType::CHARACTER => {
-                let mut buffer = vec![0; 47];
+    /// Read one packet like [`Protocol::read_raw_into`], failing before reading the body if it is longer than `max_len`.
+    fn read_raw_capped<R: std::io::Read>(
+        mut stream: R,
+        bytes: &mut Vec<u8>,
+        max_len: usize,
+    ) -> Result<(), std::io::Error> {
+        let too_large = |len: usize| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Packet of {} bytes exceeds the limit of {} bytes",
+                    len, max_len
+                ),
+            )
+        };
 
-                let packet = Packet::read_extended(stream, packet_type, &mut buffer, (45, 46))?;
+        bytes.clear();
+        bytes.resize(1, 0);
+        stream.read_exact(bytes)?;
 
-                Ok(Protocol::Character(PktCharacter::decode(packet)))
-            }
-            PktType::GAME => {
-                let mut buffer = vec![0; 6];
+        let (fixed_len, extended) = Protocol::layout_of(bytes[0])?;
 
-                let packet = Packet::read_extended(stream, packet_type, &mut buffer, (4, 5))?;
+        if 1 + fixed_len > max_len {
+            return Err(too_large(1 + fixed_len));
+        }
 
-                Ok(Protocol::Game(PktGame::decode(packet)))
-            }
-            PktType::LEAVE => Ok(Protocol::Leave(PktLeave::default())),
-            PktType::CONNECTION => {
-                let mut buffer = vec![0; 36];
+        bytes.resize(1 + fixed_len, 0);
+        stream
+            .read_exact(&mut bytes[1..])
+            .map_err(|e| partial_read(e, "packet body"))?;
 
-                let packet = Packet::read_extended(stream, packet_type, &mut buffer, (34, 35))?;
+        if let Some((lo, hi)) = extended {
+            let len = Protocol::extended_len(
+                fixed_len,
+                u16::from_le_bytes([bytes[1 + lo], bytes[1 + hi]]),
+            )?;
 
-                Ok(Protocol::Connection(PktConnection::decode(packet)))
+            if len > max_len {
+                return Err(too_large(len));
             }
-            PktType::VERSION => {
-                let mut buffer = vec![0; 4];
 
-                let packet = Packet::read_extended(stream, packet_type, &mut buffer, (2, 3))?;
-
-                Ok(Protocol::Version(PktVersion::decode(packet)))
-            }
-            PktType::DEFAULT => Err(Error::new(ErrorKind::Unsupported, "Invalid packet type")),
+            bytes.resize(len, 0);
+            stream
+                .read_exact(&mut bytes[1 + fixed_len..])
+                .map_err(|e| partial_read(e, "descriptor"))?;
         }
+
+        Ok(())
     }
 }
 
+/// The error for a type byte no packet type uses.
+fn unknown_type(type_byte: u8) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format!("Invalid packet type {}", type_byte),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,7 +1090,7 @@ mod tests {
 
     #[test]
     fn protocol_display_pvpfight() {
-        let pkt = PktPVPFight::fight("Target");
+        let pkt = PktPVPFight::new("Target");
         let proto = Protocol::PVPFight(pkt);
         let output = format!("{}", proto);
         assert!(!output.is_empty(), "Display for PVPFight must be non-empty");
@@ -272,7 +1098,7 @@ mod tests {
 
     #[test]
     fn protocol_display_loot() {
-        let pkt = PktLoot::loot("Monster");
+        let pkt = PktLoot::new("Monster");
         let proto = Protocol::Loot(pkt);
         let output = format!("{}", proto);
         assert!(!output.is_empty(), "Display for Loot must be non-empty");
@@ -391,4 +1217,760 @@ mod tests {
         let output = format!("{}", proto);
         assert!(!output.is_empty(), "Display for Version must be non-empty");
     }
+
+    /// The matching `as_*` accessor borrows the packet; the others return `None`.
+    #[test]
+    fn protocol_as_accessors() {
+        let proto = Protocol::Message(PktMessage::server("Recipient", "Hello"));
+
+        assert_eq!(
+            proto.as_message().map(|m| m.message.as_ref()),
+            Some("Hello")
+        );
+        assert!(proto.as_room().is_none());
+        assert!(proto.as_character().is_none());
+        assert!(proto.as_version().is_none());
+    }
+
+    /// The matching `into_*` accessor yields the packet.
+    #[test]
+    fn protocol_into_accessor_match() {
+        let proto = Protocol::ChangeRoom(PktChangeRoom::from(5u16));
+        let change_room = proto.into_change_room().ok().expect("Expected ChangeRoom");
+
+        assert_eq!(change_room.room_number, 5);
+    }
+
+    /// A mismatched `into_*` accessor hands back the original packet.
+    #[test]
+    fn protocol_into_accessor_mismatch() {
+        let proto = Protocol::Loot(PktLoot::new("Goblin"));
+        let unexpected = proto.into_room().err().expect("Expected the packet back");
+        let loot = unexpected.into_loot().ok().expect("Expected Loot");
+
+        assert_eq!(loot.target_name.as_ref(), "Goblin");
+    }
+
+    /// A GAME with a zero-length description is received with an empty description,
+    /// and re-serializes to exactly the fixed-size body.
+    #[test]
+    fn protocol_recv_game_empty_description() {
+        let (client, server) = crate::test_common::socket_pair();
+        let game = PktGame {
+            packet_type: PktType::GAME,
+            initial_points: 100,
+            stat_limit: 65535,
+            description_len: 0,
+            description: "".into(),
+        };
+        crate::send_to(&client, &game).expect("Send failed");
+
+        let received = Protocol::recv(&server)
+            .expect("Recv failed")
+            .into_game()
+            .ok()
+            .expect("Expected Game");
+
+        assert_eq!(received.description_len, 0);
+        assert_eq!(received.description.as_ref(), "");
+        assert_eq!(
+            received.encode_to_vec().expect("Encoding failed").len(),
+            1 + 6
+        );
+    }
+
+    /// A CHARACTER with a zero-length description is received with an empty description,
+    /// and re-serializes to exactly the fixed-size body.
+    #[test]
+    fn protocol_recv_character_empty_description() {
+        let (client, server) = crate::test_common::socket_pair();
+        let character = PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: "Hero".into(),
+            flags: crate::CharacterFlags::reset(),
+            attack: 10,
+            defense: 10,
+            regen: 5,
+            health: 100,
+            gold: 0,
+            current_room: 0,
+            description_len: 0,
+            description: "".into(),
+        };
+        crate::send_to(&client, &character).expect("Send failed");
+        // A trailing packet proves nothing extra was consumed after the empty description
+        crate::send_to(&client, &PktLeave::default()).expect("Send failed");
+
+        let received = Protocol::recv(&server)
+            .expect("Recv failed")
+            .into_character()
+            .ok()
+            .expect("Expected Character");
+
+        assert_eq!(received.name.as_ref(), "Hero");
+        assert_eq!(received.description_len, 0);
+        assert_eq!(received.description.as_ref(), "");
+        assert_eq!(
+            received.encode_to_vec().expect("Encoding failed").len(),
+            1 + 47
+        );
+        assert!(
+            Protocol::recv(&server)
+                .expect("Recv failed")
+                .as_leave()
+                .is_some()
+        );
+    }
+
+    /// recv_safe behaves like recv for well-formed packets.
+    #[test]
+    fn protocol_recv_safe() {
+        let (client, server) = crate::test_common::socket_pair();
+        crate::send_to(&client, &PktLoot::new("Goblin")).expect("Send failed");
+
+        let loot = Protocol::recv_safe(&server)
+            .expect("Recv failed")
+            .into_loot()
+            .ok()
+            .expect("Expected Loot");
+        assert_eq!(loot.target_name.as_ref(), "Goblin");
+    }
+
+    /// recv_safe still reports ordinary I/O errors, such as the peer hanging up.
+    #[test]
+    fn protocol_recv_safe_eof() {
+        let (client, server) = crate::test_common::socket_pair();
+        drop(client);
+
+        assert!(Protocol::recv_safe(&server).is_err());
+    }
+
+    /// A panic in the parser is reported as `InvalidData` with its message, for `&str` and `String` payloads alike.
+    #[test]
+    fn protocol_recv_safe_panic() {
+        let err = Protocol::catch_parse_panic(|| panic!("body index out of range"))
+            .err()
+            .expect("Panic must become an error");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "Panicked while parsing packet: body index out of range"
+        );
+
+        let err = Protocol::catch_parse_panic(|| panic!("length {}", 7))
+            .err()
+            .expect("Panic must become an error");
+        assert_eq!(err.to_string(), "Panicked while parsing packet: length 7");
+    }
+
+    /// Peeking leaves the packet in place for a single recv.
+    #[test]
+    fn protocol_peek_then_recv() {
+        let (client, server) = crate::test_common::socket_pair();
+        crate::send_to(&client, &PktChangeRoom::from(4u16)).expect("Send failed");
+        crate::send_to(&client, &PktLoot::new("Goblin")).expect("Send failed");
+
+        assert_eq!(Protocol::peek_type(&server).ok(), Some(PktType::CHANGEROOM));
+        assert_eq!(Protocol::peek_type(&server).ok(), Some(PktType::CHANGEROOM));
+
+        let room = Protocol::recv(&server).expect("Recv failed");
+        assert_eq!(room.as_change_room().map(|c| c.room_number), Some(4));
+
+        assert_eq!(Protocol::peek_type(&server).ok(), Some(PktType::LOOT));
+        assert!(
+            Protocol::recv(&server)
+                .expect("Recv failed")
+                .as_loot()
+                .is_some()
+        );
+    }
+
+    /// Peeking a closed connection reports end of file.
+    #[test]
+    fn protocol_peek_eof() {
+        let (client, server) = crate::test_common::socket_pair();
+        drop(client);
+
+        let err = Protocol::peek_type(&server).expect_err("Peek must fail");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    /// Skipping a packet, fixed or variable length, leaves the next one intact.
+    #[test]
+    fn protocol_skip() {
+        let (client, server) = crate::test_common::socket_pair();
+        crate::send_to(&client, &PktLoot::new("Goblin")).expect("Send failed");
+        crate::send_to(&client, &PktMessage::server("Player", "Skipped")).expect("Send failed");
+        crate::send_to(&client, &PktChangeRoom::from(8u16)).expect("Send failed");
+
+        assert_eq!(Protocol::skip(&server).ok(), Some(PktType::LOOT));
+        assert_eq!(Protocol::skip(&server).ok(), Some(PktType::MESSAGE));
+
+        let room = Protocol::recv(&server).expect("Recv failed");
+        assert_eq!(room.as_change_room().map(|c| c.room_number), Some(8));
+    }
+
+    /// An unknown type byte cannot be sized, so it is an error, and it is left on the stream.
+    #[test]
+    fn protocol_skip_unknown_type() {
+        let (client, server) = crate::test_common::socket_pair();
+        (&*client).write_all(&[0xFF]).expect("Write failed");
+
+        let err = Protocol::skip(&server).expect_err("Skip must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        let mut byte = [0; 1];
+        server.as_ref().read_exact(&mut byte).expect("Read failed");
+        assert_eq!(byte, [0xFF]);
+    }
+
+    /// The default policy reports an unknown type like recv.
+    #[test]
+    fn protocol_recv_with_config_error() {
+        let (client, server) = crate::test_common::socket_pair();
+        (&*client).write_all(&[0xFF]).expect("Write failed");
+
+        let err = Protocol::recv_with_config(&server, &RecvConfig::default())
+            .err()
+            .expect("Unknown type must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// The skip policy steps over stray bytes to the next packet.
+    #[test]
+    fn protocol_recv_with_config_skip() {
+        let (client, server) = crate::test_common::socket_pair();
+        (&*client)
+            .write_all(&[0xFF, 0x00, 0x42])
+            .expect("Write failed");
+        crate::send_to(&client, &PktChangeRoom::from(6u16)).expect("Send failed");
+
+        let config = RecvConfig {
+            on_unknown_type: UnknownTypePolicy::Skip,
+            ..RecvConfig::default()
+        };
+        let packet = Protocol::recv_with_config(&server, &config).expect("Recv failed");
+        assert_eq!(packet.as_change_room().map(|c| c.room_number), Some(6));
+    }
+
+    /// The close policy shuts the connection down.
+    #[test]
+    fn protocol_recv_with_config_close() {
+        let (client, server) = crate::test_common::socket_pair();
+        (&*client).write_all(&[0xFF]).expect("Write failed");
+
+        let config = RecvConfig {
+            on_unknown_type: UnknownTypePolicy::Close,
+            ..RecvConfig::default()
+        };
+        let err = Protocol::recv_with_config(&server, &config)
+            .err()
+            .expect("Unknown type must fail");
+        assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+
+        let mut buffer = [0; 1];
+        assert_eq!((&*client).read(&mut buffer).ok(), Some(0));
+    }
+
+    /// A peer that half-closes at a packet boundary, having sent nothing, yields a clean end-of-file error.
+    #[test]
+    fn protocol_recv_half_closed_before_type_byte() {
+        let (client, server) = crate::test_common::socket_pair();
+        client
+            .shutdown(std::net::Shutdown::Write)
+            .expect("Shutdown failed");
+
+        let err = Protocol::recv(&server)
+            .err()
+            .expect("Recv on a closed connection must fail");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        // Only the client's write half is closed, so the server can still reply
+        crate::send_to(&server, &PktChangeRoom::from(1u16)).expect("Send failed");
+        let reply = Protocol::recv(&client).expect("Recv failed");
+        assert!(reply.as_change_room().is_some());
+    }
+
+    /// A packet streamed straight into the socket arrives intact.
+    #[test]
+    fn protocol_recv_streamed_packet() {
+        let (client, server) = crate::test_common::socket_pair();
+        let description = "Stalactites drip. ".repeat(2000);
+        let room = PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: 12,
+            room_name: "Grotto".into(),
+            description_len: description.len() as u16,
+            description: description.as_str().into(),
+        };
+
+        let writer = std::thread::spawn(move || crate::send_streaming(&client, &room));
+        let received = Protocol::recv(&server).expect("Recv failed");
+        writer
+            .join()
+            .expect("Writer thread panicked")
+            .expect("Send failed");
+
+        let received = received.into_room().ok().expect("Expected Room");
+        assert_eq!(received.room_number, 12);
+        assert_eq!(received.description.as_ref(), description);
+    }
+
+    /// A frame holding exactly one packet parses to that packet.
+    #[test]
+    fn protocol_from_reader_bounded() {
+        let frame = PktMessage::server("Bob", "Welcome!")
+            .encode_to_vec()
+            .expect("Encoding failed");
+
+        let packet = Protocol::from_reader_bounded(&mut frame.as_slice(), frame.len())
+            .expect("Parsing failed");
+        assert_eq!(packet.packet_type(), PktType::MESSAGE);
+        assert_eq!(&*packet.as_message().unwrap().message, "Welcome!");
+    }
+
+    /// A frame longer or shorter than the packet it holds is rejected as a framing mismatch.
+    #[test]
+    fn protocol_from_reader_bounded_mismatch() {
+        let mut frame = PktMessage::server("Bob", "Welcome!")
+            .encode_to_vec()
+            .expect("Encoding failed");
+        frame.push(0);
+
+        let err = Protocol::from_reader_bounded(&mut frame.as_slice(), frame.len())
+            .err()
+            .expect("Trailing byte must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = Protocol::from_reader_bounded(&mut frame.as_slice(), 10)
+            .err()
+            .expect("Short frame must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = Protocol::from_reader_bounded(&mut frame.as_slice(), frame.len() - 2)
+            .err()
+            .expect("Truncated message must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Unknown type bytes and reads past the end of the reader are reported.
+    #[test]
+    fn protocol_from_reader_bounded_errors() {
+        let err = Protocol::from_reader_bounded(&mut [0xFFu8, 0].as_slice(), 2)
+            .err()
+            .expect("Unknown type must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        let err = Protocol::from_reader_bounded(&mut [PktType::FIGHT.into()].as_slice(), 4)
+            .err()
+            .expect("Short reader must fail");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    /// Writing through `Protocol` produces the same bytes as writing the inner packet.
+    #[test]
+    fn protocol_write_to_matches_inner() {
+        let packets = [
+            Protocol::Message(PktMessage::server("Bob", "Hi")),
+            Protocol::ChangeRoom(PktChangeRoom::new(3)),
+            Protocol::Fight(PktFight::new()),
+            Protocol::PVPFight(PktPVPFight::new("Bob")),
+            Protocol::Loot(PktLoot::new("Goblin")),
+            Protocol::Start(PktStart::new()),
+            Protocol::Error(PktError::new(crate::LurkError::BADROOM, "No.")),
+            Protocol::Accept(PktAccept::new(PktType::START)),
+            Protocol::Room(PktRoom::new(1, "Hall", "Wide.")),
+            Protocol::Character(PktCharacter::new("Hero", "Brave.")),
+            Protocol::Game(PktGame::new(100, 65535, "Caves.")),
+            Protocol::Leave(PktLeave::new()),
+            Protocol::Connection(PktConnection::new(2, "Cellar", "Damp.")),
+            Protocol::Version(PktVersion::new(2, 3)),
+        ];
+
+        for packet in &packets {
+            let bytes = packet.encode_to_vec().expect("Encoding failed");
+
+            assert_eq!(bytes[0], u8::from(packet.packet_type()));
+            assert_eq!(
+                Protocol::decode_bytes(&bytes).ok().map(|p| p.packet_type()),
+                Some(packet.packet_type())
+            );
+
+            let mut inner = Vec::new();
+            match packet {
+                Protocol::Message(p) => p.write_to(&mut inner),
+                Protocol::ChangeRoom(p) => p.write_to(&mut inner),
+                Protocol::Fight(p) => p.write_to(&mut inner),
+                Protocol::PVPFight(p) => p.write_to(&mut inner),
+                Protocol::Loot(p) => p.write_to(&mut inner),
+                Protocol::Start(p) => p.write_to(&mut inner),
+                Protocol::Error(p) => p.write_to(&mut inner),
+                Protocol::Accept(p) => p.write_to(&mut inner),
+                Protocol::Room(p) => p.write_to(&mut inner),
+                Protocol::Character(p) => p.write_to(&mut inner),
+                Protocol::Game(p) => p.write_to(&mut inner),
+                Protocol::Leave(p) => p.write_to(&mut inner),
+                Protocol::Connection(p) => p.write_to(&mut inner),
+                Protocol::Version(p) => p.write_to(&mut inner),
+                Protocol::Custom(..) => unreachable!("No custom packets in the list"),
+            }
+            .expect("Encoding failed");
+            assert_eq!(bytes, inner, "{}", packet.packet_type());
+        }
+    }
+
+    /// Any packet converts into the matching `Protocol` variant without a socket.
+    #[test]
+    fn protocol_from_packet() {
+        let protocol = Protocol::from(PktMessage::server("Bob", "Hi"));
+        assert_eq!(protocol.packet_type(), PktType::MESSAGE);
+        assert!(!format!("{}", protocol).is_empty());
+
+        let protocol: Protocol = PktLeave::new().into();
+        assert!(protocol.as_leave().is_some());
+    }
+
+    /// Pooled receives decode the same packets and hand their buffer back each time.
+    #[test]
+    fn protocol_recv_pooled() {
+        let (client, server) = crate::test_common::socket_pair();
+        let pool = BufferPool::new(4);
+
+        crate::send_to(&client, &PktRoom::new(1, "Hall", "Wide.")).expect("Send failed");
+        crate::send_to(&client, &PktLeave::new()).expect("Send failed");
+
+        let room = Protocol::recv_pooled(&server, &pool).expect("Recv failed");
+        assert_eq!(
+            room.as_room().map(|r| r.description.as_ref()),
+            Some("Wide.")
+        );
+        assert_eq!(pool.idle(), 1);
+
+        let leave = Protocol::recv_pooled(&server, &pool).expect("Recv failed");
+        assert_eq!(leave.packet_type(), PktType::LEAVE);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    /// Every strict prefix of a valid packet is incomplete, never corrupt, and reports the bytes still needed.
+    #[test]
+    fn protocol_from_bytes_incomplete() {
+        let bytes = PktRoom::new(4, "Hall", "Wide and cold.")
+            .encode_to_vec()
+            .expect("Encoding failed");
+
+        for len in 0..bytes.len() {
+            let err = Protocol::from_bytes(&bytes[..len])
+                .err()
+                .expect("Prefix must be incomplete");
+            assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+            let needed = Incomplete::from_error(&err)
+                .expect("Missing Incomplete")
+                .needed;
+            let expected = match len {
+                0 => 1,
+                1..37 => 37 - len,
+                _ => bytes.len() - len,
+            };
+            assert_eq!(needed, expected);
+        }
+
+        let mut extra = bytes.clone();
+        extra.push(0xFF);
+        let (packet, used) = Protocol::from_bytes(&extra).expect("Parsing failed");
+        assert_eq!(used, bytes.len());
+        assert_eq!(packet.as_room().map(|r| r.room_number), Some(4));
+    }
+
+    /// A length that cannot be addressed is corrupt rather than incomplete, since reading more would never help.
+    #[test]
+    fn protocol_extended_len_overflow() {
+        let err = Protocol::extended_len(usize::MAX - 1, 1).expect_err("Must overflow");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(Incomplete::from_error(&err).is_none());
+
+        assert_eq!(Protocol::extended_len(36, 5).ok(), Some(42));
+    }
+
+    /// An unknown type byte is corrupt rather than incomplete, both alone and after complete packets.
+    #[test]
+    fn protocol_from_bytes_corrupt() {
+        let err = Protocol::from_bytes(&[0xFF]).err().expect("Must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(Incomplete::from_error(&err).is_none());
+
+        let mut bytes = PktFight::new().encode_to_vec().expect("Encoding failed");
+        bytes.push(0xFF);
+        let err = Protocol::parse_all(&bytes).err().expect("Must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// A rejected peer receives the ERROR and then end of stream.
+    #[test]
+    fn protocol_reject() {
+        let (client, server) = crate::test_common::socket_pair();
+
+        Protocol::reject(&server, LurkError::NOTREADY, "Send CHARACTER first.")
+            .expect("Reject failed");
+
+        let error = Protocol::recv(&client).expect("Recv failed");
+        let error = error.as_error().expect("Expected Error");
+        assert_eq!(error.error, LurkError::NOTREADY);
+        assert_eq!(&*error.message, "Send CHARACTER first.");
+
+        let err = Protocol::recv(&client).err().expect("Stream must end");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    /// A packet whose declared length passes the cap is rejected before its description is read.
+    #[test]
+    fn protocol_recv_with_config_max_packet_bytes() {
+        let (client, server) = crate::test_common::socket_pair();
+        let config = RecvConfig {
+            max_packet_bytes: Some(100),
+            ..RecvConfig::default()
+        };
+
+        let small = PktCharacter::new("Hero", "Brave.");
+        crate::send_to(&client, &small).expect("Send failed");
+        let packet = Protocol::recv_with_config(&server, &config).expect("Recv failed");
+        assert_eq!(packet.as_character().map(|c| c.description_len), Some(6));
+
+        let description = "x".repeat(60);
+        crate::send_to(&client, &PktCharacter::new("Hero", &description)).expect("Send failed");
+        let err = Protocol::recv_with_config(&server, &config)
+            .err()
+            .expect("Oversized packet must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // The description is still unread in the socket
+        let mut rest = vec![0; 60];
+        server.as_ref().read_exact(&mut rest).expect("Read failed");
+        assert_eq!(rest, description.as_bytes());
+    }
+
+    /// A fixed-size packet larger than the cap is rejected too.
+    #[test]
+    fn protocol_recv_with_config_max_packet_bytes_fixed() {
+        let (client, server) = crate::test_common::socket_pair();
+        let config = RecvConfig {
+            max_packet_bytes: Some(16),
+            ..RecvConfig::default()
+        };
+
+        crate::send_to(&client, &PktLoot::new("Goblin")).expect("Send failed");
+        let err = Protocol::recv_with_config(&server, &config)
+            .err()
+            .expect("Oversized packet must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    /// Forwarding through a proxy delivers every packet type unchanged.
+    #[test]
+    fn protocol_forward() {
+        let (client, proxy_in) = crate::test_common::socket_pair();
+        let (proxy_out, server) = crate::test_common::socket_pair();
+        let packets = [
+            Protocol::Message(PktMessage::narrator("Bob", "Hi")),
+            Protocol::ChangeRoom(PktChangeRoom::new(3)),
+            Protocol::Fight(PktFight::new()),
+            Protocol::PVPFight(PktPVPFight::new("Bob")),
+            Protocol::Loot(PktLoot::new("Goblin")),
+            Protocol::Start(PktStart::new()),
+            Protocol::Error(PktError::new(crate::LurkError::BADROOM, "No.")),
+            Protocol::Accept(PktAccept::new(PktType::START)),
+            Protocol::Room(PktRoom::new(1, "Hall", "Wide.")),
+            Protocol::Character(PktCharacter::new("Hero", "Brave.")),
+            Protocol::Game(PktGame::new(100, 65535, "Caves.")),
+            Protocol::Leave(PktLeave::new()),
+            Protocol::Connection(PktConnection::new(2, "Cellar", "Damp.")),
+            Protocol::Version(PktVersion::new(2, 3)),
+        ];
+
+        for packet in &packets {
+            packet.forward(&client).expect("Send failed");
+
+            Protocol::recv(&proxy_in)
+                .expect("Recv failed")
+                .forward(&proxy_out)
+                .expect("Forward failed");
+
+            let received = Protocol::recv(&server).expect("Recv failed");
+            assert_eq!(received.encode_to_vec().ok(), packet.encode_to_vec().ok());
+        }
+
+        let err = Protocol::Custom(20, Arc::new(()))
+            .forward(&proxy_out)
+            .expect_err("Custom packets cannot be forwarded");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// With nothing sent, `recv_timeout` times out and puts back the stream's own timeout.
+    #[test]
+    fn protocol_recv_timeout_idle() {
+        let (_client, server) = crate::test_common::socket_pair();
+
+        let err = Protocol::recv_timeout(&server, Duration::from_millis(20))
+            .err()
+            .expect("Recv must time out");
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        assert_eq!(server.read_timeout().ok().flatten(), None);
+    }
+
+    /// A packet cut off partway times out with a clear error rather than decoding, and the timeout is restored.
+    #[test]
+    fn protocol_recv_timeout_partial() {
+        use std::io::Write;
+
+        let (client, server) = crate::test_common::socket_pair();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let bytes = PktMessage::server("Hero", "Hello").encode_to_vec().unwrap();
+
+        client.as_ref().write_all(&bytes[..20]).unwrap();
+
+        let err = Protocol::recv_timeout(&server, Duration::from_millis(20))
+            .err()
+            .expect("Recv must time out");
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+        assert!(err.to_string().contains("partway"), "{}", err);
+        assert_eq!(
+            server.read_timeout().ok().flatten(),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    /// A packet that arrives in time is received as usual.
+    #[test]
+    fn protocol_recv_timeout_in_time() {
+        let (client, server) = crate::test_common::socket_pair();
+        crate::send_to(&client, &PktChangeRoom::new(8)).expect("Send failed");
+
+        let packet = Protocol::recv_timeout(&server, Duration::from_secs(5)).expect("Recv failed");
+        assert_eq!(packet.as_change_room().map(|c| c.room_number), Some(8));
+    }
+
+    /// Any reader works, and a reader that runs dry partway through a packet is reported rather than decoded.
+    #[test]
+    fn protocol_from_reader() {
+        let mut bytes = PktRoom::new(1, "Hall", "Wide.").encode_to_vec().unwrap();
+        PktLeave::new().write_to(&mut bytes).unwrap();
+
+        let mut reader = std::io::BufReader::with_capacity(3, bytes.as_slice());
+        let room = Protocol::from_reader(&mut reader).expect("Read failed");
+        assert_eq!(
+            room.as_room().map(|r| r.description.as_ref()),
+            Some("Wide.")
+        );
+        let leave = Protocol::from_reader(&mut reader).expect("Read failed");
+        assert_eq!(leave.packet_type(), PktType::LEAVE);
+
+        let err = Protocol::from_reader(&mut &bytes[..20])
+            .err()
+            .expect("Short reader must fail");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let err = Protocol::from_reader(&mut [0xFFu8].as_slice())
+            .err()
+            .expect("Unknown type must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    /// An unknown type byte is reported with its value, distinct from a zero byte.
+    #[test]
+    fn protocol_recv_names_unknown_type() {
+        let (client, server) = crate::test_common::socket_pair();
+
+        (&*client).write_all(&[0xC8, 0x00]).expect("Write failed");
+
+        let err = Protocol::recv(&server).err().expect("Recv must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(err.to_string(), "Invalid packet type 200");
+
+        let err = Protocol::recv(&server).err().expect("Recv must fail");
+        assert_eq!(err.to_string(), "Invalid packet type 0");
+    }
+
+    /// Every path that checks a type byte names it, whether it is zero or out of range.
+    #[test]
+    fn protocol_type_checks_name_byte() {
+        for byte in [0u8, 200] {
+            let expected = format!("Invalid packet type {}", byte);
+            let bytes = [byte, 0, 0];
+
+            let err = Protocol::from_bytes(&bytes).err().expect("Parse must fail");
+            assert_eq!(err.to_string(), expected);
+
+            let err = Protocol::from_reader(&mut &bytes[..])
+                .err()
+                .expect("Read must fail");
+            assert_eq!(err.to_string(), expected);
+
+            let err = Protocol::from_reader_bounded(&mut &bytes[..], bytes.len())
+                .err()
+                .expect("Read must fail");
+            assert_eq!(err.to_string(), expected);
+
+            let (client, server) = crate::test_common::socket_pair();
+            (&*client).write_all(&bytes).expect("Write failed");
+
+            let err = Protocol::peek_type(&server).expect_err("Peek must fail");
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    /// A burst arrives in order, and a packet that cannot be serialized stops the whole burst before any write.
+    #[test]
+    fn protocol_send_all() {
+        let (client, server) = crate::test_common::socket_pair();
+
+        Protocol::send_all(
+            &client,
+            &[
+                Protocol::Room(PktRoom::new(1, "Hall", "Wide.")),
+                Protocol::ChangeRoom(PktChangeRoom::new(2)),
+                Protocol::Leave(PktLeave::new()),
+            ],
+        )
+        .expect("Send failed");
+
+        let err = Protocol::send_all(
+            &client,
+            &[
+                Protocol::Fight(PktFight::new()),
+                Protocol::Custom(20, Arc::new(())),
+            ],
+        )
+        .expect_err("Custom packets cannot be sent");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        let types: Vec<_> = (0..3)
+            .map(|_| Protocol::recv(&server).expect("Recv failed").packet_type())
+            .collect();
+        assert_eq!(types, [PktType::ROOM, PktType::CHANGEROOM, PktType::LEAVE]);
+
+        server
+            .set_nonblocking(true)
+            .expect("Set nonblocking failed");
+        let mut byte = [0; 1];
+        assert!(
+            server.as_ref().read(&mut byte).is_err(),
+            "Nothing of the failed burst may be sent"
+        );
+    }
+
+    /// A cloned packet encodes identically, and a custom body is shared rather than copied.
+    #[test]
+    fn protocol_clone() {
+        let room = Protocol::Room(PktRoom::new(1, "Hall", "Wide."));
+        assert_eq!(room.clone().encode_to_vec().ok(), room.encode_to_vec().ok());
+
+        let custom = Protocol::Custom(20, Arc::new(7u16));
+        let (Protocol::Custom(_, a), Protocol::Custom(_, b)) = (&custom, &custom.clone()) else {
+            panic!("Expected custom packets");
+        };
+        assert!(Arc::ptr_eq(a, b));
+        assert_eq!(b.downcast_ref::<u16>(), Some(&7));
+    }
 }
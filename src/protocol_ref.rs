@@ -0,0 +1,571 @@
+use std::borrow::Cow;
+use std::io::Error;
+
+use crate::packet::{borrow_name, check_body};
+use crate::{
+    CharacterFlags, LurkError, PktAccept, PktChangeRoom, PktCharacter, PktConnection, PktError,
+    PktFight, PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight, PktRoom, PktStart, PktType,
+    PktVersion, Protocol,
+};
+
+/// A packet decoded without copying, borrowing its text fields from the input buffer.
+///
+/// Mirrors [`Protocol`] variant for variant. Text is borrowed wherever it is valid UTF-8 and only copied (lossily,
+/// as [`Protocol`] decodes it) where it is not, so a router or logger can inspect a packet without allocating.
+/// Use [`ProtocolRef::to_protocol`] to keep it past the lifetime of the buffer.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use lurk_protocol::{Parser, PktMessage, ProtocolRef};
+///
+/// let bytes = PktMessage::player("Alice", "Bob", "Hello, Bob!").encode_to_vec().unwrap();
+///
+/// let (packet, used) = ProtocolRef::from_bytes(&bytes).unwrap();
+/// assert_eq!(used, bytes.len());
+///
+/// let ProtocolRef::Message(message) = &packet else { unreachable!() };
+/// assert!(matches!(message.message, Cow::Borrowed("Hello, Bob!")));
+///
+/// assert_eq!(packet.to_protocol().encode_to_vec().unwrap(), bytes);
+/// ```
+#[derive(Debug, Clone)]
+pub enum ProtocolRef<'a> {
+    /// A message sent between client and server.
+    Message(PktMessageRef<'a>),
+    /// A request to change to the given room.
+    ChangeRoom(u16),
+    /// A fight request.
+    Fight,
+    /// A player-versus-player fight request.
+    PVPFight(PktPVPFightRef<'a>),
+    /// A loot request.
+    Loot(PktLootRef<'a>),
+    /// A start request.
+    Start,
+    /// An error response.
+    Error(PktErrorRef<'a>),
+    /// An acceptance of the packet type given.
+    Accept(u8),
+    /// Room information.
+    Room(PktRoomRef<'a>),
+    /// Character information.
+    Character(PktCharacterRef<'a>),
+    /// Game information.
+    Game(PktGameRef<'a>),
+    /// A leave request.
+    Leave,
+    /// An exit from the current room.
+    Connection(PktConnectionRef<'a>),
+    /// Version information.
+    Version(PktVersionRef<'a>),
+}
+
+/// A borrowed view of a [`PktMessage`].
+#[derive(Debug, Clone)]
+pub struct PktMessageRef<'a> {
+    /// Length of the message.
+    pub message_len: u16,
+    /// Name of the recipient.
+    pub recipient: Cow<'a, str>,
+    /// Name of the sender.
+    pub sender: Cow<'a, str>,
+    /// Whether the sender field carried the narration marker.
+    pub narration: bool,
+    /// The message text.
+    pub message: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktPVPFight`].
+#[derive(Debug, Clone)]
+pub struct PktPVPFightRef<'a> {
+    /// Name of the player to fight.
+    pub target_name: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktLoot`].
+#[derive(Debug, Clone)]
+pub struct PktLootRef<'a> {
+    /// Name of the character to loot.
+    pub target_name: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktError`].
+#[derive(Debug, Clone)]
+pub struct PktErrorRef<'a> {
+    /// The error code.
+    pub error: LurkError,
+    /// Length of the error message.
+    pub message_len: u16,
+    /// The error message, up to the first NUL byte.
+    pub message: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktRoom`].
+#[derive(Debug, Clone)]
+pub struct PktRoomRef<'a> {
+    /// The room number.
+    pub room_number: u16,
+    /// Name of the room.
+    pub room_name: Cow<'a, str>,
+    /// Length of the description.
+    pub description_len: u16,
+    /// Description of the room.
+    pub description: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktCharacter`].
+#[derive(Debug, Clone)]
+pub struct PktCharacterRef<'a> {
+    /// Name of the character.
+    pub name: Cow<'a, str>,
+    /// The character's flags, reserved bits included.
+    pub flags: CharacterFlags,
+    /// Attack stat.
+    pub attack: u16,
+    /// Defense stat.
+    pub defense: u16,
+    /// Regeneration stat.
+    pub regen: u16,
+    /// Current health.
+    pub health: i16,
+    /// Gold carried.
+    pub gold: u16,
+    /// The room the character is in.
+    pub current_room: u16,
+    /// Length of the description.
+    pub description_len: u16,
+    /// Description of the character.
+    pub description: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktGame`].
+#[derive(Debug, Clone)]
+pub struct PktGameRef<'a> {
+    /// Points a new character may spend.
+    pub initial_points: u16,
+    /// The server-wide stat limit.
+    pub stat_limit: u16,
+    /// Length of the description.
+    pub description_len: u16,
+    /// Description of the game.
+    pub description: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktConnection`].
+#[derive(Debug, Clone)]
+pub struct PktConnectionRef<'a> {
+    /// Number of the connected room.
+    pub room_number: u16,
+    /// Name of the connected room.
+    pub room_name: Cow<'a, str>,
+    /// Length of the description.
+    pub description_len: u16,
+    /// Description of the connected room.
+    pub description: Cow<'a, str>,
+}
+
+/// A borrowed view of a [`PktVersion`].
+#[derive(Debug, Clone)]
+pub struct PktVersionRef<'a> {
+    /// Major revision.
+    pub major_rev: u8,
+    /// Minor revision.
+    pub minor_rev: u8,
+    /// Length of the extension list.
+    pub extensions_len: u16,
+    /// The raw extension list, as sent.
+    pub extensions: &'a [u8],
+}
+
+impl<'a> ProtocolRef<'a> {
+    /// Decode the complete packet at the start of `bytes`, returning it with the number of bytes it used.
+    ///
+    /// Fails exactly as [`Protocol::from_bytes`] does: a partial packet with [`std::io::ErrorKind::UnexpectedEof`]
+    /// wrapping an [`Incomplete`](crate::Incomplete), an unknown type byte with
+    /// [`std::io::ErrorKind::Unsupported`].
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<(ProtocolRef<'a>, usize), Error> {
+        let len = Protocol::frame_len(bytes)?;
+        let body = &bytes[1..len];
+
+        let packet = match PktType::from(bytes[0]) {
+            PktType::MESSAGE => ProtocolRef::Message(PktMessageRef::decode(body)?),
+            PktType::CHANGEROOM => ProtocolRef::ChangeRoom(u16_at(body, 0)),
+            PktType::FIGHT => ProtocolRef::Fight,
+            PktType::PVPFIGHT => ProtocolRef::PVPFight(PktPVPFightRef::decode(body)?),
+            PktType::LOOT => ProtocolRef::Loot(PktLootRef::decode(body)?),
+            PktType::START => ProtocolRef::Start,
+            PktType::ERROR => ProtocolRef::Error(PktErrorRef::decode(body)?),
+            PktType::ACCEPT => ProtocolRef::Accept(body[0]),
+            PktType::ROOM => ProtocolRef::Room(PktRoomRef::decode(body)?),
+            PktType::CHARACTER => ProtocolRef::Character(PktCharacterRef::decode(body)?),
+            PktType::GAME => ProtocolRef::Game(PktGameRef::decode(body)?),
+            PktType::LEAVE => ProtocolRef::Leave,
+            PktType::CONNECTION => ProtocolRef::Connection(PktConnectionRef::decode(body)?),
+            PktType::VERSION => ProtocolRef::Version(PktVersionRef::decode(body)?),
+            PktType::DEFAULT => unreachable!("frame_len rejects unknown types"),
+        };
+
+        Ok((packet, len))
+    }
+
+    /// The type of this packet.
+    pub fn packet_type(&self) -> PktType {
+        match self {
+            ProtocolRef::Message(_) => PktType::MESSAGE,
+            ProtocolRef::ChangeRoom(_) => PktType::CHANGEROOM,
+            ProtocolRef::Fight => PktType::FIGHT,
+            ProtocolRef::PVPFight(_) => PktType::PVPFIGHT,
+            ProtocolRef::Loot(_) => PktType::LOOT,
+            ProtocolRef::Start => PktType::START,
+            ProtocolRef::Error(_) => PktType::ERROR,
+            ProtocolRef::Accept(_) => PktType::ACCEPT,
+            ProtocolRef::Room(_) => PktType::ROOM,
+            ProtocolRef::Character(_) => PktType::CHARACTER,
+            ProtocolRef::Game(_) => PktType::GAME,
+            ProtocolRef::Leave => PktType::LEAVE,
+            ProtocolRef::Connection(_) => PktType::CONNECTION,
+            ProtocolRef::Version(_) => PktType::VERSION,
+        }
+    }
+
+    /// Copy the packet into an owned [`Protocol`], as [`Protocol::from_bytes`] would have decoded it.
+    pub fn to_protocol(&self) -> Protocol {
+        match self {
+            ProtocolRef::Message(m) => Protocol::Message(m.to_packet()),
+            ProtocolRef::ChangeRoom(room) => Protocol::ChangeRoom(PktChangeRoom::new(*room)),
+            ProtocolRef::Fight => Protocol::Fight(PktFight::new()),
+            ProtocolRef::PVPFight(p) => Protocol::PVPFight(p.to_packet()),
+            ProtocolRef::Loot(l) => Protocol::Loot(l.to_packet()),
+            ProtocolRef::Start => Protocol::Start(PktStart::new()),
+            ProtocolRef::Error(e) => Protocol::Error(e.to_packet()),
+            ProtocolRef::Accept(accept_type) => Protocol::Accept(PktAccept {
+                packet_type: PktType::ACCEPT,
+                accept_type: *accept_type,
+            }),
+            ProtocolRef::Room(r) => Protocol::Room(r.to_packet()),
+            ProtocolRef::Character(c) => Protocol::Character(c.to_packet()),
+            ProtocolRef::Game(g) => Protocol::Game(g.to_packet()),
+            ProtocolRef::Leave => Protocol::Leave(PktLeave::new()),
+            ProtocolRef::Connection(c) => Protocol::Connection(c.to_packet()),
+            ProtocolRef::Version(v) => Protocol::Version(v.to_packet()),
+        }
+    }
+}
+
+// The field offsets of every packet with a body live here. Each owned `Parser::decode` decodes its body through
+// the matching view and copies it, so the borrowed and owned decoders cannot disagree. Every view checks the body
+// against its fixed length before indexing, so a short body is an error rather than a panic.
+
+/// The little-endian `u16` at `i` in `body`.
+fn u16_at(body: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([body[i], body[i + 1]])
+}
+
+impl<'a> PktMessageRef<'a> {
+    /// Decode a `MESSAGE` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::MESSAGE)?;
+
+        // The narration marker occupies the last two bytes of the sender field
+        let (sender, narration) = match &body[34..66] {
+            [name @ .., 0x00, 0x01] => (name, true),
+            name => (name, false),
+        };
+
+        Ok(PktMessageRef {
+            message_len: u16_at(body, 0),
+            recipient: borrow_name(&body[2..34]),
+            sender: borrow_name(sender),
+            narration,
+            message: String::from_utf8_lossy(&body[66..]),
+        })
+    }
+
+    /// Copy into an owned [`PktMessage`].
+    pub(crate) fn to_packet(&self) -> PktMessage {
+        PktMessage {
+            packet_type: PktType::MESSAGE,
+            message_len: self.message_len,
+            recipient: self.recipient.as_ref().into(),
+            sender: self.sender.as_ref().into(),
+            narration: self.narration,
+            message: self.message.as_ref().into(),
+        }
+    }
+}
+
+impl<'a> PktPVPFightRef<'a> {
+    /// Decode a `PVPFIGHT` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::PVPFIGHT)?;
+
+        Ok(PktPVPFightRef {
+            target_name: borrow_name(&body[0..32]),
+        })
+    }
+
+    /// Copy into an owned [`PktPVPFight`].
+    pub(crate) fn to_packet(&self) -> PktPVPFight {
+        PktPVPFight::new(&self.target_name)
+    }
+}
+
+impl<'a> PktLootRef<'a> {
+    /// Decode a `LOOT` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::LOOT)?;
+
+        Ok(PktLootRef {
+            target_name: borrow_name(&body[0..32]),
+        })
+    }
+
+    /// Copy into an owned [`PktLoot`].
+    pub(crate) fn to_packet(&self) -> PktLoot {
+        PktLoot::new(&self.target_name)
+    }
+}
+
+impl<'a> PktErrorRef<'a> {
+    /// Decode an `ERROR` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::ERROR)?;
+
+        Ok(PktErrorRef {
+            error: LurkError::from(body[0]),
+            message_len: u16_at(body, 1),
+            message: borrow_name(&body[3..]),
+        })
+    }
+
+    /// Copy into an owned [`PktError`].
+    pub(crate) fn to_packet(&self) -> PktError {
+        PktError {
+            packet_type: PktType::ERROR,
+            error: self.error,
+            message_len: self.message_len,
+            message: self.message.as_ref().into(),
+        }
+    }
+}
+
+impl<'a> PktRoomRef<'a> {
+    /// Decode a `ROOM` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::ROOM)?;
+
+        Ok(PktRoomRef {
+            room_number: u16_at(body, 0),
+            room_name: borrow_name(&body[2..34]),
+            description_len: u16_at(body, 34),
+            description: String::from_utf8_lossy(&body[36..]),
+        })
+    }
+
+    /// Copy into an owned [`PktRoom`].
+    pub(crate) fn to_packet(&self) -> PktRoom {
+        PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: self.room_number,
+            room_name: self.room_name.as_ref().into(),
+            description_len: self.description_len,
+            description: self.description.as_ref().into(),
+        }
+    }
+}
+
+impl<'a> PktCharacterRef<'a> {
+    /// Decode a `CHARACTER` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::CHARACTER)?;
+
+        Ok(PktCharacterRef {
+            name: borrow_name(&body[0..32]),
+            flags: CharacterFlags::from_raw(body[32]),
+            attack: u16_at(body, 33),
+            defense: u16_at(body, 35),
+            regen: u16_at(body, 37),
+            health: i16::from_le_bytes([body[39], body[40]]),
+            gold: u16_at(body, 41),
+            current_room: u16_at(body, 43),
+            description_len: u16_at(body, 45),
+            description: String::from_utf8_lossy(&body[47..]),
+        })
+    }
+
+    /// Copy into an owned [`PktCharacter`].
+    pub(crate) fn to_packet(&self) -> PktCharacter {
+        PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: self.name.as_ref().into(),
+            flags: self.flags,
+            attack: self.attack,
+            defense: self.defense,
+            regen: self.regen,
+            health: self.health,
+            gold: self.gold,
+            current_room: self.current_room,
+            description_len: self.description_len,
+            description: self.description.as_ref().into(),
+        }
+    }
+}
+
+impl<'a> PktGameRef<'a> {
+    /// Decode a `GAME` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::GAME)?;
+
+        Ok(PktGameRef {
+            initial_points: u16_at(body, 0),
+            stat_limit: u16_at(body, 2),
+            description_len: u16_at(body, 4),
+            description: String::from_utf8_lossy(&body[6..]),
+        })
+    }
+
+    /// Copy into an owned [`PktGame`].
+    pub(crate) fn to_packet(&self) -> PktGame {
+        PktGame {
+            packet_type: PktType::GAME,
+            initial_points: self.initial_points,
+            stat_limit: self.stat_limit,
+            description_len: self.description_len,
+            description: self.description.as_ref().into(),
+        }
+    }
+}
+
+impl<'a> PktConnectionRef<'a> {
+    /// Decode a `CONNECTION` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::CONNECTION)?;
+
+        Ok(PktConnectionRef {
+            room_number: u16_at(body, 0),
+            room_name: borrow_name(&body[2..34]),
+            description_len: u16_at(body, 34),
+            description: String::from_utf8_lossy(&body[36..]),
+        })
+    }
+
+    /// Copy into an owned [`PktConnection`].
+    pub(crate) fn to_packet(&self) -> PktConnection {
+        PktConnection {
+            packet_type: PktType::CONNECTION,
+            room_number: self.room_number,
+            room_name: self.room_name.as_ref().into(),
+            description_len: self.description_len,
+            description: self.description.as_ref().into(),
+        }
+    }
+}
+
+impl<'a> PktVersionRef<'a> {
+    /// Decode a `VERSION` body, type byte excluded.
+    pub(crate) fn decode(body: &'a [u8]) -> Result<Self, Error> {
+        check_body(body, PktType::VERSION)?;
+
+        Ok(PktVersionRef {
+            major_rev: body[0],
+            minor_rev: body[1],
+            extensions_len: u16_at(body, 2),
+            extensions: &body[4..],
+        })
+    }
+
+    /// Copy into an owned [`PktVersion`].
+    pub(crate) fn to_packet(&self) -> PktVersion {
+        PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: self.major_rev,
+            minor_rev: self.minor_rev,
+            extensions_len: self.extensions_len,
+            extensions: (!self.extensions.is_empty()).then(|| self.extensions.to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    /// Every packet type decodes borrowed and converts back to the same bytes as the owned decoder produces.
+    #[test]
+    fn protocol_ref_matches_owned() {
+        let packets: Vec<Vec<u8>> = vec![
+            PktMessage::player("Alice", "Bob", "Hello")
+                .encode_to_vec()
+                .unwrap(),
+            PktMessage::narrator("Bob", "Wind.")
+                .encode_to_vec()
+                .unwrap(),
+            PktChangeRoom::new(513).encode_to_vec().unwrap(),
+            PktFight::new().encode_to_vec().unwrap(),
+            PktPVPFight::new("Bob").encode_to_vec().unwrap(),
+            PktLoot::new("Goblin").encode_to_vec().unwrap(),
+            PktStart::new().encode_to_vec().unwrap(),
+            PktError::new(LurkError::BADROOM, "No.")
+                .encode_to_vec()
+                .unwrap(),
+            PktAccept::new(PktType::CHARACTER).encode_to_vec().unwrap(),
+            PktRoom::new(1, "Entrance", "Arch.")
+                .encode_to_vec()
+                .unwrap(),
+            PktCharacter::new("Hero", "Brave.").encode_to_vec().unwrap(),
+            PktGame::new(100, 200, "Caves.").encode_to_vec().unwrap(),
+            PktLeave::new().encode_to_vec().unwrap(),
+            PktConnection::new(2, "Hallway", "Dim.")
+                .encode_to_vec()
+                .unwrap(),
+            PktVersion::new(2, 3).encode_to_vec().unwrap(),
+        ];
+
+        for bytes in packets {
+            let (packet, used) = ProtocolRef::from_bytes(&bytes).expect("Decode failed");
+            let (owned, _) = Protocol::from_bytes(&bytes).expect("Decode failed");
+
+            assert_eq!(used, bytes.len());
+            assert_eq!(packet.packet_type(), owned.packet_type());
+            assert_eq!(
+                packet.to_protocol().encode_to_vec().ok(),
+                owned.encode_to_vec().ok()
+            );
+        }
+    }
+
+    /// Valid UTF-8 is borrowed, and the narration marker is stripped from the sender.
+    #[test]
+    fn protocol_ref_borrows_text() {
+        let bytes = PktMessage::narrator("Bob", "A cold wind blows.")
+            .encode_to_vec()
+            .unwrap();
+
+        let Ok((ProtocolRef::Message(message), _)) = ProtocolRef::from_bytes(&bytes) else {
+            panic!("Expected a message");
+        };
+
+        assert!(message.narration);
+        assert!(matches!(message.sender, Cow::Borrowed(_)));
+        assert!(matches!(
+            message.message,
+            Cow::Borrowed("A cold wind blows.")
+        ));
+        assert_eq!(message.recipient, "Bob");
+    }
+
+    /// Invalid UTF-8 is replaced, as the owned decoder does.
+    #[test]
+    fn protocol_ref_lossy_text() {
+        let mut bytes = PktLoot::new("Gob").encode_to_vec().unwrap();
+        bytes[2] = 0xFF;
+
+        let Ok((ProtocolRef::Loot(loot), _)) = ProtocolRef::from_bytes(&bytes) else {
+            panic!("Expected a loot");
+        };
+
+        assert!(matches!(loot.target_name, Cow::Owned(_)));
+        assert_eq!(loot.target_name, "G\u{FFFD}b");
+    }
+}
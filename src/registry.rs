@@ -0,0 +1,234 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Read};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+use crate::Protocol;
+use crate::packet::partial_read;
+
+/// Decoder for the body of a custom packet, i.e. everything after the type byte.
+type CustomParser = Box<dyn Fn(&[u8]) -> Result<Box<dyn Any + Send + Sync>, Error> + Send + Sync>;
+
+struct CustomType {
+    fixed_len: usize,
+    length_index: Option<(usize, usize)>,
+    parser: CustomParser,
+}
+
+/// Custom packet types for experimental extensions, received with [`Protocol::recv_with_registry`].
+///
+/// Each custom type byte is registered with the layout of its body, so the packet can be read off the stream, and a
+/// parser for that body. Received packets come back as [`Protocol::Custom`]. The built-in types cannot be
+/// overridden, and an empty registry receives exactly as [`Protocol::recv`] does.
+///
+/// ```no_run
+/// use lurk_protocol::{Protocol, PktTypeRegistry};
+/// use std::net::TcpStream;
+/// use std::sync::Arc;
+///
+/// // Type 20: a two-byte little-endian ping counter
+/// let mut registry = PktTypeRegistry::new();
+/// registry
+///     .register(20, 2, None, |body| Ok(Box::new(u16::from_le_bytes([body[0], body[1]]))))
+///     .unwrap();
+///
+/// let stream = Arc::new(TcpStream::connect("127.0.0.1:8080").unwrap());
+///
+/// if let Protocol::Custom(20, ping) = Protocol::recv_with_registry(&stream, &registry).unwrap() {
+///     println!("Ping {}", ping.downcast_ref::<u16>().unwrap());
+/// }
+/// ```
+#[derive(Default)]
+pub struct PktTypeRegistry {
+    types: HashMap<u8, CustomType>,
+}
+
+impl PktTypeRegistry {
+    /// Create a registry with no custom types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom packet type, replacing any earlier registration of the same byte.
+    ///
+    /// The body is `fixed_len` bytes, followed, if `length_index` is given, by as many bytes as the little-endian
+    /// `u16` at those indices of the body. `parser` receives the whole body, without the type byte.
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if `type_byte` is one of the built-in types, or if `length_index`
+    /// lies outside the fixed part of the body.
+    pub fn register<F>(
+        &mut self,
+        type_byte: u8,
+        fixed_len: usize,
+        length_index: Option<(usize, usize)>,
+        parser: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(&[u8]) -> Result<Box<dyn Any + Send + Sync>, Error> + Send + Sync + 'static,
+    {
+        if Protocol::known_type(type_byte).is_ok() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Packet type {} is built in", type_byte),
+            ));
+        }
+
+        if length_index.is_some_and(|(lo, hi)| lo.max(hi) >= fixed_len) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Length index lies outside the fixed body",
+            ));
+        }
+
+        self.types.insert(
+            type_byte,
+            CustomType {
+                fixed_len,
+                length_index,
+                parser: Box::new(parser),
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Whether a custom type is registered for this byte.
+    pub fn contains(&self, type_byte: u8) -> bool {
+        self.types.contains_key(&type_byte)
+    }
+
+    /// Read the rest of a registered packet whose type byte has already been consumed, and parse it.
+    ///
+    /// A failure partway through is reported as it is for the built-in types, so a timeout stays `TimedOut`.
+    pub(crate) fn read(
+        &self,
+        stream: &TcpStream,
+        type_byte: u8,
+    ) -> Result<Arc<dyn Any + Send + Sync>, Error> {
+        let Some(custom) = self.types.get(&type_byte) else {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("Invalid packet type {}", type_byte),
+            ));
+        };
+
+        let mut stream = stream;
+        let mut body = vec![0; custom.fixed_len];
+        stream
+            .read_exact(&mut body)
+            .map_err(|e| partial_read(e, "packet body"))?;
+
+        if let Some((lo, hi)) = custom.length_index {
+            let len =
+                Protocol::extended_len(custom.fixed_len, u16::from_le_bytes([body[lo], body[hi]]))?;
+
+            body.resize(len - 1, 0);
+            stream
+                .read_exact(&mut body[custom.fixed_len..])
+                .map_err(|e| partial_read(e, "descriptor"))?;
+        }
+
+        (custom.parser)(&body).map(Arc::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_common::socket_pair;
+    use crate::{Parser, PktLeave, PktType};
+    use std::io::Write;
+
+    /// Built-in type bytes and out-of-range length indices are rejected.
+    #[test]
+    fn registry_register_rejects() {
+        let mut registry = PktTypeRegistry::new();
+
+        let err = registry
+            .register(12, 0, None, |_| Ok(Box::new(())))
+            .expect_err("Built-in type must be rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let err = registry
+            .register(20, 2, Some((2, 3)), |_| Ok(Box::new(())))
+            .expect_err("Length index must be within the fixed body");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        assert!(!registry.contains(20));
+    }
+
+    /// A registered variable-length packet is read whole and parsed, and built-in packets still decode.
+    #[test]
+    fn registry_recv_custom() {
+        let (client, server) = socket_pair();
+        let mut registry = PktTypeRegistry::new();
+        registry
+            .register(20, 3, Some((1, 2)), |body| {
+                Ok(Box::new((body[0], body[3..].to_vec())))
+            })
+            .expect("Register failed");
+
+        client
+            .as_ref()
+            .write_all(&[20, 7, 2, 0, b'h', b'i'])
+            .unwrap();
+        client
+            .as_ref()
+            .write_all(&PktLeave::new().encode_to_vec().unwrap())
+            .unwrap();
+
+        let Protocol::Custom(20, payload) =
+            Protocol::recv_with_registry(&server, &registry).expect("Recv failed")
+        else {
+            panic!("Expected a custom packet");
+        };
+        assert_eq!(
+            payload.downcast_ref::<(u8, Vec<u8>)>(),
+            Some(&(7, b"hi".to_vec()))
+        );
+
+        let packet = Protocol::recv_with_registry(&server, &registry).expect("Recv failed");
+        assert_eq!(packet.packet_type(), PktType::LEAVE);
+    }
+
+    /// A custom packet that stalls partway through times out like a built-in one, and one cut short is `UnexpectedEof`.
+    #[test]
+    fn registry_recv_partial() {
+        let (client, server) = socket_pair();
+        let mut registry = PktTypeRegistry::new();
+        registry
+            .register(20, 3, Some((1, 2)), |_| Ok(Box::new(())))
+            .expect("Register failed");
+        server
+            .set_read_timeout(Some(std::time::Duration::from_millis(50)))
+            .unwrap();
+
+        client.as_ref().write_all(&[20, 7]).unwrap();
+        let err = Protocol::recv_with_registry(&server, &registry)
+            .err()
+            .expect("Recv must time out");
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+        let (client, server) = socket_pair();
+        client.as_ref().write_all(&[20, 7, 4, 0, b'h']).unwrap();
+        drop(client);
+        let err = Protocol::recv_with_registry(&server, &registry)
+            .err()
+            .expect("Recv must fail");
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    /// An unregistered unknown byte still fails as `Unsupported`.
+    #[test]
+    fn registry_recv_unregistered() {
+        let (client, server) = socket_pair();
+
+        client.as_ref().write_all(&[21]).unwrap();
+
+        let err = Protocol::recv_with_registry(&server, &PktTypeRegistry::new())
+            .err()
+            .expect("Recv must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
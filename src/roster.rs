@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::PktCharacter;
+
+/// A client-side view of the characters in the player's room, kept current from incoming `CHARACTER` packets.
+///
+/// The server announces a character leaving the room by sending its `CHARACTER` with the room it moved to,
+/// so any update placing a character outside the player's room removes it. When the player's own
+/// character moves, everyone left behind is dropped.
+///
+/// ```
+/// use lurk_protocol::{CharacterFlags, PktCharacter, PktType, Roster};
+///
+/// let character = |name: &str, room: u16| PktCharacter {
+///     packet_type: PktType::CHARACTER,
+///     name: name.into(),
+///     flags: CharacterFlags::alive(),
+///     attack: 10,
+///     defense: 10,
+///     regen: 10,
+///     health: 100,
+///     gold: 0,
+///     current_room: room,
+///     description_len: 0,
+///     description: "".into(),
+/// };
+///
+/// let mut roster = Roster::new("Hero");
+/// roster.apply(&character("Hero", 1));
+/// roster.apply(&character("Goblin", 1));
+/// assert_eq!(roster.in_room(1).count(), 2);
+///
+/// // The goblin wanders off to room 2
+/// roster.apply(&character("Goblin", 2));
+/// assert!(roster.get("Goblin").is_none());
+/// ```
+pub struct Roster {
+    player: Arc<str>,
+    room: Option<u16>,
+    characters: HashMap<Arc<str>, PktCharacter>,
+}
+
+impl Roster {
+    /// Create an empty roster for the player with the given name.
+    pub fn new(player: &str) -> Self {
+        Self {
+            player: Arc::from(player),
+            room: None,
+            characters: HashMap::new(),
+        }
+    }
+
+    /// The room the player is in, once their own `CHARACTER` has been applied.
+    pub fn room(&self) -> Option<u16> {
+        self.room
+    }
+
+    /// Apply an incoming `CHARACTER` update, inserting, updating, or removing the character by name.
+    pub fn apply(&mut self, character: &PktCharacter) {
+        if character.name == self.player {
+            if self.room != Some(character.current_room) {
+                self.room = Some(character.current_room);
+                self.characters
+                    .retain(|_, c| c.current_room == character.current_room);
+            }
+        } else if self.room.is_some_and(|room| room != character.current_room) {
+            self.characters.remove(&character.name);
+            return;
+        }
+
+        self.characters
+            .insert(character.name.clone(), character.clone());
+    }
+
+    /// Look up a tracked character by name.
+    pub fn get(&self, name: &str) -> Option<&PktCharacter> {
+        self.characters.get(name)
+    }
+
+    /// Iterate over the tracked characters in the given room, including the player.
+    pub fn in_room(&self, room: u16) -> impl Iterator<Item = &PktCharacter> {
+        self.characters
+            .values()
+            .filter(move |c| c.current_room == room)
+    }
+
+    /// The number of tracked characters, including the player.
+    pub fn len(&self) -> usize {
+        self.characters.len()
+    }
+
+    /// Whether no characters are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.characters.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharacterFlags, PktType};
+
+    fn character(name: &str, room: u16, health: i16) -> PktCharacter {
+        PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: name.into(),
+            flags: CharacterFlags::alive(),
+            attack: 10,
+            defense: 10,
+            regen: 10,
+            health,
+            gold: 0,
+            current_room: room,
+            description_len: 0,
+            description: "".into(),
+        }
+    }
+
+    #[test]
+    fn roster_insert_and_update() {
+        let mut roster = Roster::new("Hero");
+        roster.apply(&character("Hero", 1, 100));
+        roster.apply(&character("Goblin", 1, 30));
+        roster.apply(&character("Goblin", 1, 10));
+
+        assert_eq!(roster.len(), 2);
+        assert_eq!(roster.get("Goblin").map(|c| c.health), Some(10));
+    }
+
+    #[test]
+    fn roster_removes_character_leaving_room() {
+        let mut roster = Roster::new("Hero");
+        roster.apply(&character("Hero", 1, 100));
+        roster.apply(&character("Elf", 1, 100));
+        roster.apply(&character("Elf", 4, 100));
+
+        assert!(roster.get("Elf").is_none());
+        assert_eq!(roster.in_room(1).count(), 1);
+    }
+
+    #[test]
+    fn roster_player_move_drops_old_room() {
+        let mut roster = Roster::new("Hero");
+        roster.apply(&character("Hero", 1, 100));
+        roster.apply(&character("Goblin", 1, 30));
+        roster.apply(&character("Hero", 2, 100));
+        roster.apply(&character("Troll", 2, 80));
+
+        assert_eq!(roster.room(), Some(2));
+        assert!(roster.get("Goblin").is_none());
+
+        let mut names: Vec<_> = roster.in_room(2).map(|c| c.name.as_ref()).collect();
+        names.sort();
+        assert_eq!(names, ["Hero", "Troll"]);
+    }
+
+    /// Before the player's own character arrives, every update is kept.
+    #[test]
+    fn roster_before_player_known() {
+        let mut roster = Roster::new("Hero");
+        roster.apply(&character("Goblin", 1, 30));
+        roster.apply(&character("Troll", 2, 80));
+
+        assert_eq!(roster.room(), None);
+        assert_eq!(roster.len(), 2);
+
+        roster.apply(&character("Hero", 2, 100));
+        assert!(roster.get("Goblin").is_none());
+        assert!(roster.get("Troll").is_some());
+    }
+}
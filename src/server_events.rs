@@ -0,0 +1,401 @@
+use crate::{LurkError, PktCharacter, PktConnection, PktMessage, PktRoom, Protocol};
+
+/// Build the packets announcing that a player has left the room, ready to broadcast to those left behind.
+///
+/// Returns the player's `CHARACTER` placed in `to_room` and, if `narrate` is set, a narrator `MESSAGE`
+/// describing the departure. The message has an empty recipient; set it for each player it is sent to.
+///
+/// ```
+/// use lurk_protocol::{CharacterFlags, PktCharacter, PktType, Protocol, server_events};
+///
+/// let player = PktCharacter {
+///     packet_type: PktType::CHARACTER,
+///     name: "Glorfindel".into(),
+///     flags: CharacterFlags::alive(),
+///     attack: 10,
+///     defense: 10,
+///     regen: 10,
+///     health: 100,
+///     gold: 0,
+///     current_room: 1,
+///     description_len: 0,
+///     description: "".into(),
+/// };
+///
+/// let packets = server_events::player_left(&player, 2, true);
+///
+/// assert!(matches!(&packets[0], Protocol::Character(c) if c.current_room == 2));
+/// assert!(matches!(&packets[1], Protocol::Message(m) if m.narration));
+/// ```
+pub fn player_left(player: &PktCharacter, to_room: u16, narrate: bool) -> Vec<Protocol> {
+    let mut departed = player.clone();
+    departed.current_room = to_room;
+
+    let mut packets = vec![Protocol::Character(departed)];
+
+    if narrate {
+        packets.push(Protocol::Message(PktMessage::narrator(
+            "",
+            &format!("{} leaves the room.", player.name),
+        )));
+    }
+
+    packets
+}
+
+/// Build the packets a server sends once a player's `START` has been accepted, in the documented order.
+///
+/// The sequence is the `ROOM` the player starts in, the player's own `CHARACTER`, a `CHARACTER` for every other
+/// occupant of the room, then a `CONNECTION` for each exit. The player is skipped if they also appear in `occupants`.
+///
+/// ```
+/// use lurk_protocol::{CharacterFlags, PktCharacter, PktRoom, PktType, Protocol, server_events};
+///
+/// let room = PktRoom {
+///     packet_type: PktType::ROOM,
+///     room_number: 1,
+///     room_name: "Entrance".into(),
+///     description_len: 0,
+///     description: "".into(),
+/// };
+/// let player = PktCharacter {
+///     packet_type: PktType::CHARACTER,
+///     name: "Hero".into(),
+///     flags: CharacterFlags::alive(),
+///     attack: 10,
+///     defense: 10,
+///     regen: 10,
+///     health: 100,
+///     gold: 0,
+///     current_room: 1,
+///     description_len: 0,
+///     description: "".into(),
+/// };
+///
+/// let packets = server_events::welcome(&player, &room, &[], &[]);
+///
+/// assert!(matches!(packets[0], Protocol::Room(_)));
+/// assert!(matches!(packets[1], Protocol::Character(_)));
+/// ```
+pub fn welcome(
+    player: &PktCharacter,
+    room: &PktRoom,
+    occupants: &[PktCharacter],
+    connections: &[PktConnection],
+) -> Vec<Protocol> {
+    let mut packets = Vec::with_capacity(2 + occupants.len() + connections.len());
+
+    packets.push(Protocol::Room(room.clone()));
+    packets.push(Protocol::Character(player.clone()));
+    packets.extend(
+        occupants
+            .iter()
+            .filter(|occupant| occupant.name != player.name)
+            .cloned()
+            .map(Protocol::Character),
+    );
+    packets.extend(connections.iter().cloned().map(Protocol::Connection));
+
+    packets
+}
+
+/// Build the refresh a server sends after moving a player itself, e.g. a teleport or a fall through the floor.
+///
+/// The `CHANGEROOM` documentation asks for updated `ROOM`, `CHARACTER`, and `CONNECTION` packets whenever the
+/// server changes a player's room, so the client does not keep a stale map. The sequence is the same as
+/// [`welcome`]: the new `ROOM`, the player's `CHARACTER` placed in it, the other `occupants`, then each of the
+/// `connections`.
+///
+/// ```
+/// use lurk_protocol::{PktCharacter, PktConnection, PktRoom, server_events};
+///
+/// let player = PktCharacter { current_room: 1, ..PktCharacter::new("Hero", "") };
+/// let pit = PktRoom::new(9, "Pit", "You fell.");
+///
+/// let packets = server_events::relocate(&player, &pit, &[PktConnection::new(1, "Entrance", "")], &[]);
+///
+/// assert_eq!(packets[1].as_character().map(|c| c.current_room), Some(9));
+/// assert_eq!(packets.len(), 3);
+/// ```
+pub fn relocate(
+    player: &PktCharacter,
+    new_room: &PktRoom,
+    connections: &[PktConnection],
+    occupants: &[PktCharacter],
+) -> Vec<Protocol> {
+    let mut moved = player.clone();
+    moved.current_room = new_room.room_number;
+
+    welcome(&moved, new_room, occupants, connections)
+}
+
+/// The characters fighting on the initiator's side of a `FIGHT`: the initiator, then every living player in
+/// the same room with the join-battle flag ([`CharacterFlags::BATTLE`](crate::CharacterFlags::BATTLE)).
+///
+/// Monsters never join, even with `BATTLE` set: a `FIGHT` is players against the monsters in the room, so
+/// monsters are always the opposing side. Dead players keep their `BATTLE` flag but are left out, as is the
+/// initiator if they also appear in `room_occupants`.
+///
+/// ```
+/// use lurk_protocol::{CharacterFlags, PktCharacter, server_events};
+///
+/// let hero = PktCharacter { flags: CharacterFlags::alive(), ..PktCharacter::new("Hero", "") };
+/// let ally = PktCharacter { flags: CharacterFlags::alive(), ..PktCharacter::new("Ally", "") };
+/// let idle = PktCharacter { flags: CharacterFlags::ALIVE, ..PktCharacter::new("Idle", "") };
+///
+/// let occupants = [ally, idle];
+/// let fighters = server_events::participants_for_fight(&hero, &occupants);
+///
+/// assert_eq!(fighters.iter().map(|c| c.name.as_ref()).collect::<Vec<_>>(), ["Hero", "Ally"]);
+/// ```
+pub fn participants_for_fight<'a>(
+    initiator: &'a PktCharacter,
+    room_occupants: &'a [PktCharacter],
+) -> Vec<&'a PktCharacter> {
+    let mut participants = vec![initiator];
+
+    participants.extend(room_occupants.iter().filter(|occupant| {
+        occupant.name != initiator.name
+            && occupant.in_same_room_as(initiator)
+            && occupant.flags.is_alive()
+            && occupant.flags.is_battle()
+            && !occupant.flags.is_monster()
+    }));
+
+    participants
+}
+
+/// Apply a `LOOT` of `target` by `looter`: move the target's gold to the looter and return the amount taken.
+///
+/// Only a dead character in the looter's room can be looted; anything else is [`LurkError::NOTARGET`], as is a
+/// target the server cannot find, which the caller reports before calling this. If the looter's gold would
+/// overflow, only what fits is taken and the rest stays with the target, so no gold is lost.
+///
+/// ```
+/// use lurk_protocol::{CharacterFlags, LurkError, PktCharacter, server_events};
+///
+/// let mut hero = PktCharacter { flags: CharacterFlags::alive(), gold: 10, ..PktCharacter::new("Hero", "") };
+/// let mut goblin = PktCharacter { flags: CharacterFlags::MONSTER, gold: 25, ..PktCharacter::new("Goblin", "") };
+///
+/// assert_eq!(server_events::loot_result(&mut hero, &mut goblin), Ok(25));
+/// assert_eq!((hero.gold, goblin.gold), (35, 0));
+///
+/// goblin.flags |= CharacterFlags::ALIVE;
+/// assert_eq!(server_events::loot_result(&mut hero, &mut goblin), Err(LurkError::NOTARGET));
+/// ```
+pub fn loot_result(looter: &mut PktCharacter, target: &mut PktCharacter) -> Result<u16, LurkError> {
+    if target.flags.is_alive() || !looter.in_same_room_as(target) {
+        return Err(LurkError::NOTARGET);
+    }
+
+    let looted = target.gold.min(u16::MAX - looter.gold);
+    looter.gold += looted;
+    target.gold -= looted;
+
+    Ok(looted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CharacterFlags, PktType};
+
+    fn player() -> PktCharacter {
+        character("Hero")
+    }
+
+    fn character(name: &str) -> PktCharacter {
+        PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: name.into(),
+            flags: CharacterFlags::alive(),
+            attack: 10,
+            defense: 10,
+            regen: 10,
+            health: 100,
+            gold: 5,
+            current_room: 1,
+            description_len: 0,
+            description: "".into(),
+        }
+    }
+
+    /// Without narration only the relocated CHARACTER is sent.
+    #[test]
+    fn player_left_without_narration() {
+        let packets = player_left(&player(), 7, false);
+
+        assert_eq!(packets.len(), 1);
+        let character = packets[0].as_character().expect("Expected Character");
+        assert_eq!(character.current_room, 7);
+        assert_eq!(character.gold, 5);
+    }
+
+    /// Narration follows the CHARACTER update and names the departing player.
+    #[test]
+    fn player_left_with_narration() {
+        let packets = player_left(&player(), 7, true);
+
+        assert_eq!(packets.len(), 2);
+        assert!(packets[0].as_character().is_some());
+
+        let message = packets[1].as_message().expect("Expected Message");
+        assert!(message.narration);
+        assert_eq!(message.message.as_ref(), "Hero leaves the room.");
+    }
+
+    fn connection(room_number: u16) -> PktConnection {
+        PktConnection {
+            packet_type: PktType::CONNECTION,
+            room_number,
+            room_name: "Hallway".into(),
+            description_len: 0,
+            description: "".into(),
+        }
+    }
+
+    /// The welcome sequence is ROOM, the player, each other occupant, then each exit.
+    #[test]
+    fn welcome_order() {
+        let room = PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: 1,
+            room_name: "Entrance".into(),
+            description_len: 0,
+            description: "".into(),
+        };
+        let occupants = [character("Goblin"), player(), character("Troll")];
+        let connections = [connection(2), connection(3)];
+
+        let packets = welcome(&player(), &room, &occupants, &connections);
+        let types: Vec<PktType> = packets.iter().map(Protocol::packet_type).collect();
+
+        assert_eq!(
+            types,
+            [
+                PktType::ROOM,
+                PktType::CHARACTER,
+                PktType::CHARACTER,
+                PktType::CHARACTER,
+                PktType::CONNECTION,
+                PktType::CONNECTION,
+            ]
+        );
+
+        let names: Vec<&str> = packets
+            .iter()
+            .filter_map(Protocol::as_character)
+            .map(|c| c.name.as_ref())
+            .collect();
+        assert_eq!(names, ["Hero", "Goblin", "Troll"]);
+    }
+
+    /// Only living players with BATTLE in the initiator's room join, never monsters.
+    #[test]
+    fn participants_for_fight_rules() {
+        let with = |name: &str, flags: CharacterFlags, room: u16| PktCharacter {
+            flags,
+            current_room: room,
+            ..character(name)
+        };
+        let hero = player();
+        let occupants = [
+            with("Hero", CharacterFlags::alive(), 1),
+            with("Ally", CharacterFlags::alive(), 1),
+            with("Elsewhere", CharacterFlags::alive(), 2),
+            with("Fallen", CharacterFlags::dead(), 1),
+            with("Bystander", CharacterFlags::ALIVE, 1),
+            with(
+                "Goblin",
+                CharacterFlags::alive() | CharacterFlags::MONSTER,
+                1,
+            ),
+        ];
+
+        let names: Vec<_> = participants_for_fight(&hero, &occupants)
+            .iter()
+            .map(|c| c.name.as_ref())
+            .collect();
+        assert_eq!(names, ["Hero", "Ally"]);
+    }
+
+    /// Looting a dead character in the room moves all of its gold to the looter.
+    #[test]
+    fn loot_result_transfers_gold() {
+        let mut looter = player();
+        let mut target = PktCharacter {
+            flags: CharacterFlags::MONSTER,
+            gold: 40,
+            ..character("Goblin")
+        };
+
+        assert_eq!(loot_result(&mut looter, &mut target), Ok(40));
+        assert_eq!((looter.gold, target.gold), (45, 0));
+        assert_eq!(loot_result(&mut looter, &mut target), Ok(0));
+    }
+
+    /// Living targets and targets in another room cannot be looted, and nothing changes hands.
+    #[test]
+    fn loot_result_rejects() {
+        let mut looter = player();
+        let mut living = character("Goblin");
+        let mut elsewhere = PktCharacter {
+            flags: CharacterFlags::empty(),
+            current_room: 2,
+            ..character("Corpse")
+        };
+
+        assert_eq!(
+            loot_result(&mut looter, &mut living),
+            Err(LurkError::NOTARGET)
+        );
+        assert_eq!(
+            loot_result(&mut looter, &mut elsewhere),
+            Err(LurkError::NOTARGET)
+        );
+        assert_eq!((looter.gold, living.gold, elsewhere.gold), (5, 5, 5));
+    }
+
+    /// Gold that would overflow the looter's purse stays with the target.
+    #[test]
+    fn loot_result_saturates() {
+        let mut looter = PktCharacter {
+            gold: u16::MAX - 3,
+            ..player()
+        };
+        let mut target = PktCharacter {
+            flags: CharacterFlags::empty(),
+            gold: 10,
+            ..character("Corpse")
+        };
+
+        assert_eq!(loot_result(&mut looter, &mut target), Ok(3));
+        assert_eq!((looter.gold, target.gold), (u16::MAX, 7));
+    }
+
+    /// A forced move sends the new room, the player placed in it, the occupants, then the exits.
+    #[test]
+    fn relocate_refreshes_room() {
+        let room = PktRoom::new(4, "Pit", "Dark.");
+        let occupants = [character("Rat")];
+        let connections = [connection(1)];
+
+        let packets = relocate(&player(), &room, &connections, &occupants);
+        let types: Vec<PktType> = packets.iter().map(Protocol::packet_type).collect();
+
+        assert_eq!(
+            types,
+            [
+                PktType::ROOM,
+                PktType::CHARACTER,
+                PktType::CHARACTER,
+                PktType::CONNECTION,
+            ]
+        );
+        assert_eq!(packets[0].as_room().map(|r| r.room_number), Some(4));
+
+        let moved = packets[1].as_character().expect("Expected Character");
+        assert_eq!(&*moved.name, "Hero");
+        assert_eq!(moved.current_room, 4);
+    }
+}
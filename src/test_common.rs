@@ -0,0 +1,11 @@
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+
+/// Connect a pair of loopback sockets, returned as `(client, server)`.
+pub(crate) fn socket_pair() -> (Arc<TcpStream>, Arc<TcpStream>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+    let client = TcpStream::connect(listener.local_addr().unwrap()).expect("Failed to connect");
+    let (server, _) = listener.accept().expect("Failed to accept");
+
+    (Arc::new(client), Arc::new(server))
+}
@@ -0,0 +1,130 @@
+use crate::{
+    CharacterFlags, LurkError, PktAccept, PktChangeRoom, PktCharacter, PktConnection, PktError,
+    PktFight, PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight, PktRoom, PktStart, PktType,
+    PktVersion, Protocol,
+};
+
+/// A small deterministic generator (SplitMix64), so the same seed gives the same packet on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.next() as u8
+    }
+
+    fn u16(&mut self) -> u16 {
+        self.next() as u16
+    }
+
+    /// Printable ASCII text of up to `max_len` bytes, so it survives the lossy UTF-8 decode unchanged.
+    fn text(&mut self, max_len: u64) -> String {
+        let len = self.below(max_len + 1);
+
+        (0..len)
+            .map(|_| (b' ' + self.below(95) as u8) as char)
+            .collect()
+    }
+}
+
+/// Generate a valid packet from `seed`, together with its serialized bytes.
+///
+/// The same seed always gives the same packet. Every generated packet decodes back to exactly the bytes returned,
+/// so the pair can seed a fuzz corpus or check serialization and deserialization against each other.
+///
+/// ```
+/// use lurk_protocol::Protocol;
+/// use lurk_protocol::testing::random_packet;
+///
+/// for seed in 0..100 {
+///     let (packet, bytes) = random_packet(seed);
+///     let (decoded, used) = Protocol::from_bytes(&bytes).unwrap();
+///
+///     assert_eq!(used, bytes.len());
+///     assert_eq!(decoded.encode_to_vec().unwrap(), bytes);
+///     assert_eq!(packet.packet_type(), decoded.packet_type());
+/// }
+/// ```
+pub fn random_packet(seed: u64) -> (Protocol, Vec<u8>) {
+    let mut rng = Rng(seed);
+
+    let packet = match rng.below(14) {
+        0 => {
+            let message = rng.text(200);
+
+            let packet = if rng.below(2) == 0 {
+                PktMessage::narrator(&rng.text(32), &message)
+            } else {
+                PktMessage::player(&rng.text(32), &rng.text(32), &message)
+            };
+
+            Protocol::Message(packet)
+        }
+        1 => Protocol::ChangeRoom(PktChangeRoom::new(rng.u16())),
+        2 => Protocol::Fight(PktFight::new()),
+        3 => Protocol::PVPFight(PktPVPFight::new(&rng.text(32))),
+        4 => Protocol::Loot(PktLoot::new(&rng.text(32))),
+        5 => Protocol::Start(PktStart::new()),
+        6 => Protocol::Error(PktError::new(
+            LurkError::from(rng.below(9) as u8),
+            &rng.text(100),
+        )),
+        7 => Protocol::Accept(PktAccept::new(PktType::from(rng.below(14) as u8 + 1))),
+        8 => Protocol::Room(PktRoom::new(rng.u16(), &rng.text(32), &rng.text(200))),
+        9 => Protocol::Character(PktCharacter {
+            flags: CharacterFlags::from_bits_truncate(rng.u8()),
+            attack: rng.u16(),
+            defense: rng.u16(),
+            regen: rng.u16(),
+            health: rng.u16() as i16,
+            gold: rng.u16(),
+            current_room: rng.u16(),
+            ..PktCharacter::new(&rng.text(32), &rng.text(200))
+        }),
+        10 => Protocol::Game(PktGame::new(rng.u16(), rng.u16(), &rng.text(200))),
+        11 => Protocol::Leave(PktLeave::new()),
+        12 => Protocol::Connection(PktConnection::new(rng.u16(), &rng.text(32), &rng.text(200))),
+        _ => Protocol::Version(PktVersion::new(rng.u8(), rng.u8())),
+    };
+
+    let bytes = packet
+        .encode_to_vec()
+        .expect("Generated packets always serialize");
+
+    (packet, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generation is deterministic and every packet round-trips through the decoder.
+    #[test]
+    fn testing_random_packet_round_trip() {
+        let mut seen = std::collections::HashSet::new();
+
+        for seed in 0..500 {
+            let (packet, bytes) = random_packet(seed);
+            let (decoded, used) = Protocol::from_bytes(&bytes).expect("Decode failed");
+
+            assert_eq!(random_packet(seed).1, bytes);
+            assert_eq!(used, bytes.len());
+            assert_eq!(decoded.encode_to_vec().ok(), Some(bytes));
+            seen.insert(packet.packet_type());
+        }
+
+        assert_eq!(seen.len(), 14);
+    }
+}
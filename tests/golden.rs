@@ -0,0 +1,176 @@
+//! Compare the serialized bytes of representative packets against committed golden files in `tests/golden/`.
+//!
+//! Any change to the wire format fails here. If the change is intended, regenerate the files with
+//! `BLESS=1 cargo test --test golden` and commit them.
+
+use std::path::PathBuf;
+
+use lurk_protocol::{
+    CharacterFlags, LurkError, Parser, PktAccept, PktChangeRoom, PktCharacter, PktConnection,
+    PktError, PktFight, PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight, PktRoom, PktStart,
+    PktType, PktVersion,
+};
+
+/// Check `packet` against `tests/golden/<name>.bin`, or rewrite the file when `BLESS` is set.
+fn check<'a>(name: &str, packet: &impl Parser<'a>) {
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "tests",
+        "golden",
+        &format!("{name}.bin"),
+    ]
+    .iter()
+    .collect();
+    let bytes = packet.encode_to_vec().expect("Encoding failed");
+
+    if std::env::var_os("BLESS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create golden directory");
+        std::fs::write(&path, &bytes).expect("Failed to write golden file");
+        return;
+    }
+
+    let golden = std::fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "Missing golden file {}: {}; run with BLESS=1 to create it",
+            path.display(),
+            e
+        )
+    });
+
+    assert_eq!(
+        bytes, golden,
+        "Wire format of {} changed; run with BLESS=1 if this is intended",
+        name
+    );
+}
+
+#[test]
+fn golden_message() {
+    check(
+        "message",
+        &PktMessage::player("Alice", "Bob", "Hello, Bob!"),
+    );
+    check(
+        "message_narration",
+        &PktMessage::narrator("Bob", "A cold wind blows."),
+    );
+}
+
+#[test]
+fn golden_change_room() {
+    check("change_room", &PktChangeRoom::from(513u16));
+}
+
+#[test]
+fn golden_fight() {
+    check("fight", &PktFight::default());
+}
+
+#[test]
+fn golden_pvp_fight() {
+    check("pvp_fight", &PktPVPFight::new("Bob"));
+}
+
+#[test]
+fn golden_loot() {
+    check("loot", &PktLoot::new("Goblin"));
+}
+
+#[test]
+fn golden_start() {
+    check("start", &PktStart::default());
+}
+
+#[test]
+fn golden_error() {
+    check(
+        "error",
+        &PktError::new(LurkError::STATERROR, "Too many stat points."),
+    );
+}
+
+#[test]
+fn golden_accept() {
+    check("accept", &PktAccept::new(PktType::CHARACTER));
+}
+
+#[test]
+fn golden_room() {
+    check(
+        "room",
+        &PktRoom {
+            packet_type: PktType::ROOM,
+            room_number: 1,
+            room_name: "Entrance".into(),
+            description_len: 21,
+            description: "A torchlit stone arch".into(),
+        },
+    );
+}
+
+#[test]
+fn golden_character() {
+    check(
+        "character",
+        &PktCharacter {
+            packet_type: PktType::CHARACTER,
+            name: "Glorfindel".into(),
+            flags: CharacterFlags::alive(),
+            attack: 30,
+            defense: 40,
+            regen: 30,
+            health: -2,
+            gold: 1000,
+            current_room: 258,
+            description_len: 17,
+            description: "An elf of renown.".into(),
+        },
+    );
+}
+
+#[test]
+fn golden_game() {
+    check(
+        "game",
+        &PktGame {
+            packet_type: PktType::GAME,
+            initial_points: 100,
+            stat_limit: 65535,
+            description_len: 11,
+            description: "Deep Caves.".into(),
+        },
+    );
+}
+
+#[test]
+fn golden_leave() {
+    check("leave", &PktLeave::default());
+}
+
+#[test]
+fn golden_connection() {
+    check(
+        "connection",
+        &PktConnection {
+            packet_type: PktType::CONNECTION,
+            room_number: 2,
+            room_name: "Hallway".into(),
+            description_len: 10,
+            description: "Long, dim.".into(),
+        },
+    );
+}
+
+#[test]
+fn golden_version() {
+    check(
+        "version",
+        &PktVersion {
+            packet_type: PktType::VERSION,
+            major_rev: 2,
+            minor_rev: 3,
+            extensions_len: 0,
+            extensions: None,
+        },
+    );
+}
@@ -0,0 +1,163 @@
+//! Feed every packet type one byte short of complete and check that decoding reports it instead of panicking.
+//!
+//! Each variable-length type is checked twice: with its text empty, so the missing byte falls in the fixed part
+//! of the body, and with text, so it falls in the trailing data sized by the length field. Either way the error
+//! asks for exactly the one missing byte of the full frame.
+
+use std::io::ErrorKind;
+
+use lurk_protocol::{
+    Incomplete, LurkError, Parser, PktAccept, PktChangeRoom, PktCharacter, PktConnection, PktError,
+    PktFight, PktGame, PktLeave, PktLoot, PktMessage, PktPVPFight, PktRoom, PktStart, PktType,
+    PktVersion, Protocol, ProtocolRef,
+};
+
+/// Check that `packet`, missing its last byte, fails every decoder as truncated.
+fn check<'a, P>(packet: &P)
+where
+    P: Parser<'a> + for<'b> TryFrom<&'b [u8], Error = std::io::Error>,
+{
+    let bytes = packet.encode_to_vec().expect("Encoding failed");
+    let truncated = &bytes[..bytes.len() - 1];
+    let name = PktType::from(bytes[0]);
+
+    let (_, frame_len) = Protocol::from_bytes(&bytes).expect("Complete packet must decode");
+    assert_eq!(frame_len, bytes.len(), "{}", name);
+
+    let err = Protocol::from_bytes(truncated)
+        .err()
+        .unwrap_or_else(|| panic!("{} decoded from a truncated buffer", name));
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "{}", name);
+    assert_eq!(
+        Incomplete::from_error(&err).map(|i| truncated.len() + i.needed),
+        Some(frame_len),
+        "{}",
+        name
+    );
+    assert_eq!(
+        Incomplete::from_error(&err).map(|i| i.needed),
+        Some(1),
+        "{}",
+        name
+    );
+
+    let err = ProtocolRef::from_bytes(truncated)
+        .err()
+        .unwrap_or_else(|| panic!("{} borrowed from a truncated buffer", name));
+    assert_eq!(
+        Incomplete::from_error(&err).map(|i| i.needed),
+        Some(1),
+        "{}",
+        name
+    );
+
+    let err = P::try_from(truncated)
+        .err()
+        .unwrap_or_else(|| panic!("{} parsed from a truncated buffer", name));
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "{}", name);
+}
+
+#[test]
+fn truncated_message() {
+    check(&PktMessage::player("Alice", "Bob", ""));
+}
+
+#[test]
+fn truncated_change_room() {
+    check(&PktChangeRoom::new(1));
+}
+
+#[test]
+fn truncated_fight() {
+    check(&PktFight::new());
+}
+
+#[test]
+fn truncated_pvp_fight() {
+    check(&PktPVPFight::new("Bob"));
+}
+
+#[test]
+fn truncated_loot() {
+    check(&PktLoot::new("Goblin"));
+}
+
+#[test]
+fn truncated_start() {
+    check(&PktStart::new());
+}
+
+#[test]
+fn truncated_error() {
+    check(&PktError::new(LurkError::OTHER, ""));
+}
+
+#[test]
+fn truncated_accept() {
+    check(&PktAccept::new(PktType::START));
+}
+
+#[test]
+fn truncated_room() {
+    check(&PktRoom::new(1, "Entrance", ""));
+}
+
+#[test]
+fn truncated_character() {
+    check(&PktCharacter::new("Hero", ""));
+}
+
+#[test]
+fn truncated_game() {
+    check(&PktGame::new(100, 200, ""));
+}
+
+#[test]
+fn truncated_leave() {
+    check(&PktLeave::new());
+}
+
+#[test]
+fn truncated_connection() {
+    check(&PktConnection::new(2, "Hallway", ""));
+}
+
+#[test]
+fn truncated_version() {
+    check(&PktVersion::new(2, 3));
+}
+
+#[test]
+fn truncated_message_text() {
+    check(&PktMessage::player("Alice", "Bob", "Hello there"));
+}
+
+#[test]
+fn truncated_error_text() {
+    check(&PktError::new(LurkError::BADROOM, "No such room"));
+}
+
+#[test]
+fn truncated_room_text() {
+    check(&PktRoom::new(1, "Entrance", "A draughty hall."));
+}
+
+#[test]
+fn truncated_character_text() {
+    check(&PktCharacter::new("Hero", "Brave and bold."));
+}
+
+#[test]
+fn truncated_game_text() {
+    check(&PktGame::new(100, 200, "A maze of twisty caves."));
+}
+
+#[test]
+fn truncated_connection_text() {
+    check(&PktConnection::new(2, "Hallway", "Long and narrow."));
+}
+
+#[test]
+fn truncated_version_text() {
+    check(&PktVersion::with_extensions(2, 3, [b"SEQ".as_slice(), b"OK"]).expect("Extensions fit"));
+}
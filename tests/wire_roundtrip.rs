@@ -0,0 +1,189 @@
+//! Send each variable-length packet over a real socket and check that `Protocol::recv` yields the same packet.
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+
+use lurk_protocol::{
+    CharacterFlags, ExtensionSet, LurkError, Parser, PktCharacter, PktConnection, PktError,
+    PktGame, PktMessage, PktRoom, PktType, PktVersion, Protocol, send_to,
+};
+
+/// Connect a pair of loopback sockets, returned as `(client, server)`.
+fn socket_pair() -> (Arc<TcpStream>, Arc<TcpStream>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+    let client = TcpStream::connect(listener.local_addr().unwrap()).expect("Failed to connect");
+    let (server, _) = listener.accept().expect("Failed to accept");
+
+    (Arc::new(client), Arc::new(server))
+}
+
+/// Send `packet` from one end, receive it on the other, and return both encodings for comparison.
+fn roundtrip<'a, P, F>(packet: &P, unwrap: F) -> (Vec<u8>, Vec<u8>)
+where
+    P: Parser<'a> + std::fmt::Display,
+    F: FnOnce(Protocol) -> Option<Vec<u8>>,
+{
+    let (client, server) = socket_pair();
+
+    send_to(&client, packet).expect("Send failed");
+    let received = Protocol::recv(&server).expect("Recv failed");
+
+    let sent = packet.encode_to_vec().expect("Encoding failed");
+    let received = unwrap(received).expect("Received the wrong packet type");
+
+    (sent, received)
+}
+
+#[test]
+fn wire_message() {
+    let packet = PktMessage::player("Alice", "Bob", "Meet me in the tavern.");
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_message().ok().and_then(|m| m.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_message_narration() {
+    let packet = PktMessage::narrator("Bob", "The torches flicker.");
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_message().ok().and_then(|m| m.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_room() {
+    let packet = PktRoom {
+        packet_type: PktType::ROOM,
+        room_number: 42,
+        room_name: "Great Hall".into(),
+        description_len: 27,
+        description: "A vaulted, echoing chamber.".into(),
+    };
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_room().ok().and_then(|r| r.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_character() {
+    let packet = PktCharacter {
+        packet_type: PktType::CHARACTER,
+        name: "Glorfindel".into(),
+        flags: CharacterFlags::alive(),
+        attack: 30,
+        defense: 40,
+        regen: 30,
+        health: -5,
+        gold: 120,
+        current_room: 3,
+        description_len: 17,
+        description: "An elf of renown.".into(),
+    };
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_character().ok().and_then(|c| c.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_game() {
+    let packet = PktGame {
+        packet_type: PktType::GAME,
+        initial_points: 100,
+        stat_limit: 65535,
+        description_len: 19,
+        description: "Welcome to the maze".into(),
+    };
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_game().ok().and_then(|g| g.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_error() {
+    let packet = PktError::new(LurkError::BADROOM, "No such room.");
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_error().ok().and_then(|e| e.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_connection() {
+    let packet = PktConnection {
+        packet_type: PktType::CONNECTION,
+        room_number: 7,
+        room_name: "Cellar".into(),
+        description_len: 14,
+        description: "Damp and dark.".into(),
+    };
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_connection()
+            .ok()
+            .and_then(|c| c.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+#[test]
+fn wire_version() {
+    let packet = PktVersion {
+        packet_type: PktType::VERSION,
+        major_rev: 2,
+        minor_rev: 3,
+        extensions_len: 0,
+        extensions: None,
+    };
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_version().ok().and_then(|v| v.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}
+
+/// The extension list sent in `VERSION` survives the trip, not just its length.
+#[test]
+fn wire_version_extensions() {
+    let packet = [&b"SEQ"[..], &b"compress"[..]]
+        .into_iter()
+        .collect::<ExtensionSet>()
+        .to_version(2, 3)
+        .expect("Encoding failed");
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_version().ok().and_then(|v| v.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+
+    let received = PktVersion::try_from(received.as_slice()).expect("Parse failed");
+    assert_eq!(received.extension_count(), 2);
+    assert_eq!(received.extension(1), Some(&b"compress"[..]));
+}
+
+/// Empty descriptions are the smallest variable-length bodies and must not stall `recv`.
+#[test]
+fn wire_empty_descriptions() {
+    let packet = PktRoom {
+        packet_type: PktType::ROOM,
+        room_number: 1,
+        room_name: "Void".into(),
+        description_len: 0,
+        description: "".into(),
+    };
+    let (sent, received) = roundtrip(&packet, |p| {
+        p.into_room().ok().and_then(|r| r.encode_to_vec().ok())
+    });
+
+    assert_eq!(sent, received);
+}